@@ -0,0 +1,31 @@
+//! How long it takes to give a request its `I18n`, which shares the catalog of its language
+//! instead of copying it.
+
+use actix_web::FromRequest;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use futures_01::Future;
+use rocket_i18n::{testing, Catalog, CatalogBuilder, I18n, Translations};
+
+/// A catalog with `len` messages, about the size of the catalog of a real application.
+fn catalog(len: usize) -> Catalog {
+    (0..len)
+        .fold(CatalogBuilder::new(), |builder, i| {
+            builder.msg(
+                &format!("Message number {} of the application", i),
+                &format!("Message numéro {} de l'application", i),
+            )
+        })
+        .build()
+}
+
+fn extract(c: &mut Criterion) {
+    let catalog = catalog(5_000);
+    let translations = Translations::from(vec![("en", catalog.clone()), ("fr", catalog.clone())]);
+    let req = testing::actix_request(translations, "fr-FR, fr;q=0.9").to_http_request();
+
+    c.bench_function("extract I18n", |b| b.iter(|| I18n::extract(black_box(&req)).wait().unwrap()));
+    c.bench_function("clone a catalog", |b| b.iter(|| black_box(&catalog).clone()));
+}
+
+criterion_group!(benches, extract);
+criterion_main!(benches);
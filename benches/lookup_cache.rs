@@ -0,0 +1,51 @@
+//! How long it takes to look up messages that only the last language of a fallback chain
+//! translates, with and without the lookup cache.
+
+use actix_web::FromRequest;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use futures_01::Future;
+use rocket_i18n::{testing, Catalog, CatalogBuilder, I18n, Translations};
+
+/// A catalog translating messages `from..to` (and nothing else).
+fn catalog(from: usize, to: usize) -> Catalog {
+    (from..to)
+        .fold(CatalogBuilder::new(), |builder, i| {
+            builder.msg(
+                &format!("Message number {} of the application", i),
+                &format!("Translation number {} of the application", i),
+            )
+        })
+        .build()
+}
+
+fn lookup(c: &mut Criterion) {
+    // gl, pt and es translate a part of the messages each, only en translates the hot ones
+    let translations = Translations::from(vec![
+        ("en", catalog(0, 5_000)),
+        ("es", catalog(1_000, 4_000)),
+        ("pt", catalog(2_000, 4_000)),
+        ("gl", catalog(3_000, 4_000)),
+    ])
+    .with_fallback_chain("gl", &["pt", "es", "en"]);
+    let hot: Vec<_> = (0..100)
+        .map(|i| format!("Message number {} of the application", i))
+        .collect();
+
+    let mut group = c.benchmark_group("lookup through a 3-deep fallback chain");
+    let uncached = translations.clone().with_lookup_cache_size(0);
+    for (name, translations) in [("cached", translations), ("uncached", uncached)] {
+        let req = testing::actix_request(translations, "gl").to_http_request();
+        let i18n = I18n::extract(&req).wait().unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for msg_id in &hot {
+                    black_box(i18n.catalog.gettext(black_box(msg_id)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup);
+criterion_main!(benches);
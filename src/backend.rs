@@ -0,0 +1,291 @@
+//! Catalogs loaded from a database, or from any other storage.
+
+use std::{error::Error, future::Future, pin::Pin};
+
+#[cfg(feature = "gettext")]
+use crate::ReloadableTranslations;
+use crate::{english_plural, Catalog, CatalogBuilder, Translations};
+
+/// The errors of a `CatalogBackend`.
+pub type BackendError = Box<dyn Error + Send + Sync>;
+
+/// The future returned by the methods of a `CatalogBackend`.
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, BackendError>> + Send + 'a>>;
+
+/// A message and its translations, as stored by a `CatalogBackend`.
+#[derive(Clone, Debug, Default)]
+pub struct StoredMessage {
+    /// The context of the message (`msgctxt`), if any.
+    pub context: Option<String>,
+    /// The message itself (`msgid`).
+    pub id: String,
+    /// The plural form of the message (`msgid_plural`), if it has one.
+    pub plural: Option<String>,
+    /// The translations of the message: one for each plural form of the language if it has a
+    /// plural form, and a single one otherwise.
+    pub translations: Vec<String>,
+}
+
+/// A storage of translations, other than `.mo` files: a table edited by translators in an admin
+/// interface, for instance.
+///
+/// ```rust,ignore
+/// struct Api(Client);
+///
+/// impl CatalogBackend for Api {
+///     fn messages<'a>(&'a self, lang: &'a str) -> BackendFuture<'a, Vec<StoredMessage>> {
+///         Box::pin(async move { Ok(self.0.messages(lang).await?) })
+///     }
+/// }
+///
+/// let translations = rocket_i18n::i18n_backend(&Api(client), vec!["en", "fr"]).await?;
+/// ```
+///
+/// With the `postgres` feature, `PostgresBackend` reads them from a PostgreSQL table.
+///
+/// Requires the `backend` feature.
+pub trait CatalogBackend: Send + Sync {
+    /// The translated messages of a language.
+    fn messages<'a>(&'a self, lang: &'a str) -> BackendFuture<'a, Vec<StoredMessage>>;
+
+    /// The function choosing the plural form to use for a number in a language (see
+    /// `CatalogBuilder::plural_rule`). It is the English rule (`n != 1`) by default.
+    fn plural_rule(&self, _lang: &str) -> fn(u64) -> usize {
+        english_plural
+    }
+}
+
+/// Builds the catalog of a language from the messages of a backend.
+///
+/// Requires the `backend` feature.
+pub async fn catalog_from_backend<B: CatalogBackend + ?Sized>(
+    backend: &B,
+    lang: &str,
+) -> Result<Catalog, BackendError> {
+    let builder =
+        backend
+            .messages(lang)
+            .await?
+            .into_iter()
+            .fold(CatalogBuilder::new(), |builder, msg| {
+                let translations = msg
+                    .translations
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                builder.entry(
+                    msg.context.as_deref(),
+                    &msg.id,
+                    msg.plural.as_deref(),
+                    &translations,
+                )
+            });
+    Ok(builder.plural_rule(backend.plural_rule(lang)).build())
+}
+
+/// Loads the catalogs of `langs` from a backend, when the application starts.
+///
+/// Requires the `backend` feature.
+pub async fn i18n_backend<B: CatalogBackend + ?Sized>(
+    backend: &B,
+    langs: Vec<&'static str>,
+) -> Result<Translations, BackendError> {
+    let mut catalogs = Vec::with_capacity(langs.len());
+    for lang in langs {
+        catalogs.push((lang, catalog_from_backend(backend, lang).await?));
+    }
+    Ok(catalogs.into())
+}
+
+/// Loads the catalog of a language from a backend again, and swaps it into `translations`, once
+/// translators edited it for instance.
+///
+/// If it can't be loaded, the translations are left untouched.
+///
+/// Requires the `backend` and `gettext` features.
+#[cfg(feature = "gettext")]
+pub async fn reload_from_backend<B: CatalogBackend + ?Sized>(
+    backend: &B,
+    translations: &ReloadableTranslations,
+    lang: &'static str,
+) -> Result<(), BackendError> {
+    let catalog = catalog_from_backend(backend, lang).await?;
+    translations.update(&Translations::from(vec![(lang, catalog)]));
+    Ok(())
+}
+
+/// Reads translations from a PostgreSQL table, with one row for each translation:
+///
+/// ```sql
+/// CREATE TABLE translations (
+///     lang TEXT NOT NULL,
+///     context TEXT,
+///     msgid TEXT NOT NULL,
+///     msgid_plural TEXT,
+///     form INTEGER NOT NULL DEFAULT 0,
+///     msgstr TEXT NOT NULL
+/// );
+/// ```
+///
+/// Messages with a plural form have a row for each form, numbered by `form`, starting from `0`.
+///
+/// ```rust,ignore
+/// let backend = PostgresBackend::new(pool);
+/// let translations = rocket_i18n::i18n_backend(&backend, vec!["en", "fr"]).await?;
+/// ```
+///
+/// Requires the `postgres` feature.
+#[cfg(feature = "postgres")]
+#[derive(Clone)]
+pub struct PostgresBackend {
+    pool: sqlx::PgPool,
+    table: String,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresBackend {
+    /// Reads the `translations` table.
+    pub fn new(pool: sqlx::PgPool) -> PostgresBackend {
+        PostgresBackend {
+            pool,
+            table: "translations".to_owned(),
+        }
+    }
+
+    /// Reads another table, with the same columns. Its name is inserted in the query as is, so
+    /// it must not come from the users of the application.
+    pub fn table<S: Into<String>>(mut self, table: S) -> PostgresBackend {
+        self.table = table.into();
+        self
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl CatalogBackend for PostgresBackend {
+    fn messages<'a>(&'a self, lang: &'a str) -> BackendFuture<'a, Vec<StoredMessage>> {
+        Box::pin(async move {
+            let query = format!(
+                "SELECT context, msgid, msgid_plural, msgstr FROM {} WHERE lang = $1 \
+                 ORDER BY context, msgid, form",
+                self.table
+            );
+            let rows: Vec<(Option<String>, String, Option<String>, String)> =
+                sqlx::query_as(&query)
+                    .bind(lang)
+                    .fetch_all(&self.pool)
+                    .await?;
+
+            // Rows are sorted, so the forms of a message follow each other
+            let mut messages: Vec<StoredMessage> = Vec::new();
+            for (context, id, plural, translation) in rows {
+                match messages.last_mut() {
+                    Some(last) if last.context == context && last.id == id => {
+                        last.translations.push(translation)
+                    }
+                    _ => messages.push(StoredMessage {
+                        context,
+                        id,
+                        plural,
+                        translations: vec![translation],
+                    }),
+                }
+            }
+            Ok(messages)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Translations stored in memory, in French and Polish.
+    struct Memory(Mutex<Vec<(&'static str, StoredMessage)>>);
+
+    impl CatalogBackend for Memory {
+        fn messages<'a>(&'a self, lang: &'a str) -> BackendFuture<'a, Vec<StoredMessage>> {
+            Box::pin(async move {
+                let messages = self.0.lock().unwrap();
+                if !messages.iter().any(|(l, _)| *l == lang) {
+                    return Err(format!("no translations for {}", lang).into());
+                }
+                Ok(messages
+                    .iter()
+                    .filter(|(l, _)| *l == lang)
+                    .map(|(_, msg)| msg.clone())
+                    .collect())
+            })
+        }
+
+        fn plural_rule(&self, lang: &str) -> fn(u64) -> usize {
+            match lang {
+                "fr" => |n| if n > 1 { 1 } else { 0 },
+                _ => english_plural,
+            }
+        }
+    }
+
+    fn message(
+        context: Option<&str>,
+        id: &str,
+        plural: Option<&str>,
+        translations: &[&str],
+    ) -> StoredMessage {
+        StoredMessage {
+            context: context.map(str::to_owned),
+            id: id.to_owned(),
+            plural: plural.map(str::to_owned),
+            translations: translations.iter().map(|&t| t.to_owned()).collect(),
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn backend() -> Memory {
+        Memory(Mutex::new(vec![
+            ("fr", message(None, "Hello", None, &["Bonjour"])),
+            ("fr", message(Some("menu"), "Open", None, &["Ouvrir"])),
+            ("fr", message(None, "One post", Some("{0} posts"), &["Un billet", "{0} billets"])),
+            ("en", message(None, "Hello", None, &["Hi"])),
+        ]))
+    }
+
+    #[test]
+    fn catalogs_are_built_from_the_stored_messages() {
+        let translations = block_on(i18n_backend(&backend(), vec!["fr", "en"])).unwrap();
+        assert_eq!(translations.all_langs().copied().collect::<Vec<_>>(), vec!["fr", "en"]);
+        let fr = translations.get("fr").unwrap();
+        assert_eq!(fr.gettext("Hello"), "Bonjour");
+        assert_eq!(fr.pgettext("menu", "Open"), "Ouvrir");
+        assert_eq!(fr.gettext("Open"), "Open");
+        assert_eq!(fr.ngettext("One post", "{0} posts", 0), "Un billet");
+        assert_eq!(fr.ngettext("One post", "{0} posts", 2), "{0} billets");
+        assert_eq!(translations.get("en").unwrap().gettext("Hello"), "Hi");
+
+        let error = block_on(i18n_backend(&backend(), vec!["fr", "de"])).err().unwrap();
+        assert_eq!(error.to_string(), "no translations for de");
+    }
+
+    #[test]
+    #[cfg(feature = "gettext")]
+    fn catalogs_are_reloaded_from_the_backend() {
+        let backend = backend();
+        let translations = block_on(i18n_backend(&backend, vec!["fr", "en"])).unwrap();
+        let translations = ReloadableTranslations::from_translations("app", translations);
+        backend.0.lock().unwrap()[0].1 = message(None, "Hello", None, &["Salut"]);
+
+        block_on(reload_from_backend(&backend, &translations, "fr")).unwrap();
+        let snapshot = translations.snapshot();
+        assert_eq!(snapshot.get("fr").unwrap().gettext("Hello"), "Salut");
+        assert_eq!(snapshot.get("en").unwrap().gettext("Hello"), "Hi");
+
+        assert!(block_on(reload_from_backend(&backend, &translations, "de")).is_err());
+        assert_eq!(translations.snapshot().len(), 2);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{english_plural, Catalog};
+#[cfg(feature = "gettext")]
+use crate::{mo, ParseOptions};
+
+/// Builds a catalog from messages written in Rust, without any `.mo` file.
+///
+/// It is handy for examples, tests, or small applications with only a few messages.
+///
+/// ```rust,ignore
+/// let catalog = CatalogBuilder::new()
+///     .msg("Hello, world!", "Bonjour tout le monde !")
+///     .plural("One new message", "{0} new messages", &["Un nouveau message", "{0} nouveaux messages"])
+///     .msg_in_context("file menu", "Open", "Ouvrir")
+///     .plural_rule(|n| if n > 1 { 1 } else { 0 })
+///     .build();
+/// let translations = Translations::from(vec![("fr", catalog)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CatalogBuilder {
+    /// The messages, as in `.mo` files: the original string, with its context and plural form if
+    /// any, and its translations.
+    entries: Vec<Entry>,
+    plural: fn(u64) -> usize,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    context: Option<String>,
+    id: String,
+    #[cfg_attr(not(feature = "gettext"), allow(dead_code))]
+    plural: Option<String>,
+    translations: Vec<String>,
+}
+
+impl Default for CatalogBuilder {
+    fn default() -> CatalogBuilder {
+        CatalogBuilder::new()
+    }
+}
+
+impl CatalogBuilder {
+    /// Starts an empty catalog, using the English plural rule (`n != 1`).
+    pub fn new() -> CatalogBuilder {
+        CatalogBuilder {
+            entries: Vec::new(),
+            plural: english_plural,
+        }
+    }
+
+    /// Adds the translation of a message.
+    pub fn msg(self, msg_id: &str, translation: &str) -> CatalogBuilder {
+        self.entry(None, msg_id, None, &[translation])
+    }
+
+    /// Adds the translations of a message that has a plural form, one for each plural form of
+    /// the language (see `plural_rule`).
+    pub fn plural(
+        self,
+        msg_id: &str,
+        msg_id_plural: &str,
+        translations: &[&str],
+    ) -> CatalogBuilder {
+        self.entry(None, msg_id, Some(msg_id_plural), translations)
+    }
+
+    /// Adds the translation of a message in a given context (`msgctxt`).
+    pub fn msg_in_context(
+        self,
+        msg_context: &str,
+        msg_id: &str,
+        translation: &str,
+    ) -> CatalogBuilder {
+        self.entry(Some(msg_context), msg_id, None, &[translation])
+    }
+
+    /// Changes the function choosing the plural form to use for a number: it returns the index
+    /// of the translation to use.
+    pub fn plural_rule(mut self, plural: fn(u64) -> usize) -> CatalogBuilder {
+        self.plural = plural;
+        self
+    }
+
+    /// Builds the catalog. If a message was added more than once, its last translation is used.
+    #[cfg(feature = "gettext")]
+    pub fn build(self) -> Catalog {
+        let mut entries = self
+            .entries
+            .into_iter()
+            .map(|e| {
+                let mut original = match e.context {
+                    Some(ctx) => format!("{}\u{4}{}", ctx, e.id),
+                    None => e.id,
+                };
+                if let Some(plural) = e.plural {
+                    original.push('\0');
+                    original.push_str(&plural);
+                }
+                (original, e.translations.join("\0"))
+            })
+            .collect::<Vec<_>>();
+        // Only keep the last translation of each message
+        entries.reverse();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|a, b| a.0 == b.0);
+        ParseOptions::new()
+            .force_plural(self.plural)
+            .parse(&mo::write(&entries)[..])
+            .expect("Couldn't build catalog")
+    }
+
+    /// Builds the catalog. If a message was added more than once, its last translation is used.
+    #[cfg(not(feature = "gettext"))]
+    pub fn build(self) -> Catalog {
+        let mut catalog = Catalog::new();
+        catalog.set_plural_rule(self.plural);
+        for e in self.entries {
+            catalog.insert(
+                e.context.as_deref(),
+                &e.id,
+                e.translations,
+            );
+        }
+        catalog
+    }
+
+    pub(crate) fn entry(
+        mut self,
+        context: Option<&str>,
+        id: &str,
+        plural: Option<&str>,
+        translations: &[&str],
+    ) -> CatalogBuilder {
+        self.entries.push(Entry {
+            context: context.map(str::to_owned),
+            id: id.to_owned(),
+            plural: plural.map(str::to_owned),
+            translations: translations.iter().map(|t| (*t).to_owned()).collect(),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogs_are_built_from_messages() {
+        let catalog = CatalogBuilder::new()
+            .msg("Hello", "Salut")
+            .msg("Hello", "Bonjour")
+            .msg_in_context("file menu", "Open", "Ouvrir")
+            .plural("One file", "{0} files", &["Un fichier", "{0} fichiers"])
+            .build();
+        assert_eq!(catalog.gettext("Hello"), "Bonjour");
+        assert_eq!(catalog.pgettext("file menu", "Open"), "Ouvrir");
+        assert_eq!(catalog.gettext("Open"), "Open");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 1), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "{0} fichiers");
+        assert_eq!(catalog.gettext("Goodbye"), "Goodbye");
+        assert_eq!(CatalogBuilder::default().build().gettext("Hello"), "Hello");
+    }
+
+    #[test]
+    fn the_plural_rule_can_be_changed() {
+        let catalog = CatalogBuilder::new()
+            .plural("One file", "{0} files", &["Un fichier", "{0} fichiers"])
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 2), "{0} fichiers");
+    }
+}
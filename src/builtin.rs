@@ -0,0 +1,99 @@
+//! A minimal catalog, used instead of `gettext::Catalog` when the `gettext` feature is disabled.
+
+use std::collections::HashMap;
+
+use crate::english_plural;
+
+/// A catalog of translated messages, built in memory.
+///
+/// It has the same lookup methods as `gettext::Catalog`, so it works with `i18n!` and `t!`, but
+/// it can't be loaded from `.mo` files.
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    /// The translations of each message, indexed by `context\u{4}message` for messages with a
+    /// context, like in `.mo` files.
+    messages: HashMap<String, Vec<String>>,
+    plural: fn(u64) -> usize,
+}
+
+impl Default for Catalog {
+    fn default() -> Catalog {
+        Catalog::new()
+    }
+}
+
+impl Catalog {
+    /// Creates an empty catalog, using the English plural rule (`n != 1`).
+    pub fn new() -> Catalog {
+        Catalog {
+            messages: HashMap::new(),
+            plural: english_plural,
+        }
+    }
+
+    /// Changes the function choosing the plural form to use for a number.
+    pub fn set_plural_rule(&mut self, plural: fn(u64) -> usize) {
+        self.plural = plural;
+    }
+
+    /// Adds the translation of a message. Messages with a plural form have one translation for
+    /// each form.
+    ///
+    /// The header entry of the catalog (`Language`, `Plural-Forms`, …) is the translation of
+    /// `""`, as in `.mo` files.
+    pub fn insert(&mut self, msg_context: Option<&str>, msg_id: &str, translations: Vec<String>) {
+        self.messages.insert(key(msg_context, msg_id), translations);
+    }
+
+    /// Translates a message.
+    pub fn gettext<'a>(&'a self, msg_id: &'a str) -> &'a str {
+        self.find(&key(None, msg_id), 0).unwrap_or(msg_id)
+    }
+
+    /// Translates a message that has a plural form, choosing the form to use for `n`.
+    pub fn ngettext<'a>(&'a self, msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+        self.find(&key(None, msg_id), (self.plural)(n))
+            .unwrap_or_else(|| untranslated(msg_id, msg_id_plural, n))
+    }
+
+    /// Translates a message in a given context.
+    pub fn pgettext<'a>(&'a self, msg_context: &'a str, msg_id: &'a str) -> &'a str {
+        self.find(&key(Some(msg_context), msg_id), 0)
+            .unwrap_or(msg_id)
+    }
+
+    /// Translates a message that has a plural form in a given context.
+    pub fn npgettext<'a>(
+        &'a self,
+        msg_context: &'a str,
+        msg_id: &'a str,
+        msg_id_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.find(&key(Some(msg_context), msg_id), (self.plural)(n))
+            .unwrap_or_else(|| untranslated(msg_id, msg_id_plural, n))
+    }
+
+    fn find(&self, key: &str, form: usize) -> Option<&str> {
+        self.messages
+            .get(key)
+            .and_then(|translations| translations.get(form))
+            .map(String::as_str)
+    }
+}
+
+fn key(msg_context: Option<&str>, msg_id: &str) -> String {
+    match msg_context {
+        Some(ctx) => format!("{}\u{4}{}", ctx, msg_id),
+        None => msg_id.to_owned(),
+    }
+}
+
+/// What gettext returns for untranslated messages: the singular for `1`, the plural otherwise.
+fn untranslated<'a>(msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+    if n == 1 {
+        msg_id
+    } else {
+        msg_id_plural
+    }
+}
@@ -0,0 +1,888 @@
+#[cfg(feature = "track-access")]
+use std::collections::HashSet;
+#[cfg(feature = "lookup-cache")]
+use std::sync::Mutex;
+#[cfg(any(feature = "lookup-cache", feature = "track-access"))]
+use std::{collections::hash_map::RandomState, hash::BuildHasher};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    ptr,
+    sync::{Arc, RwLock},
+};
+
+use crate::{Catalog, I18n, I18nContext};
+
+/// The catalog of the language of a request.
+///
+/// It has the same lookup methods as `gettext::Catalog` (so it works with `i18n!` and `t!`),
+/// and dereferences to the underlying catalog.
+///
+/// The underlying catalog is shared with `Translations`: creating (or cloning) an `I18nCatalog`
+/// never copies its messages.
+///
+/// If a fallback chain was declared for its language (see `Translations::with_fallback_chain`),
+/// messages that it doesn't translate are looked up in the catalogs of the chain, in order.
+/// Overlays (see `Translations::overlay`) are looked up before the catalog of the language.
+/// Dereferencing it only gives the catalog of the language itself.
+#[derive(Clone)]
+pub struct I18nCatalog {
+    pub(crate) catalog: Arc<Catalog>,
+    /// Catalogs overriding some messages of `catalog`, in order of precedence.
+    pub(crate) overlays: Vec<Arc<Catalog>>,
+    /// The catalogs of the fallback chain of the language, in order.
+    pub(crate) fallbacks: Vec<Arc<Catalog>>,
+    /// The language of the catalog, for `on_missing`.
+    pub(crate) lang: &'static str,
+    /// Called when no catalog translates a message, see `Translations::on_missing_translation`.
+    pub(crate) on_missing: Option<MissingHook>,
+    /// Remembers the translations of messages, when there are several catalogs to scan.
+    #[cfg(feature = "lookup-cache")]
+    pub(crate) lookups: Option<LookupCache>,
+    /// Isolates the number of elements of plural messages, for right-to-left languages.
+    pub(crate) isolated: Option<IsolatedCounts>,
+    /// Pseudo-translates every message, see `Translations::with_pseudo_locale`.
+    #[cfg(feature = "pseudo")]
+    pub(crate) pseudo: Option<crate::pseudo::Pseudo>,
+    #[cfg(feature = "track-access")]
+    pub(crate) accessed: AccessLog,
+}
+
+impl I18nCatalog {
+    /// Translates a message.
+    pub fn gettext<'a>(&'a self, msg_id: &'a str) -> &'a str {
+        self.record(None, msg_id);
+        self.translate(None, &[msg_id], None, |catalog| catalog.gettext(msg_id))
+    }
+
+    /// Translates a message that has a plural form, choosing the form to use for `n`.
+    ///
+    /// In right-to-left languages, `{0}` (where the number of elements is inserted) is wrapped in
+    /// Unicode bidi isolates (U+2068 and U+2069), so that the digits of the number are displayed
+    /// on the right side of the surrounding text, with `i18n!` and `t!` too.
+    pub fn ngettext<'a>(&'a self, msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+        self.record(None, msg_id);
+        let translation = self.translate(None, &[msg_id, msg_id_plural], Some(n), |catalog| {
+            catalog.ngettext(msg_id, msg_id_plural, n)
+        });
+        self.isolate_count(translation)
+    }
+
+    /// Translates a message in a given context.
+    pub fn pgettext<'a>(&'a self, msg_context: &'a str, msg_id: &'a str) -> &'a str {
+        self.record(Some(msg_context), msg_id);
+        self.translate(Some(msg_context), &[msg_id], None, |catalog| {
+            catalog.pgettext(msg_context, msg_id)
+        })
+    }
+
+    /// Translates a message that has a plural form in a given context. The number of elements is
+    /// isolated in right-to-left languages, like with `ngettext`.
+    pub fn npgettext<'a>(
+        &'a self,
+        msg_context: &'a str,
+        msg_id: &'a str,
+        msg_id_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.record(Some(msg_context), msg_id);
+        let originals = [msg_id, msg_id_plural];
+        let translation = self.translate(Some(msg_context), &originals, Some(n), |catalog| {
+            catalog.npgettext(msg_context, msg_id, msg_id_plural, n)
+        });
+        self.isolate_count(translation)
+    }
+
+    /// Wraps `{0}` in bidi isolates if the language is written from right to left.
+    fn isolate_count<'a>(&'a self, translation: &'a str) -> &'a str {
+        match &self.isolated {
+            Some(isolated) => isolated.isolate(translation),
+            None => translation,
+        }
+    }
+
+    /// Looks a message up in the overlays, then in the catalog, then in the fallback chain until
+    /// a catalog translates it.
+    ///
+    /// Catalogs return the very strings they were given (`originals`) for messages they don't
+    /// translate, which is how untranslated messages are told apart from translations that
+    /// happen to be identical.
+    fn translate<'a>(
+        &'a self,
+        msg_context: Option<&str>,
+        originals: &[&'a str],
+        n: Option<u64>,
+        lookup: impl Fn(&'a Catalog) -> &'a str,
+    ) -> &'a str {
+        let (source, translation) = self.resolve(msg_context, originals, n, &lookup);
+        #[cfg(feature = "pseudo")]
+        {
+            if let Some(pseudo) = &self.pseudo {
+                return pseudo.translate(translation);
+            }
+        }
+        if source == Source::Missing {
+            if let Some(on_missing) = &self.on_missing {
+                on_missing(self.lang, msg_context, originals[0]);
+            }
+        }
+        translation
+    }
+
+    /// Finds the translation of a message in the lookup cache, or in the catalogs (and then
+    /// remembers it).
+    #[cfg(feature = "lookup-cache")]
+    fn resolve<'a>(
+        &'a self,
+        msg_context: Option<&str>,
+        originals: &[&'a str],
+        n: Option<u64>,
+        lookup: &impl Fn(&'a Catalog) -> &'a str,
+    ) -> (Source, &'a str) {
+        let cache = match &self.lookups {
+            Some(cache) => cache,
+            None => return self.find(originals, lookup),
+        };
+        let key = (self.lang, msg_context, originals[0], n);
+        match cache.get(key, |source| self.catalog_of(source)) {
+            Some((Source::Missing, _)) => (Source::Missing, lookup(&self.catalog)),
+            Some((source, Some(translation))) => (source, translation),
+            _ => {
+                let (source, translation) = self.find(originals, lookup);
+                cache.insert(key, source, self.catalog_of(source), translation);
+                (source, translation)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "lookup-cache"))]
+    #[inline(always)]
+    fn resolve<'a>(
+        &'a self,
+        _: Option<&str>,
+        originals: &[&'a str],
+        _: Option<u64>,
+        lookup: &impl Fn(&'a Catalog) -> &'a str,
+    ) -> (Source, &'a str) {
+        self.find(originals, lookup)
+    }
+
+    /// Finds the catalog that translates a message, and its translation (the message itself if
+    /// no catalog translates it).
+    fn find<'a>(
+        &'a self,
+        originals: &[&'a str],
+        lookup: &impl Fn(&'a Catalog) -> &'a str,
+    ) -> (Source, &'a str) {
+        let translate = |catalog: &'a Catalog| {
+            let translation = lookup(catalog);
+            Some(translation).filter(|translation| {
+                !originals
+                    .iter()
+                    .any(|original| ptr::eq(*translation, *original))
+            })
+        };
+        if let Some(found) = self
+            .overlays
+            .iter()
+            .enumerate()
+            .find_map(|(i, catalog)| Some((Source::Overlay(i), translate(catalog)?)))
+        {
+            return found;
+        }
+        #[cfg(feature = "pseudo")]
+        {
+            // The pseudo-locale translates everything itself
+            if self.pseudo.is_some() {
+                return (Source::Catalog, lookup(&self.catalog));
+            }
+        }
+        if let Some(translation) = translate(&self.catalog) {
+            return (Source::Catalog, translation);
+        }
+        self.fallbacks
+            .iter()
+            .enumerate()
+            .find_map(|(i, catalog)| Some((Source::Fallback(i), translate(catalog)?)))
+            .unwrap_or_else(|| (Source::Missing, lookup(&self.catalog)))
+    }
+
+    /// The catalog in which a message was found.
+    #[cfg(feature = "lookup-cache")]
+    fn catalog_of(&self, source: Source) -> &Arc<Catalog> {
+        match source {
+            Source::Overlay(i) => &self.overlays[i],
+            Source::Catalog | Source::Missing => &self.catalog,
+            Source::Fallback(i) => &self.fallbacks[i],
+        }
+    }
+
+    #[cfg(feature = "track-access")]
+    fn record(&self, msg_context: Option<&str>, msg_id: &str) {
+        self.accessed.record(msg_context, msg_id)
+    }
+
+    #[cfg(not(feature = "track-access"))]
+    #[inline(always)]
+    fn record(&self, _: Option<&str>, _: &str) {}
+}
+
+/// Plural translations of right-to-left languages, with `{0}` wrapped in bidi isolates.
+///
+/// They are leaked, like pseudo-translations, and shared by every request: there is at most one
+/// for each plural form of each message.
+#[derive(Clone, Default)]
+pub(crate) struct IsolatedCounts(Arc<RwLock<HashMap<String, &'static str>>>);
+
+impl IsolatedCounts {
+    fn isolate<'a>(&self, translation: &'a str) -> &'a str {
+        if !translation.contains("{0}") {
+            return translation;
+        }
+        let isolated = self.0.read().ok().and_then(|m| m.get(translation).copied());
+        if let Some(isolated) = isolated {
+            return isolated;
+        }
+        let isolated: &'static str =
+            Box::leak(translation.replace("{0}", "\u{2068}{0}\u{2069}").into_boxed_str());
+        if let Ok(mut translations) = self.0.write() {
+            translations.insert(translation.to_owned(), isolated);
+        }
+        isolated
+    }
+}
+
+/// A function called with the language, the context and the id of a message that is not
+/// translated.
+pub(crate) type MissingHook = Arc<dyn Fn(&'static str, Option<&str>, &str) + Send + Sync>;
+
+/// The lookup methods used by `i18n!` and `t!`.
+///
+/// Every catalog type of this crate implements them (`gettext::Catalog`, or the built-in
+/// `Catalog` when the `gettext` feature is disabled, and `I18nCatalog`), so code that only
+/// translates messages can be generic over the catalog it uses.
+pub trait Lookup {
+    /// Translates a message.
+    fn gettext<'a>(&'a self, msg_id: &'a str) -> &'a str;
+
+    /// Translates a message that has a plural form, choosing the form to use for `n`.
+    fn ngettext<'a>(&'a self, msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str;
+
+    /// Translates a message in a given context.
+    fn pgettext<'a>(&'a self, msg_context: &'a str, msg_id: &'a str) -> &'a str;
+
+    /// Translates a message that has a plural form in a given context.
+    fn npgettext<'a>(
+        &'a self,
+        msg_context: &'a str,
+        msg_id: &'a str,
+        msg_id_plural: &'a str,
+        n: u64,
+    ) -> &'a str;
+}
+
+macro_rules! impl_lookup {
+    ($catalog:ty) => {
+        impl Lookup for $catalog {
+            fn gettext<'a>(&'a self, msg_id: &'a str) -> &'a str {
+                <$catalog>::gettext(self, msg_id)
+            }
+
+            fn ngettext<'a>(&'a self, msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+                <$catalog>::ngettext(self, msg_id, msg_id_plural, n)
+            }
+
+            fn pgettext<'a>(&'a self, msg_context: &'a str, msg_id: &'a str) -> &'a str {
+                <$catalog>::pgettext(self, msg_context, msg_id)
+            }
+
+            fn npgettext<'a>(
+                &'a self,
+                msg_context: &'a str,
+                msg_id: &'a str,
+                msg_id_plural: &'a str,
+                n: u64,
+            ) -> &'a str {
+                <$catalog>::npgettext(self, msg_context, msg_id, msg_id_plural, n)
+            }
+        }
+    };
+}
+
+impl_lookup!(Catalog);
+impl_lookup!(I18nCatalog);
+#[cfg(feature = "mmap")]
+impl_lookup!(crate::MappedCatalog);
+
+/// Where `t!` and `t_escaped!` find the catalog to use: anything that has one, like templates.
+///
+/// It is implemented for `I18n`, `I18nContext` and `I18nCatalog` (so templates dereferencing to
+/// `I18nContext` work out of the box). For your own structs, `impl_with_catalog!` implements it
+/// with one of their fields, even a nested one:
+///
+/// ```rust,ignore
+/// struct Index {
+///     page: Page, // page.i18n.catalog is an I18nCatalog
+/// }
+///
+/// rocket_i18n::impl_with_catalog!(Index, page.i18n.catalog);
+/// ```
+pub trait WithCatalog {
+    /// The type of the catalog.
+    type Catalog: Lookup;
+
+    /// The catalog to translate messages with.
+    fn catalog(&self) -> &Self::Catalog;
+}
+
+impl WithCatalog for I18nCatalog {
+    type Catalog = I18nCatalog;
+
+    fn catalog(&self) -> &I18nCatalog {
+        self
+    }
+}
+
+impl WithCatalog for I18n {
+    type Catalog = I18nCatalog;
+
+    fn catalog(&self) -> &I18nCatalog {
+        &self.catalog
+    }
+}
+
+impl WithCatalog for I18nContext {
+    type Catalog = I18nCatalog;
+
+    fn catalog(&self) -> &I18nCatalog {
+        &self.catalog
+    }
+}
+
+/// Implements `WithCatalog` for a struct, with its `catalog` field, or with the `I18nCatalog` at
+/// the given path (`i18n.catalog`).
+#[macro_export]
+macro_rules! impl_with_catalog {
+    ($ty:ty) => {
+        $crate::impl_with_catalog!($ty, catalog);
+    };
+    ($ty:ty, $( $field:ident ).+) => {
+        impl $crate::WithCatalog for $ty {
+            type Catalog = $crate::I18nCatalog;
+
+            fn catalog(&self) -> &$crate::I18nCatalog {
+                &self.$( $field ).+
+            }
+        }
+    };
+}
+
+impl Deref for I18nCatalog {
+    type Target = Catalog;
+
+    fn deref(&self) -> &Catalog {
+        &self.catalog
+    }
+}
+
+/// The catalog that translates a message, among the ones of an `I18nCatalog`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Source {
+    Overlay(usize),
+    Catalog,
+    Fallback(usize),
+    /// No catalog translates it.
+    Missing,
+}
+
+/// The number of translations `LookupCache` remembers by default, for all the languages.
+#[cfg(feature = "lookup-cache")]
+pub(crate) const LOOKUP_CACHE_SIZE: usize = 4096;
+
+/// The key of a translation in `LookupCache`: the language, the context, the id and the number
+/// of elements (for plural messages) of the message.
+#[cfg(feature = "lookup-cache")]
+type LookupKey<'k> = (&'static str, Option<&'k str>, &'k str, Option<u64>);
+
+/// Remembers the translations of the most recently used messages, so that the overlays and the
+/// fallback chain of a language are not scanned again for every lookup.
+///
+/// It is shared by all the requests, and `Translations` replaces it whenever its catalogs change
+/// (when they are reloaded, for instance).
+#[cfg(feature = "lookup-cache")]
+#[derive(Clone)]
+pub(crate) struct LookupCache(Arc<Mutex<Lru>>);
+
+#[cfg(feature = "lookup-cache")]
+impl Default for LookupCache {
+    fn default() -> LookupCache {
+        LookupCache::with_capacity(LOOKUP_CACHE_SIZE)
+    }
+}
+
+#[cfg(feature = "lookup-cache")]
+impl LookupCache {
+    pub fn with_capacity(capacity: usize) -> LookupCache {
+        LookupCache(Arc::new(Mutex::new(Lru {
+            capacity,
+            hasher: RandomState::new(),
+            index: HashMap::new(),
+            entries: Vec::new(),
+            head: NIL,
+            tail: NIL,
+        })))
+    }
+
+    /// The number of translations it can remember.
+    pub fn capacity(&self) -> usize {
+        self.0.lock().map_or(0, |lru| lru.capacity)
+    }
+
+    /// A new empty cache, with the same capacity.
+    pub fn emptied(&self) -> LookupCache {
+        LookupCache::with_capacity(self.capacity())
+    }
+
+    /// The source and the translation of a message, if they are cached (without translation for
+    /// messages that no catalog translates).
+    ///
+    /// `catalog_of` gives the catalogs of the `I18nCatalog` looking the message up: translations
+    /// are only returned if they were found in the very same catalogs, that keep them alive.
+    fn get<'a>(
+        &self,
+        key: LookupKey,
+        catalog_of: impl Fn(Source) -> &'a Arc<Catalog>,
+    ) -> Option<(Source, Option<&'a str>)> {
+        let mut lru = self.0.lock().ok()?;
+        let entry = lru.get(key)?;
+        let translation = match &entry.translation {
+            None => None,
+            Some((catalog, translation)) => {
+                let found_in = catalog_of(entry.source);
+                if !Arc::ptr_eq(catalog, found_in) {
+                    return None;
+                }
+                // SAFETY: the translation is a string of `catalog`, that `found_in` keeps alive
+                // for 'a, and catalogs are never modified once they are shared.
+                Some(unsafe { &*translation.0 })
+            }
+        };
+        Some((entry.source, translation))
+    }
+
+    /// Remembers the translation of a message, found in `catalog`.
+    fn insert(&self, key: LookupKey, source: Source, catalog: &Arc<Catalog>, translation: &str) {
+        let translation = match source {
+            // The message itself, that only lives as long as the lookup
+            Source::Missing => None,
+            _ => Some((catalog.clone(), CachedStr(translation))),
+        };
+        if let Ok(mut lru) = self.0.lock() {
+            lru.insert(key, source, translation);
+        }
+    }
+}
+
+/// A translation remembered by `LookupCache`, that points into the catalog stored next to it.
+#[cfg(feature = "lookup-cache")]
+struct CachedStr(*const str);
+
+// SAFETY: it is only dereferenced while its catalog is alive, and catalogs are immutable.
+#[cfg(feature = "lookup-cache")]
+unsafe impl Send for CachedStr {}
+
+/// No entry, at the ends of the list of `Lru`.
+#[cfg(feature = "lookup-cache")]
+const NIL: usize = usize::MAX;
+
+/// A least recently used cache, with its entries in a doubly linked list from the most to the
+/// least recently used one.
+///
+/// Entries are indexed by the hash of their key, so that looking a message up doesn't allocate:
+/// the key is only copied when it is inserted.
+#[cfg(feature = "lookup-cache")]
+struct Lru {
+    capacity: usize,
+    hasher: RandomState,
+    index: HashMap<u64, usize>,
+    entries: Vec<LruEntry>,
+    head: usize,
+    tail: usize,
+}
+
+#[cfg(feature = "lookup-cache")]
+struct LruEntry {
+    hash: u64,
+    lang: &'static str,
+    msg_context: Option<String>,
+    msg_id: String,
+    n: Option<u64>,
+    source: Source,
+    translation: Option<(Arc<Catalog>, CachedStr)>,
+    prev: usize,
+    next: usize,
+}
+
+#[cfg(feature = "lookup-cache")]
+impl Lru {
+    fn get(&mut self, (lang, msg_context, msg_id, n): LookupKey) -> Option<&LruEntry> {
+        let hash = self.hasher.hash_one((lang, msg_context, msg_id, n));
+        let i = *self.index.get(&hash)?;
+        let entry = &self.entries[i];
+        if entry.lang != lang
+            || entry.msg_context.as_deref() != msg_context
+            || entry.msg_id != msg_id
+            || entry.n != n
+        {
+            return None;
+        }
+        self.unlink(i);
+        self.push_front(i);
+        Some(&self.entries[i])
+    }
+
+    fn insert(
+        &mut self,
+        (lang, msg_context, msg_id, n): LookupKey,
+        source: Source,
+        translation: Option<(Arc<Catalog>, CachedStr)>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let hash = self.hasher.hash_one((lang, msg_context, msg_id, n));
+        let entry = LruEntry {
+            hash,
+            lang,
+            msg_context: msg_context.map(str::to_owned),
+            msg_id: msg_id.to_owned(),
+            n,
+            source,
+            translation,
+            prev: NIL,
+            next: NIL,
+        };
+        let i = match self.index.get(&hash) {
+            // The same key, or another one with the same hash, that it replaces
+            Some(&i) => {
+                self.unlink(i);
+                i
+            }
+            None if self.entries.len() < self.capacity => {
+                self.entries.push(entry);
+                let i = self.entries.len() - 1;
+                self.index.insert(hash, i);
+                self.push_front(i);
+                return;
+            }
+            None => {
+                let i = self.tail;
+                self.unlink(i);
+                self.index.remove(&self.entries[i].hash);
+                self.index.insert(hash, i);
+                i
+            }
+        };
+        self.entries[i] = entry;
+        self.push_front(i);
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (prev, next) = (self.entries[i].prev, self.entries[i].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        self.entries[i].prev = NIL;
+        self.entries[i].next = self.head;
+        match self.head {
+            NIL => self.tail = i,
+            head => self.entries[head].prev = i,
+        }
+        self.head = i;
+    }
+}
+
+/// The set of messages that were looked up, shared by all the requests.
+#[cfg(feature = "track-access")]
+#[derive(Clone, Default)]
+pub(crate) struct AccessLog(Arc<RwLock<AccessKeys>>);
+
+/// The keys of the messages that were looked up (`context\u{4}id`, like in `.mo` files), by
+/// hash, so that looking up a message that was already seen doesn't allocate its key.
+#[cfg(feature = "track-access")]
+#[derive(Default)]
+struct AccessKeys {
+    hasher: RandomState,
+    keys: HashMap<u64, Vec<String>>,
+}
+
+#[cfg(feature = "track-access")]
+impl AccessKeys {
+    fn contains(&self, hash: u64, msg_context: Option<&str>, msg_id: &str) -> bool {
+        self.keys.get(&hash).is_some_and(|keys| {
+            keys.iter().any(|key| match msg_context {
+                Some(ctx) => {
+                    key.len() == ctx.len() + 1 + msg_id.len()
+                        && key.starts_with(ctx)
+                        && key[ctx.len()..].starts_with('\u{4}')
+                        && key.ends_with(msg_id)
+                }
+                None => key == msg_id,
+            })
+        })
+    }
+}
+
+#[cfg(feature = "track-access")]
+impl AccessLog {
+    fn record(&self, msg_context: Option<&str>, msg_id: &str) {
+        // Most messages were already seen, so only take the write lock for new ones
+        let hash = match self.0.read() {
+            Ok(log) => {
+                let hash = log.hasher.hash_one((msg_context, msg_id));
+                if log.contains(hash, msg_context, msg_id) {
+                    return;
+                }
+                hash
+            }
+            Err(_) => return,
+        };
+        if let Ok(mut log) = self.0.write() {
+            if !log.contains(hash, msg_context, msg_id) {
+                let key = match msg_context {
+                    Some(ctx) => format!("{}\u{4}{}", ctx, msg_id),
+                    None => msg_id.to_owned(),
+                };
+                log.keys.entry(hash).or_default().push(key);
+            }
+        }
+    }
+
+    pub fn keys(&self) -> HashSet<String> {
+        self.0
+            .read()
+            .map(|log| log.keys.values().flatten().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogBuilder, Translations};
+    use std::sync::Mutex;
+
+    #[test]
+    #[cfg(feature = "lookup-cache")]
+    fn the_least_recently_used_translation_is_evicted() {
+        let catalog = Arc::new(CatalogBuilder::new().msg("a", "A").msg("b", "B").build());
+        let catalog_of = |_| &catalog;
+        let cache = LookupCache::with_capacity(2);
+        cache.insert(("fr", None, "a", None), Source::Catalog, &catalog, catalog.gettext("a"));
+        let b = ("fr", Some("menu"), "b", Some(2));
+        cache.insert(b, Source::Fallback(1), &catalog, catalog.gettext("b"));
+        let a = Some((Source::Catalog, Some("A")));
+        assert_eq!(cache.get(("fr", None, "a", None), catalog_of), a);
+        cache.insert(("en", None, "c", None), Source::Missing, &catalog, "c");
+
+        assert_eq!(cache.get(b, catalog_of), None);
+        assert_eq!(cache.get(("fr", None, "a", None), catalog_of), a);
+        assert_eq!(cache.get(("en", None, "c", None), catalog_of), Some((Source::Missing, None)));
+        assert_eq!(cache.get(("fr", None, "c", None), catalog_of), None);
+        assert_eq!(cache.get(("fr", None, "a", Some(1)), catalog_of), None);
+
+        // Translations are only given to the catalogs that hold them
+        let other = Arc::new(CatalogBuilder::new().build());
+        assert_eq!(cache.get(("fr", None, "a", None), |_| &other), None);
+    }
+
+    #[test]
+    #[cfg(feature = "lookup-cache")]
+    fn plural_forms_are_cached_for_each_count() {
+        let es = CatalogBuilder::new()
+            .plural("One file", "{0} files", &["Un archivo", "{0} archivos"])
+            .plural_rule(|n| if n == 1 { 0 } else { 1 })
+            .build();
+        let gl = CatalogBuilder::new().msg("Hello", "Ola").build();
+        let translations =
+            Translations::from(vec![("es", es), ("gl", gl)]).with_fallback_chain("gl", &["es"]);
+
+        let gl = translations.for_request("gl").unwrap();
+        assert!(gl.catalog.lookups.is_some());
+        for _ in 0..2 {
+            assert_eq!(gl.catalog.ngettext("One file", "{0} files", 1), "Un archivo");
+            assert_eq!(gl.catalog.ngettext("One file", "{0} files", 2), "{0} archivos");
+            assert_eq!(gl.catalog.gettext("Hello"), "Ola");
+            assert_eq!(gl.catalog.gettext("Bye"), "Bye");
+        }
+
+        let uncached = translations.with_lookup_cache_size(0);
+        assert!(uncached.for_request("gl").unwrap().catalog.lookups.is_none());
+    }
+
+    #[test]
+    fn lookups_are_resolved_again_after_a_reload() {
+        let fr = CatalogBuilder::new().msg("Hello", "Bonjour").build();
+        let en_gb = CatalogBuilder::new().msg("Bye", "Cheerio").build();
+        let translations = Translations::from(vec![("fr", fr), ("en-GB", en_gb)])
+            .with_fallback_chain("fr", &["en-GB"])
+            .overlay("fr", CatalogBuilder::new().msg("Hello", "Salut").build());
+
+        let before = translations.for_request("fr").unwrap();
+        for _ in 0..2 {
+            assert_eq!(before.catalog.gettext("Hello"), "Salut");
+            assert_eq!(before.catalog.gettext("Bye"), "Cheerio");
+            assert_eq!(before.catalog.gettext("Thanks"), "Thanks");
+        }
+
+        let mut reloaded = translations.clone();
+        let fr = CatalogBuilder::new().msg("Bye", "Au revoir").build();
+        reloaded.insert("fr", Arc::new(fr));
+        let after = reloaded.for_request("fr").unwrap();
+        assert_eq!(after.catalog.gettext("Bye"), "Au revoir");
+        assert_eq!(before.catalog.gettext("Bye"), "Cheerio");
+    }
+
+    #[test]
+    fn untranslated_messages_are_looked_up_in_the_fallback_chain() {
+        let gl = CatalogBuilder::new().msg("Hello", "Ola").build();
+        let es = CatalogBuilder::new()
+            .msg("Hello", "Hola")
+            .msg("Bye", "Adiós")
+            .plural("One file", "{0} files", &["Un archivo", "{0} archivos"])
+            .build();
+        let en = CatalogBuilder::new().msg("Bye", "Goodbye").msg("Thanks", "Cheers").build();
+        let translations = Translations::from(vec![("en", en), ("es", es), ("gl", gl)])
+            .with_fallback_chain("gl", &["pt", "es", "en"])
+            .with_fallback_chain("ast", &["es", "en"]);
+
+        let gl = translations.for_request("gl").unwrap();
+        assert_eq!(gl.catalog.gettext("Hello"), "Ola");
+        assert_eq!(gl.catalog.gettext("Bye"), "Adiós");
+        assert_eq!(gl.catalog.gettext("Thanks"), "Cheers");
+        assert_eq!(gl.catalog.gettext("Later"), "Later");
+        assert_eq!(gl.catalog.ngettext("One file", "{0} files", 2), "{0} archivos");
+
+        let ast = translations.for_request("ast").unwrap();
+        assert_eq!(ast.lang, "es");
+        assert_eq!(ast.catalog.gettext("Thanks"), "Cheers");
+
+        let es = translations.for_request("es").unwrap();
+        assert_eq!(es.catalog.gettext("Thanks"), "Thanks");
+    }
+
+    #[test]
+    fn overlays_override_some_messages() {
+        let fr = CatalogBuilder::new()
+            .msg("Welcome to Plume", "Bienvenue sur Plume")
+            .msg("Log in", "Se connecter")
+            .build();
+        let brand = |welcome| CatalogBuilder::new().msg("Welcome to Plume", welcome).build();
+        let translations = Translations::from(vec![("fr", fr)])
+            .overlay("fr", brand("Bienvenue sur Blog"))
+            .overlay("FR", brand("Bienvenue sur MonBlog"))
+            .overlay("de", brand("Willkommen"));
+
+        let fr = translations.for_request("fr").unwrap();
+        assert_eq!(fr.catalog.gettext("Welcome to Plume"), "Bienvenue sur MonBlog");
+        assert_eq!(fr.catalog.gettext("Log in"), "Se connecter");
+        assert_eq!(fr.catalog.gettext("Log out"), "Log out");
+        assert_eq!(fr.catalog.deref().gettext("Welcome to Plume"), "Bienvenue sur Plume");
+        assert!(translations.for_request("de").is_none());
+    }
+
+    #[test]
+    fn missing_translations_are_reported() {
+        let missing = Arc::new(Mutex::new(Vec::new()));
+        let reported = missing.clone();
+        let fr = CatalogBuilder::new().msg("Hello", "Bonjour").build();
+        let en = CatalogBuilder::new().msg("Bye", "Goodbye").build();
+        let translations = Translations::from(vec![("en", en), ("fr", fr)])
+            .with_fallback_chain("fr", &["en"])
+            .on_missing_translation(move |lang, msg_context, msg_id| {
+                let context = msg_context.map(str::to_owned);
+                reported.lock().unwrap().push((lang, context, msg_id.to_owned()));
+            });
+
+        let fr = translations.for_request("fr").unwrap();
+        fr.catalog.gettext("Hello");
+        fr.catalog.gettext("Bye");
+        fr.catalog.pgettext("menu", "Open");
+        fr.catalog.ngettext("One file", "{0} files", 2);
+        let missing = missing.lock().unwrap();
+        assert_eq!(
+            *missing,
+            vec![
+                ("fr", Some("menu".to_owned()), "Open".to_owned()),
+                ("fr", None, "One file".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn catalogs_can_be_found_in_any_field() {
+        struct Page {
+            i18n: I18n,
+        }
+        struct Index {
+            page: Page,
+        }
+        crate::impl_with_catalog!(Index, page.i18n.catalog);
+
+        /// What `t!` expands to, for any template.
+        fn hello<T: WithCatalog>(template: &T) -> &str {
+            template.catalog().gettext("Hello")
+        }
+
+        let catalog = || CatalogBuilder::new().msg("Hello", "Bonjour").build();
+        let index = Index {
+            page: Page {
+                i18n: I18n::new_for_tests("fr", catalog()),
+            },
+        };
+        assert_eq!(hello(&index), "Bonjour");
+        assert_eq!(hello(&index.page.i18n), "Bonjour");
+        assert_eq!(hello(&I18nContext::from(I18n::new_for_tests("fr", catalog()))), "Bonjour");
+    }
+
+    #[cfg(feature = "track-access")]
+    #[test]
+    fn each_accessed_message_is_recorded_once() {
+        let log = AccessLog::default();
+        log.record(None, "Open");
+        log.record(Some("file menu"), "Open");
+        log.record(None, "Open");
+        log.record(Some("file menu"), "Open");
+        log.record(Some("file"), " menu\u{4}Open");
+
+        let log = log.0.read().unwrap();
+        assert_eq!(log.keys.values().map(Vec::len).sum::<usize>(), 3);
+        assert!(log.contains(log.hasher.hash_one((None::<&str>, "Open")), None, "Open"));
+        let hash = log.hasher.hash_one((Some("file menu"), "Open"));
+        assert!(log.contains(hash, Some("file menu"), "Open"));
+        assert!(!log.contains(hash, Some("file"), "Open"));
+    }
+
+    #[cfg(feature = "track-access")]
+    #[test]
+    fn accessed_keys_are_listed() {
+        let translations = Translations::from(vec![("fr", CatalogBuilder::new().build())]);
+        let i18n = translations.for_request("fr").unwrap();
+        i18n.catalog.gettext("Hello");
+        i18n.catalog.pgettext("file menu", "Open");
+        i18n.catalog.gettext("Hello");
+
+        let keys = translations.accessed_keys();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains("Hello"));
+        assert!(keys.contains("file menu\u{4}Open"));
+    }
+}
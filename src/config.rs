@@ -0,0 +1,483 @@
+use std::{collections::HashMap, sync::Arc};
+
+#[cfg(any(feature = "actix-web", feature = "actix-web-4"))]
+use crate::error_body::ErrorBody;
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+use crate::signing;
+use crate::Resolver;
+
+/// Options controlling how the language of a request is negotiated.
+///
+/// It should be stored in the state of your application, next to your `Translations`. When it
+/// is missing, the default options are used. It can be built with `I18nConfig::builder()`, or
+/// with struct update syntax:
+///
+/// ```rust,ignore
+/// let config = I18nConfig {
+///     cookie: Some("lang".into()),
+///     ..Default::default()
+/// };
+/// ```
+pub struct I18nConfig {
+    /// Guess the language from the country of the client when `Accept-Language` doesn't match
+    /// any of the supported languages.
+    pub geo: Option<GeoFallback>,
+    /// The name of a cookie in which the language chosen by the user is stored (`lang`, for
+    /// instance).
+    ///
+    /// When this cookie contains a supported language, it is used instead of the one negotiated
+    /// from `Accept-Language`. A value of `auto` (or an empty one) means that the user has no
+    /// preference. It is not read unless a name is set.
+    pub cookie: Option<String>,
+    /// A secret key signing the value of `cookie` with an HMAC (at least 32 random bytes, read
+    /// from the environment or from a file rather than written in the code).
+    ///
+    /// The cookie is sent by the client, which can put anything in it: signing it makes sure that
+    /// the language was stored by your application, and not injected to probe how unexpected
+    /// values are handled. When a key is set, a cookie whose signature is missing or wrong is
+    /// ignored, as if there was no cookie. Use `I18nConfig::cookie_value` to get the value to
+    /// store.
+    ///
+    /// With Rocket, setting it makes the cookie a private cookie instead (encrypted with the
+    /// `secret_key` of Rocket, and set with `Cookies::add_private`), and this key is not used.
+    /// Without a key, the cookie is a plain one.
+    pub cookie_key: Option<Vec<u8>>,
+    /// The name of a query parameter that overrides the language of a single request (`lang`, to
+    /// get `/posts/1?lang=fr` in French, for instance).
+    ///
+    /// It takes precedence over the cookie and over `Accept-Language`, if it is supported, and is
+    /// not read unless a name is set. It is handy to test translations, or to share a link in a
+    /// specific language.
+    pub query: Option<String>,
+    /// Read the language from the first segment of the path of the request (`/fr/posts/1`), when
+    /// it is a supported language.
+    ///
+    /// It takes precedence over the cookie and over `Accept-Language`, but not over the query
+    /// parameter. Your routes still have to accept this prefix: `I18n::strip_lang_prefix` returns
+    /// the rest of the path.
+    pub path_prefix: bool,
+    /// Read the language from the subdomain of the request (`fr.example.com`), when the `Host`
+    /// header ends with this domain (`example.com`) and the subdomain is a supported language.
+    ///
+    /// It takes precedence over the cookie and over `Accept-Language`, but not over the query
+    /// parameter or the path prefix.
+    pub subdomain_suffix: Option<String>,
+    /// The steps of the negotiation, in order (`Resolver::default_chain()` by default).
+    ///
+    /// Remove steps to disable them, reorder them, or insert your own `LocaleResolver`s. The
+    /// default language is used when none of them finds a supported language.
+    pub chain: Vec<Resolver>,
+    /// Languages for which the region matters too much to fall back to another one (`de-CH`, for
+    /// instance).
+    ///
+    /// When one of them is requested, it is only matched exactly: a request for `de-CH` will never
+    /// be served the `de` or `de-DE` catalogs, but will fall back to the next preference of the
+    /// client, or to the default language.
+    pub region_critical: Vec<String>,
+    /// Which catalog to choose when a regional variant (`en-GB`) is requested but not
+    /// supported, and both the language itself (`en`) and another of its regional variants
+    /// (`en-US`) are.
+    ///
+    /// When `true` (the default), the catalog of the language itself is chosen. When `false`, the
+    /// other regional variant is. An exact match is always preferred.
+    pub prefer_base_over_region: bool,
+    /// Log, at the debug level, why each language requested by a client was chosen or rejected.
+    ///
+    /// It is useful to understand why someone got the wrong language, but logs several lines
+    /// for each request, so it is disabled by default.
+    pub log_candidates: bool,
+    /// The language to use when none of the languages requested by the client is supported.
+    ///
+    /// When it is not set (or not supported), the first language of the `Translations` is used.
+    pub default_lang: Option<String>,
+    /// What the Actix Web extractors do when a request can't be translated (an InternalServerError
+    /// by default).
+    pub on_failure: OnFailure,
+}
+
+impl Default for I18nConfig {
+    fn default() -> I18nConfig {
+        I18nConfig {
+            geo: None,
+            cookie: None,
+            cookie_key: None,
+            query: None,
+            path_prefix: false,
+            subdomain_suffix: None,
+            chain: Resolver::default_chain(),
+            region_critical: Vec::new(),
+            prefer_base_over_region: true,
+            log_candidates: false,
+            default_lang: None,
+            on_failure: OnFailure::InternalServerError,
+        }
+    }
+}
+
+impl I18nConfig {
+    /// Starts building a configuration, from the default options.
+    ///
+    /// ```rust,ignore
+    /// let config = I18nConfig::builder()
+    ///     .cookie("lang")
+    ///     .query("lang")
+    ///     .default_lang("en")
+    ///     .build();
+    /// ```
+    pub fn builder() -> I18nConfigBuilder {
+        I18nConfigBuilder {
+            config: I18nConfig::default(),
+        }
+    }
+
+    /// The value to store in `cookie` for a language: the language itself, followed by its
+    /// signature if `cookie_key` is set.
+    ///
+    /// ```rust,ignore
+    /// let cookie = Cookie::new("lang", config.cookie_value("fr"));
+    /// ```
+    ///
+    /// Requires the feature of a web framework.
+    #[cfg(any(
+        test,
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub fn cookie_value(&self, lang: &str) -> String {
+        match (&self.cookie_key, &self.cookie) {
+            (Some(key), Some(name)) => signing::sign(key, name, lang),
+            _ => lang.to_owned(),
+        }
+    }
+
+    /// The language stored in the value of `cookie`, if its signature is valid (or if there is no
+    /// `cookie_key`).
+    #[cfg(any(
+        test,
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub(crate) fn cookie_lang(&self, name: &str, value: String) -> Option<String> {
+        match &self.cookie_key {
+            Some(key) => signing::verify(key, name, &value).map(str::to_owned),
+            None => Some(value),
+        }
+    }
+
+    pub(crate) fn is_region_critical(&self, lang: &str) -> bool {
+        self.region_critical
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case(lang))
+    }
+}
+
+/// Builds an `I18nConfig`, see `I18nConfig::builder`.
+///
+/// Each method sets the option of `I18nConfig` with the same name.
+pub struct I18nConfigBuilder {
+    config: I18nConfig,
+}
+
+impl I18nConfigBuilder {
+    /// See `I18nConfig::geo`.
+    pub fn geo(mut self, geo: GeoFallback) -> I18nConfigBuilder {
+        self.config.geo = Some(geo);
+        self
+    }
+
+    /// See `I18nConfig::cookie`.
+    pub fn cookie<S: Into<String>>(mut self, name: S) -> I18nConfigBuilder {
+        self.config.cookie = Some(name.into());
+        self
+    }
+
+    /// See `I18nConfig::cookie_key`.
+    pub fn cookie_key<K: Into<Vec<u8>>>(mut self, key: K) -> I18nConfigBuilder {
+        self.config.cookie_key = Some(key.into());
+        self
+    }
+
+    /// See `I18nConfig::query`.
+    pub fn query<S: Into<String>>(mut self, name: S) -> I18nConfigBuilder {
+        self.config.query = Some(name.into());
+        self
+    }
+
+    /// See `I18nConfig::path_prefix`.
+    pub fn path_prefix(mut self, enabled: bool) -> I18nConfigBuilder {
+        self.config.path_prefix = enabled;
+        self
+    }
+
+    /// See `I18nConfig::subdomain_suffix`.
+    pub fn subdomain_suffix<S: Into<String>>(mut self, suffix: S) -> I18nConfigBuilder {
+        self.config.subdomain_suffix = Some(suffix.into());
+        self
+    }
+
+    /// See `I18nConfig::chain`.
+    pub fn chain(mut self, chain: Vec<Resolver>) -> I18nConfigBuilder {
+        self.config.chain = chain;
+        self
+    }
+
+    /// Adds a step at the end of the chain of resolvers. See `I18nConfig::chain`.
+    pub fn resolver(mut self, resolver: Resolver) -> I18nConfigBuilder {
+        self.config.chain.push(resolver);
+        self
+    }
+
+    /// Adds a language to `I18nConfig::region_critical`.
+    pub fn region_critical<S: Into<String>>(mut self, lang: S) -> I18nConfigBuilder {
+        self.config.region_critical.push(lang.into());
+        self
+    }
+
+    /// See `I18nConfig::prefer_base_over_region`.
+    pub fn prefer_base_over_region(mut self, prefer: bool) -> I18nConfigBuilder {
+        self.config.prefer_base_over_region = prefer;
+        self
+    }
+
+    /// See `I18nConfig::log_candidates`.
+    pub fn log_candidates(mut self, enabled: bool) -> I18nConfigBuilder {
+        self.config.log_candidates = enabled;
+        self
+    }
+
+    /// See `I18nConfig::default_lang`.
+    pub fn default_lang<S: Into<String>>(mut self, lang: S) -> I18nConfigBuilder {
+        self.config.default_lang = Some(lang.into());
+        self
+    }
+
+    /// See `I18nConfig::on_failure`.
+    pub fn on_failure(mut self, on_failure: OnFailure) -> I18nConfigBuilder {
+        self.config.on_failure = on_failure;
+        self
+    }
+
+    /// The configuration, to store in the state of your application.
+    pub fn build(self) -> I18nConfig {
+        self.config
+    }
+}
+
+/// What the Actix Web extractors do when a request can't be translated: when there are no
+/// translations in the state of the application, or when the negotiated language has no catalog.
+///
+/// ```rust,ignore
+/// App::new()
+///     .app_data(web::Data::new(translations))
+///     .app_data(web::Data::new(I18nConfig {
+///         on_failure: OnFailure::Status(406),
+///         ..Default::default()
+///     }))
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub enum OnFailure {
+    /// Fail with an InternalServerError listing the available languages, as JSON if the request
+    /// asked for it, and as HTML otherwise (or with an empty body, when there are no
+    /// translations).
+    InternalServerError,
+    /// Fail with another status (`406` for Not Acceptable, for instance), with the same body.
+    Status(u16),
+    /// Use the catalog of the default language instead, like `DefaultI18n` does, so that the
+    /// request never fails.
+    DefaultLang,
+    /// Fail with the response built by this function, given the negotiated language (`None` when
+    /// there are no translations) and the available languages.
+    Custom(Arc<dyn Fn(Option<&str>, &[&'static str]) -> FailureResponse + Send + Sync>),
+}
+
+/// A response to a request that can't be translated, built by `OnFailure::Custom`.
+#[derive(Clone, Debug)]
+pub struct FailureResponse {
+    /// The status code of the response.
+    pub status: u16,
+    /// The `Content-Type` of the response.
+    pub content_type: String,
+    /// The body of the response.
+    pub body: String,
+}
+
+#[cfg(any(feature = "actix-web", feature = "actix-web-4"))]
+impl FailureResponse {
+    /// The response to a request that can't be translated, as configured in
+    /// `I18nConfig::on_failure`, or `None` with `OnFailure::DefaultLang`.
+    ///
+    /// `lang` is the negotiated language, or `None` if there are no translations, in which case
+    /// the body is empty; `accept` is the `Accept` header of the request.
+    pub(crate) fn for_request(
+        config: Option<&I18nConfig>,
+        accept: Option<&str>,
+        lang: Option<&str>,
+        available: &[&'static str],
+    ) -> Option<FailureResponse> {
+        let status = match config.map(|c| &c.on_failure) {
+            Some(OnFailure::DefaultLang) => return None,
+            Some(OnFailure::Custom(respond)) => return Some(respond(lang, available)),
+            Some(OnFailure::Status(status)) => *status,
+            Some(OnFailure::InternalServerError) | None => 500,
+        };
+        Some(match lang {
+            Some(_) => {
+                let body = ErrorBody::new(accept, available);
+                FailureResponse {
+                    status,
+                    content_type: body.content_type().to_owned(),
+                    body: body.content,
+                }
+            }
+            None => FailureResponse {
+                status,
+                content_type: String::new(),
+                body: String::new(),
+            },
+        })
+    }
+}
+
+/// Maps the country detected by an edge CDN to a language.
+///
+/// It is only consulted when the `Accept-Language` header doesn't match any of the supported
+/// languages, so an explicit preference of the user is never overridden.
+pub struct GeoFallback {
+    header: String,
+    countries: HashMap<String, String>,
+}
+
+impl GeoFallback {
+    /// Reads the country code (`FR`, `DE`, …) from a custom header.
+    pub fn new<H: Into<String>>(header: H) -> GeoFallback {
+        GeoFallback {
+            header: header.into(),
+            countries: HashMap::new(),
+        }
+    }
+
+    /// Reads the country code from the `CF-IPCountry` header set by Cloudflare.
+    pub fn cloudflare() -> GeoFallback {
+        GeoFallback::new("CF-IPCountry")
+    }
+
+    /// Reads the country code from the `Fastly-Geo-Country` header set by Fastly.
+    pub fn fastly() -> GeoFallback {
+        GeoFallback::new("Fastly-Geo-Country")
+    }
+
+    /// Uses `lang` for clients located in `country`.
+    pub fn country(mut self, country: &str, lang: &str) -> GeoFallback {
+        self.countries
+            .insert(country.to_uppercase(), lang.to_owned());
+        self
+    }
+
+    /// The name of the header containing the country code.
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// The language to use for clients located in `country`, if any.
+    pub fn lang(&self, country: &str) -> Option<&str> {
+        self.countries
+            .get(&country.trim().to_uppercase())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        negotiation::{self, RequestInfo},
+        testing,
+    };
+
+    #[test]
+    fn the_builder_sets_every_option() {
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .query("hl")
+            .path_prefix(true)
+            .subdomain_suffix("example.com")
+            .region_critical("de-CH")
+            .prefer_base_over_region(false)
+            .default_lang("fr")
+            .build();
+        assert_eq!(config.cookie.as_deref(), Some("lang"));
+        assert_eq!(config.subdomain_suffix.as_deref(), Some("example.com"));
+        assert!(config.path_prefix);
+        assert!(!config.prefer_base_over_region);
+        assert!(config.is_region_critical("DE-ch"));
+        assert!(!config.is_region_critical("de"));
+        assert_eq!(config.cookie_value("de"), "de");
+
+        let langs = testing::translations(&["en", "fr", "de"]);
+        assert_eq!(negotiation::default_lang(&langs, Some(&config)), "fr");
+        assert_eq!(negotiation::default_lang(&langs, None), "en");
+
+        let negotiate = |query, path| {
+            let header = |_: &str| Some("de");
+            let cookies = |_: &str| None;
+            let request = RequestInfo::new(Some(&config), &header, &cookies, query, path);
+            negotiation::negotiate(&langs, Some(&config), &request)
+        };
+        assert_eq!(negotiate(Some("hl=fr"), "/"), "fr");
+        assert_eq!(negotiate(Some("lang=fr"), "/en/posts"), "en");
+        assert_eq!(negotiate(None, "/posts"), "de");
+    }
+
+    #[test]
+    #[cfg(any(feature = "actix-web", feature = "actix-web-4"))]
+    fn failures_are_answered_as_configured() {
+        let config = |on_failure| I18nConfig {
+            on_failure,
+            ..Default::default()
+        };
+        let respond = |config: Option<&I18nConfig>, lang| {
+            FailureResponse::for_request(config, Some("application/json"), lang, &["en", "fr"])
+        };
+
+        let response = respond(None, Some("de")).unwrap();
+        assert_eq!(response.status, 500);
+        assert_eq!(response.content_type, "application/json");
+        assert!(response.body.contains(r#""available":["en","fr"]"#));
+
+        let response = respond(Some(&config(OnFailure::Status(406))), None).unwrap();
+        assert_eq!(response.status, 406);
+        assert!(response.body.is_empty());
+
+        assert!(respond(Some(&config(OnFailure::DefaultLang)), Some("de")).is_none());
+
+        let custom = config(OnFailure::Custom(Arc::new(|lang, available| FailureResponse {
+            status: 404,
+            content_type: "text/plain".into(),
+            body: format!("{:?} {}", lang, available.join(",")),
+        })));
+        assert_eq!(respond(Some(&custom), Some("de")).unwrap().body, "Some(\"de\") en,fr");
+    }
+}
@@ -0,0 +1,407 @@
+//! Locale-aware formatting of dates and times.
+//!
+//! The names of the months and of the days of the week, and the patterns of the most common
+//! locales are built-in. Locales that are not known use the ISO 8601 format (`2024-03-05`).
+
+use chrono::{Datelike, Timelike};
+
+use crate::langs;
+
+/// How long a formatted date is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateStyle {
+    /// Only numbers: `3/5/24` in English, `05/03/2024` in French.
+    Short,
+    /// With the abbreviated name of the month: `Mar 5, 2024`, `5 mars 2024`.
+    Medium,
+    /// With the full names of the month and of the day of the week: `Tuesday, March 5, 2024`,
+    /// `mardi 5 mars 2024`.
+    Long,
+}
+
+/// How dates and times are written in a given locale.
+///
+/// In patterns, `{d}` is the day, `{M}` the month and `{y}` the year (`{dd}` and `{MM}` with two
+/// digits, `{yy}` for the last two digits of the year), `{MMM}` and `{MMMM}` the short and full
+/// names of the month, and `{EEEE}` the name of the day of the week. `{H}` is the hour (`{HH}`
+/// with two digits, `{h}` on 12 hours, followed by `{a}`), and `{mm}` the minutes.
+struct DateNames {
+    months: &'static [&'static str; 12],
+    short_months: &'static [&'static str; 12],
+    /// Starting with Monday.
+    weekdays: &'static [&'static str; 7],
+    /// The patterns of the short, medium and long dates.
+    dates: [&'static str; 3],
+    time: &'static str,
+}
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const EN_SHORT_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const EN_WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The names and patterns of each locale.
+const LOCALES: &[(&str, DateNames)] = &[
+    (
+        "de",
+        DateNames {
+            months: &[
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            short_months: &[
+                "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.",
+                "Nov.", "Dez.",
+            ],
+            weekdays: &[
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+            dates: ["{dd}.{MM}.{yy}", "{dd}.{MM}.{y}", "{EEEE}, {d}. {MMMM} {y}"],
+            time: "{HH}:{mm}",
+        },
+    ),
+    (
+        "en",
+        DateNames {
+            months: &EN_MONTHS,
+            short_months: &EN_SHORT_MONTHS,
+            weekdays: &EN_WEEKDAYS,
+            dates: ["{M}/{d}/{yy}", "{MMM} {d}, {y}", "{EEEE}, {MMMM} {d}, {y}"],
+            time: "{h}:{mm} {a}",
+        },
+    ),
+    (
+        "en-GB",
+        DateNames {
+            months: &EN_MONTHS,
+            short_months: &EN_SHORT_MONTHS,
+            weekdays: &EN_WEEKDAYS,
+            dates: ["{dd}/{MM}/{y}", "{d} {MMM} {y}", "{EEEE} {d} {MMMM} {y}"],
+            time: "{HH}:{mm}",
+        },
+    ),
+    (
+        "es",
+        DateNames {
+            months: &[
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+            short_months: &[
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+            ],
+            weekdays: &[
+                "lunes",
+                "martes",
+                "miércoles",
+                "jueves",
+                "viernes",
+                "sábado",
+                "domingo",
+            ],
+            dates: [
+                "{d}/{M}/{yy}",
+                "{d} {MMM} {y}",
+                "{EEEE}, {d} de {MMMM} de {y}",
+            ],
+            time: "{H}:{mm}",
+        },
+    ),
+    (
+        "fr",
+        DateNames {
+            months: &[
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+            short_months: &[
+                "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+                "nov.", "déc.",
+            ],
+            weekdays: &[
+                "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+            ],
+            dates: ["{dd}/{MM}/{y}", "{d} {MMM} {y}", "{EEEE} {d} {MMMM} {y}"],
+            time: "{HH}:{mm}",
+        },
+    ),
+    (
+        "it",
+        DateNames {
+            months: &[
+                "gennaio",
+                "febbraio",
+                "marzo",
+                "aprile",
+                "maggio",
+                "giugno",
+                "luglio",
+                "agosto",
+                "settembre",
+                "ottobre",
+                "novembre",
+                "dicembre",
+            ],
+            short_months: &[
+                "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+            ],
+            weekdays: &[
+                "lunedì",
+                "martedì",
+                "mercoledì",
+                "giovedì",
+                "venerdì",
+                "sabato",
+                "domenica",
+            ],
+            dates: ["{dd}/{MM}/{yy}", "{d} {MMM} {y}", "{EEEE} {d} {MMMM} {y}"],
+            time: "{HH}:{mm}",
+        },
+    ),
+    (
+        "nl",
+        DateNames {
+            months: &[
+                "januari",
+                "februari",
+                "maart",
+                "april",
+                "mei",
+                "juni",
+                "juli",
+                "augustus",
+                "september",
+                "oktober",
+                "november",
+                "december",
+            ],
+            short_months: &[
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            weekdays: &[
+                "maandag",
+                "dinsdag",
+                "woensdag",
+                "donderdag",
+                "vrijdag",
+                "zaterdag",
+                "zondag",
+            ],
+            dates: ["{dd}-{MM}-{y}", "{d} {MMM} {y}", "{EEEE} {d} {MMMM} {y}"],
+            time: "{HH}:{mm}",
+        },
+    ),
+    (
+        "pt",
+        DateNames {
+            months: &[
+                "janeiro",
+                "fevereiro",
+                "março",
+                "abril",
+                "maio",
+                "junho",
+                "julho",
+                "agosto",
+                "setembro",
+                "outubro",
+                "novembro",
+                "dezembro",
+            ],
+            short_months: &[
+                "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.",
+                "nov.", "dez.",
+            ],
+            weekdays: &[
+                "segunda-feira",
+                "terça-feira",
+                "quarta-feira",
+                "quinta-feira",
+                "sexta-feira",
+                "sábado",
+                "domingo",
+            ],
+            dates: [
+                "{dd}/{MM}/{y}",
+                "{d} de {MMM} de {y}",
+                "{EEEE}, {d} de {MMMM} de {y}",
+            ],
+            time: "{HH}:{mm}",
+        },
+    ),
+];
+
+/// The names and patterns of a locale, trying its base language if the full tag is not known.
+fn names(lang: &str) -> Option<&'static DateNames> {
+    let find = |tag: &str| {
+        LOCALES
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(tag))
+            .map(|(_, n)| n)
+    };
+    find(&lang.replace('_', "-")).or_else(|| find(langs::base(lang)))
+}
+
+/// Replaces the fields of a pattern (`{d}`, `{MMMM}`, …) with their value.
+fn expand(pattern: &str, field: impl Fn(&str) -> String) -> String {
+    let mut res = String::with_capacity(pattern.len() * 2);
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => {
+                res.push_str(&field(&rest[start + 1..start + end]));
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Formats a date in a given locale.
+pub(crate) fn format_date<D: Datelike>(lang: &str, date: &D, style: DateStyle) -> String {
+    let (names, pattern) = match names(lang) {
+        Some(names) => (Some(names), names.dates[style as usize]),
+        None => (None, "{y}-{MM}-{dd}"),
+    };
+    expand(pattern, |field| match (field, names) {
+        ("d", _) => date.day().to_string(),
+        ("dd", _) => format!("{:02}", date.day()),
+        ("M", _) => date.month().to_string(),
+        ("MM", _) => format!("{:02}", date.month()),
+        ("MMM", Some(names)) => names.short_months[date.month0() as usize].to_owned(),
+        ("MMMM", Some(names)) => names.months[date.month0() as usize].to_owned(),
+        ("EEEE", Some(names)) => {
+            names.weekdays[date.weekday().num_days_from_monday() as usize].to_owned()
+        }
+        ("y", _) => date.year().to_string(),
+        ("yy", _) => format!("{:02}", date.year().rem_euclid(100)),
+        _ => String::new(),
+    })
+}
+
+/// Formats a time (hours and minutes) in a given locale.
+pub(crate) fn format_time<T: Timelike>(lang: &str, time: &T) -> String {
+    let pattern = names(lang).map_or("{HH}:{mm}", |names| names.time);
+    expand(pattern, |field| match field {
+        "H" => time.hour().to_string(),
+        "HH" => format!("{:02}", time.hour()),
+        "h" => match time.hour() % 12 {
+            0 => "12".to_owned(),
+            hour => hour.to_string(),
+        },
+        "a" if time.hour() < 12 => "AM".to_owned(),
+        "a" => "PM".to_owned(),
+        "mm" => format!("{:02}", time.minute()),
+        _ => String::new(),
+    })
+}
+
+/// Formats a date and a time in a given locale: `Mar 5, 2024, 2:30 PM`.
+pub(crate) fn format_datetime<T: Datelike + Timelike>(
+    lang: &str,
+    datetime: &T,
+    style: DateStyle,
+) -> String {
+    let date = format_date(lang, datetime, style);
+    let time = format_time(lang, datetime);
+    if names(lang).is_some() {
+        format!("{}, {}", date, time)
+    } else {
+        format!("{} {}", date, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn dates_follow_the_patterns_of_the_locale() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        assert_eq!(format_date("en", &date, DateStyle::Short), "3/5/24");
+        assert_eq!(format_date("en-US", &date, DateStyle::Medium), "Mar 5, 2024");
+        assert_eq!(format_date("en", &date, DateStyle::Long), "Tuesday, March 5, 2024");
+        assert_eq!(format_date("en_GB", &date, DateStyle::Short), "05/03/2024");
+        assert_eq!(format_date("fr-CA", &date, DateStyle::Long), "mardi 5 mars 2024");
+        assert_eq!(format_date("de", &date, DateStyle::Short), "05.03.24");
+        assert_eq!(format_date("xx", &date, DateStyle::Long), "2024-03-05");
+    }
+
+    #[test]
+    fn times_follow_the_clock_of_the_locale() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let afternoon = date.and_hms_opt(14, 30, 0).unwrap();
+        let midnight = date.and_hms_opt(0, 5, 0).unwrap();
+        assert_eq!(format_time("en", &afternoon), "2:30 PM");
+        assert_eq!(format_time("en", &midnight), "12:05 AM");
+        assert_eq!(format_time("fr", &midnight), "00:05");
+        assert_eq!(format_datetime("en", &afternoon, DateStyle::Medium), "Mar 5, 2024, 2:30 PM");
+        assert_eq!(format_datetime("fr", &afternoon, DateStyle::Short), "05/03/2024, 14:30");
+        assert_eq!(format_datetime("xx", &afternoon, DateStyle::Short), "2024-03-05 14:30");
+    }
+}
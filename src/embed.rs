@@ -0,0 +1,113 @@
+//! Catalogs embedded in the binary with `rust-embed`.
+
+use std::{
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use rust_embed::RustEmbed;
+
+use crate::{loader::catalog_error, parse_catalog, I18nError, Translations};
+
+/// Loads translations from the `.mo` files of a `rust-embed` asset folder, laid out like the
+/// `translations` directory (`{lang}/LC_MESSAGES/{domain}.mo`), so that they are part of the
+/// binary without `gettext_macros::include_i18n`.
+///
+/// ```rust,ignore
+/// #[derive(RustEmbed)]
+/// #[folder = "translations/"]
+/// #[include = "*.mo"]
+/// struct Catalogs;
+///
+/// let translations = rocket_i18n::i18n_embed::<Catalogs>("plume", vec!["en", "fr"]);
+/// ```
+///
+/// Like `i18n`, it panics if one of them is missing or can't be parsed.
+///
+/// Requires the `embed` feature.
+pub fn i18n_embed<E: RustEmbed>(domain: &str, langs: Vec<&'static str>) -> Translations {
+    try_i18n_embed::<E>(domain, langs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as `i18n_embed`, but returns an error instead of panicking.
+///
+/// Requires the `embed` feature.
+pub fn try_i18n_embed<E: RustEmbed>(
+    domain: &str,
+    langs: Vec<&'static str>,
+) -> Result<Translations, I18nError> {
+    langs
+        .into_iter()
+        .map(|lang| {
+            // rust-embed always separates directories with slashes
+            let file = format!("{}/LC_MESSAGES/{}.mo", lang, domain);
+            let path = PathBuf::from(&file);
+            let embedded = match E::get(&file) {
+                Some(embedded) => embedded,
+                None => {
+                    let error = io::Error::new(ErrorKind::NotFound, "the catalog is not embedded");
+                    return Err(I18nError::Io { lang, path, error });
+                }
+            };
+            match parse_catalog(&embedded.data[..], lang) {
+                Ok(catalog) => Ok((lang, catalog)),
+                Err(error) => Err(catalog_error(lang, path, error)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Translations::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::mo_file;
+    use rust_embed::{EmbeddedFile, Metadata};
+    use std::borrow::Cow;
+
+    /// What `#[derive(RustEmbed)]` would generate for a folder with English and French catalogs.
+    struct Catalogs;
+
+    impl RustEmbed for Catalogs {
+        fn get(file: &str) -> Option<EmbeddedFile> {
+            let hello = match file {
+                "en/LC_MESSAGES/app.mo" => "Hello",
+                "fr/LC_MESSAGES/app.mo" => "Bonjour",
+                "de/LC_MESSAGES/app.mo" => return Some(embedded(b"not a catalog".to_vec())),
+                _ => return None,
+            };
+            Some(embedded(mo_file(None, hello)))
+        }
+
+        fn iter() -> impl Iterator<Item = Cow<'static, str>> + 'static {
+            ["en", "fr", "de"]
+                .iter()
+                .map(|lang| Cow::Owned(format!("{}/LC_MESSAGES/app.mo", lang)))
+        }
+    }
+
+    fn embedded(data: Vec<u8>) -> EmbeddedFile {
+        EmbeddedFile {
+            data: Cow::Owned(data),
+            metadata: Metadata::__rust_embed_new([0; 32], None, None),
+        }
+    }
+
+    #[test]
+    fn catalogs_are_read_from_the_embedded_folder() {
+        let langs = i18n_embed::<Catalogs>("app", vec!["en", "fr"]);
+        assert_eq!(langs.all_langs().copied().collect::<Vec<_>>(), ["en", "fr"]);
+        assert_eq!(langs.get("fr").unwrap().gettext("Hello"), "Bonjour");
+
+        match try_i18n_embed::<Catalogs>("app", vec!["en", "it"]).err().unwrap() {
+            I18nError::Io { lang, path, error } => {
+                assert_eq!(lang, "it");
+                assert_eq!(path, PathBuf::from("it/LC_MESSAGES/app.mo"));
+                assert_eq!(error.kind(), ErrorKind::NotFound);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(try_i18n_embed::<Catalogs>("app", vec!["de"]).is_err());
+        assert!(try_i18n_embed::<Catalogs>("other", vec!["en"]).is_err());
+    }
+}
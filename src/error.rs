@@ -0,0 +1,101 @@
+use std::{
+    error, fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Why translations couldn't be loaded.
+#[derive(Debug)]
+pub enum I18nError {
+    /// The directory of the catalogs couldn't be read (or, with `Loader::relative_to_exe`, the
+    /// directory of the executable couldn't be found).
+    Dir { path: PathBuf, error: io::Error },
+    /// The catalog of a language couldn't be read.
+    Io {
+        lang: &'static str,
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// The catalog of a language isn't a valid `.mo` file.
+    Parse {
+        lang: &'static str,
+        path: PathBuf,
+        error: gettext::Error,
+    },
+    /// The `.po` file of a language has a syntax error, on the given line (starting from `1`).
+    #[cfg(feature = "po")]
+    PoSyntax {
+        lang: &'static str,
+        path: PathBuf,
+        line: usize,
+    },
+}
+
+impl I18nError {
+    /// The language whose catalog couldn't be loaded, unless the whole directory couldn't be
+    /// read.
+    pub fn lang(&self) -> Option<&'static str> {
+        match *self {
+            I18nError::Dir { .. } => None,
+            I18nError::Io { lang, .. } | I18nError::Parse { lang, .. } => Some(lang),
+            #[cfg(feature = "po")]
+            I18nError::PoSyntax { lang, .. } => Some(lang),
+        }
+    }
+
+    /// The path of the catalog (or of the directory) that couldn't be loaded.
+    pub fn path(&self) -> &Path {
+        match self {
+            I18nError::Dir { path, .. }
+            | I18nError::Io { path, .. }
+            | I18nError::Parse { path, .. } => path,
+            #[cfg(feature = "po")]
+            I18nError::PoSyntax { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for I18nError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            I18nError::Dir { path, error } => write!(
+                f,
+                "Couldn't read the directory of the catalogs ({}): {}",
+                path.display(),
+                error
+            ),
+            I18nError::Io { lang, path, error } => write!(
+                f,
+                "Couldn't open the catalog of {} ({}): {}",
+                lang,
+                path.display(),
+                error
+            ),
+            I18nError::Parse { lang, path, error } => write!(
+                f,
+                "Error while loading the catalog of {} ({}): {}",
+                lang,
+                path.display(),
+                error
+            ),
+            #[cfg(feature = "po")]
+            I18nError::PoSyntax { lang, path, line } => write!(
+                f,
+                "Syntax error in the catalog of {} ({}, line {})",
+                lang,
+                path.display(),
+                line
+            ),
+        }
+    }
+}
+
+impl error::Error for I18nError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            I18nError::Dir { error, .. } | I18nError::Io { error, .. } => Some(error),
+            I18nError::Parse { error, .. } => Some(error),
+            #[cfg(feature = "po")]
+            I18nError::PoSyntax { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! The body of the responses sent when no translations can be found for a request.
+
+use crate::escape_html;
+
+/// An error body, in the format the client asked for.
+#[derive(Debug)]
+pub(crate) struct ErrorBody {
+    /// Whether the body is JSON (or HTML otherwise).
+    pub json: bool,
+    pub content: String,
+}
+
+impl ErrorBody {
+    /// Builds the body of an error response, listing the available languages.
+    ///
+    /// The body is JSON if the `Accept` header lists `application/json` before `text/html`, and
+    /// HTML otherwise, so that APIs and pages both get something they can display.
+    pub fn new(accept: Option<&str>, available: &[&'static str]) -> ErrorBody {
+        let json = accept
+            .into_iter()
+            .flat_map(|header| header.split(','))
+            .filter_map(|media| media.split(';').next())
+            .map(str::trim)
+            .find(|media| *media == "application/json" || *media == "text/html")
+            == Some("application/json");
+
+        let content = if json {
+            let langs = available
+                .iter()
+                .map(|lang| format!("\"{}\"", lang.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!(
+                "{{\"error\":\"Could not find translations\",\"available\":[{}]}}",
+                langs
+            )
+        } else {
+            let mut langs = String::new();
+            for lang in available {
+                langs.push_str("<li>");
+                langs.push_str(&escape_html(lang));
+                langs.push_str("</li>");
+            }
+            format!(
+                "<!DOCTYPE html><html><head><title>Could not find translations</title></head>\
+                 <body><h1>Could not find translations</h1><p>Available languages:</p>\
+                 <ul>{}</ul></body></html>",
+                langs
+            )
+        };
+
+        ErrorBody { json, content }
+    }
+
+    /// The `Content-Type` of the body.
+    pub fn content_type(&self) -> &'static str {
+        if self.json {
+            "application/json"
+        } else {
+            "text/html; charset=utf-8"
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Fluent resources, as an alternative to gettext catalogs.
+
+use std::{fs, path::Path, sync::Arc};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use log::warn;
+use unic_langid::LanguageIdentifier;
+
+use crate::{empty_catalog, langs, Translations};
+
+/// The Fluent messages of a language.
+///
+/// Requires the `fluent` feature.
+pub struct FluentCatalog {
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl FluentCatalog {
+    /// Creates the catalog of a language from the content of its `.ftl` resources.
+    ///
+    /// Syntax errors are logged, and the messages that can't be parsed are left out, so that the
+    /// others can still be used.
+    pub fn new(lang: &str, sources: Vec<String>) -> FluentCatalog {
+        let mut bundle = FluentBundle::new_concurrent(vec![lang
+            .parse::<LanguageIdentifier>()
+            .unwrap_or_default()]);
+        // Like plural messages of catalogs, only isolate arguments in right-to-left languages
+        bundle.set_use_isolating(langs::is_rtl(lang));
+        for source in sources {
+            let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+                for error in errors {
+                    warn!("Syntax error in a Fluent resource of {}: {:?}", lang, error);
+                }
+                resource
+            });
+            if let Err(errors) = bundle.add_resource(resource) {
+                for error in errors {
+                    warn!("Error in a Fluent resource of {}: {:?}", lang, error);
+                }
+            }
+        }
+        FluentCatalog { bundle }
+    }
+
+    /// Formats a message (or one of its attributes, as `message.attribute`), with the given
+    /// arguments.
+    ///
+    /// If the message doesn't exist, its id is returned, as gettext returns untranslated
+    /// messages.
+    pub fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        let mut parts = id.splitn(2, '.');
+        let message = parts.next().and_then(|id| self.bundle.get_message(id));
+        let pattern = match (message, parts.next()) {
+            (Some(message), None) => message.value(),
+            (Some(message), Some(attribute)) => message.get_attribute(attribute).map(|a| a.value()),
+            (None, _) => None,
+        };
+        match pattern {
+            Some(pattern) => {
+                let mut errors = Vec::new();
+                let formatted = self.bundle.format_pattern(pattern, args, &mut errors);
+                for error in errors {
+                    warn!("Error while formatting the Fluent message {}: {:?}", id, error);
+                }
+                formatted.into_owned()
+            }
+            None => id.to_owned(),
+        }
+    }
+
+    /// Whether this catalog has a message.
+    pub fn has_message(&self, id: &str) -> bool {
+        self.bundle.has_message(id)
+    }
+}
+
+/// Loads Fluent resources at runtime: all the `.ftl` files of `{dir}/{lang}/` (in the order of
+/// their names), for each language.
+///
+/// The resulting translations have no gettext catalogs: messages are formatted with the
+/// `fluent!` macro (or `I18n::fluent`).
+///
+/// ```rust,ignore
+/// let translations = rocket_i18n::i18n_fluent("locales", vec!["en-US", "fr"]);
+/// ```
+///
+/// It panics if the directory of a language can't be read, like `i18n` does when a catalog is
+/// missing.
+pub fn i18n_fluent<P: AsRef<Path>>(dir: P, langs: Vec<&'static str>) -> Translations {
+    let mut translations = langs
+        .iter()
+        .map(|lang| (*lang, empty_catalog()))
+        .collect::<Translations>();
+    for lang in langs {
+        let mut paths = fs::read_dir(dir.as_ref().join(lang))
+            .expect("Couldn't read Fluent resources")
+            .map(|entry| entry.expect("Couldn't read Fluent resources").path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ftl"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        let sources = paths
+            .iter()
+            .map(|path| fs::read_to_string(path).expect("Couldn't read Fluent resource"))
+            .collect();
+        translations = translations.with_fluent(lang, FluentCatalog::new(lang, sources));
+    }
+    translations
+}
+
+impl Translations {
+    /// Adds the Fluent messages of a language, that `I18n::fluent` and `fluent!` will use.
+    ///
+    /// Requires the `fluent` feature.
+    pub fn with_fluent(mut self, lang: &'static str, catalog: FluentCatalog) -> Translations {
+        self.fluent.retain(|l| l.0 != lang);
+        self.fluent.push((lang, Arc::new(catalog)));
+        self
+    }
+
+    pub(crate) fn fluent_catalog(&self, lang: &str) -> Option<Arc<FluentCatalog>> {
+        self.fluent
+            .iter()
+            .find(|l| l.0 == lang)
+            .map(|l| l.1.clone())
+    }
+}
+
+/// Formats a Fluent message in the language of a request, with named arguments.
+///
+/// ```rust,ignore
+/// fluent!(i18n, "hello-world")
+/// fluent!(i18n, "welcome", userName = user.name, unreadEmails = 42)
+/// ```
+///
+/// Requires the `fluent` feature.
+#[macro_export]
+macro_rules! fluent {
+    ($i18n:expr, $id:expr) => {
+        $i18n.fluent($id, None)
+    };
+    ($i18n:expr, $id:expr, $( $name:ident = $value:expr ),+ $(,)*) => {{
+        let mut args = $crate::FluentArgs::new();
+        $( args.set(stringify!($name), $value); )+
+        $i18n.fluent($id, Some(&args))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    const FR: &str = r#"
+hello = Bonjour { $name } !
+emails = { $count ->
+    [one] Un courriel
+   *[other] { $count } courriels
+}
+login = Connexion
+    .title = Se connecter à votre compte
+broken = {
+"#;
+
+    #[test]
+    fn messages_are_formatted_with_their_arguments() {
+        let catalog = FluentCatalog::new("fr", vec![FR.to_owned()]);
+        let mut args = FluentArgs::new();
+        args.set("name", "Ana");
+        assert_eq!(catalog.format("hello", Some(&args)), "Bonjour Ana !");
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(catalog.format("emails", Some(&args)), "Un courriel");
+        let mut args = FluentArgs::new();
+        args.set("count", 3);
+        assert_eq!(catalog.format("emails", Some(&args)), "3 courriels");
+        assert_eq!(catalog.format("login.title", None), "Se connecter à votre compte");
+        assert_eq!(catalog.format("login.subtitle", None), "login.subtitle");
+        assert_eq!(catalog.format("missing", None), "missing");
+        assert!(catalog.has_message("login"));
+        assert!(!catalog.has_message("broken"));
+    }
+
+    #[test]
+    fn the_fluent_macro_uses_the_catalog_of_the_request() {
+        let translations = testing::translations(&["en", "fr"])
+            .with_fluent("fr", FluentCatalog::new("fr", vec![FR.to_owned()]));
+        let fr = translations.for_request("fr").unwrap();
+        assert_eq!(fluent!(fr, "hello", name = "Ana"), "Bonjour Ana !");
+        assert_eq!(fluent!(fr, "login"), "Connexion");
+        let en = translations.for_request("en").unwrap();
+        assert_eq!(fluent!(en, "hello", name = "Ana"), "hello");
+    }
+}
@@ -0,0 +1,272 @@
+//! Locale-aware formatting of numbers.
+//!
+//! The conventions of the most common locales are built-in. Locales that are not known use a
+//! neutral format (`1234.56`).
+
+use crate::langs;
+
+/// How numbers are written in a given locale.
+struct Conventions {
+    decimal: char,
+    group: char,
+    currency: Position,
+    percent: Position,
+}
+
+/// Where the currency symbol or the percent sign goes.
+#[derive(Clone, Copy)]
+enum Position {
+    /// `$1,234.56`, `%45`
+    Before,
+    /// `R$ 1.234,56`
+    BeforeSpaced,
+    /// `45%`
+    Attached,
+    /// `1 234,56 €`, `45 %`
+    After,
+}
+
+use self::Position::*;
+
+const NBSP: char = '\u{a0}';
+const NNBSP: char = '\u{202f}';
+
+const fn conv(decimal: char, group: char, currency: Position, percent: Position) -> Conventions {
+    Conventions {
+        decimal,
+        group,
+        currency,
+        percent,
+    }
+}
+
+/// The conventions of each locale: decimal separator, grouping separator, and position of the
+/// currency symbol and of the percent sign.
+const LOCALES: &[(&str, Conventions)] = &[
+    ("cs", conv(',', NBSP, After, After)),
+    ("da", conv(',', '.', After, After)),
+    ("de", conv(',', '.', After, After)),
+    ("de-CH", conv('.', '’', BeforeSpaced, Attached)),
+    ("en", conv('.', ',', Before, Attached)),
+    ("es", conv(',', '.', After, After)),
+    ("es-MX", conv('.', ',', Before, Attached)),
+    ("fi", conv(',', NBSP, After, After)),
+    ("fr", conv(',', NNBSP, After, After)),
+    ("it", conv(',', '.', After, Attached)),
+    ("ja", conv('.', ',', Before, Attached)),
+    ("ko", conv('.', ',', Before, Attached)),
+    ("nb", conv(',', NBSP, After, After)),
+    ("nl", conv(',', '.', BeforeSpaced, Attached)),
+    ("pl", conv(',', NBSP, After, Attached)),
+    ("pt", conv(',', NBSP, After, Attached)),
+    ("pt-BR", conv(',', '.', BeforeSpaced, Attached)),
+    ("ru", conv(',', NBSP, After, After)),
+    ("sv", conv(',', NBSP, After, After)),
+    ("tr", conv(',', '.', Before, Before)),
+    ("uk", conv(',', NBSP, After, Attached)),
+    ("zh", conv('.', ',', Before, Attached)),
+];
+
+/// Currency codes, with their symbol and number of decimals.
+const CURRENCIES: &[(&str, &str, usize)] = &[
+    ("AUD", "A$", 2),
+    ("BRL", "R$", 2),
+    ("CAD", "CA$", 2),
+    ("CHF", "CHF", 2),
+    ("CNY", "¥", 2),
+    ("CZK", "Kč", 2),
+    ("DKK", "kr", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("INR", "₹", 2),
+    ("JPY", "¥", 0),
+    ("KRW", "₩", 0),
+    ("MXN", "MX$", 2),
+    ("NOK", "kr", 2),
+    ("PLN", "zł", 2),
+    ("RUB", "₽", 2),
+    ("SEK", "kr", 2),
+    ("TRY", "₺", 2),
+    ("UAH", "₴", 2),
+    ("USD", "$", 2),
+];
+
+/// The conventions of a locale, trying its base language if the full tag is not known.
+fn conventions(lang: &str) -> Option<&'static Conventions> {
+    let find = |tag: &str| {
+        LOCALES
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(tag))
+            .map(|(_, c)| c)
+    };
+    find(&lang.replace('_', "-")).or_else(|| find(langs::base(lang)))
+}
+
+/// Formats a number with a fixed number of decimals, using the separators of a locale.
+fn format_decimal(n: f64, decimals: usize, conv: &Conventions) -> String {
+    let formatted = format!("{:.*}", decimals, n.abs());
+    let mut parts = formatted.splitn(2, '.');
+    let int = parts.next().unwrap_or_default();
+    let frac = parts.next();
+
+    let mut res = String::with_capacity(formatted.len() + int.len() / 3 + 1);
+    if n < 0.0 && formatted.chars().any(|c| c != '0' && c != '.') {
+        res.push('-');
+    }
+    push_grouped(&mut res, int, conv.group);
+    if let Some(frac) = frac {
+        res.push(conv.decimal);
+        res.push_str(frac);
+    }
+    res
+}
+
+/// Appends the digits of an integer to `res`, separating groups of thousands with `group`.
+fn push_grouped(res: &mut String, int: &str, group: char) {
+    let first_group = int.len() % 3;
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && i % 3 == first_group {
+            res.push(group);
+        }
+        res.push(digit);
+    }
+}
+
+/// Removes the trailing zeros of the decimals of a number, and its decimal separator if there
+/// are no decimals left.
+fn trim_decimals(mut number: String, decimal: char) -> String {
+    if number.contains(decimal) {
+        while number.ends_with('0') {
+            number.pop();
+        }
+        if number.ends_with(decimal) {
+            number.pop();
+        }
+    }
+    number
+}
+
+/// Formats an integer, separating groups of thousands (`1,234,567`, or `1 234 567` in French).
+pub(crate) fn format_integer(lang: &str, n: i64) -> String {
+    let digits = n.to_string();
+    match conventions(lang) {
+        Some(conv) => {
+            let mut res = String::with_capacity(digits.len() + digits.len() / 3);
+            let digits = match digits.strip_prefix('-') {
+                Some(digits) => {
+                    res.push('-');
+                    digits
+                }
+                None => &digits[..],
+            };
+            push_grouped(&mut res, digits, conv.group);
+            res
+        }
+        None => digits,
+    }
+}
+
+/// Formats a number (`1234567.891` becomes `1,234,567.891`, or `1 234 567,891` in French), with
+/// at most three decimals.
+pub(crate) fn format_number(lang: &str, n: f64) -> String {
+    match conventions(lang) {
+        Some(conv) => trim_decimals(format_decimal(n, 3, conv), conv.decimal),
+        None => trim_decimals(format!("{:.3}", n), '.'),
+    }
+}
+
+/// Formats an amount of money in a given locale, with the symbol of its currency (an ISO 4217
+/// code like `EUR`).
+///
+/// If the locale or the currency are not known, the amount is formatted as `1234.56 EUR`.
+pub(crate) fn format_currency(lang: &str, amount: f64, currency: &str) -> String {
+    let known = CURRENCIES
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(currency));
+    match (conventions(lang), known) {
+        (Some(conv), Some(&(_, symbol, decimals))) => {
+            let number = format_decimal(amount, decimals, conv);
+            let (sign, number) = match number.strip_prefix('-') {
+                Some(number) => ("-", number),
+                None => ("", &number[..]),
+            };
+            match conv.currency {
+                Before => format!("{}{}{}", sign, symbol, number),
+                BeforeSpaced => format!("{}{}{}{}", sign, symbol, NBSP, number),
+                Attached => format!("{}{}{}", sign, number, symbol),
+                After => format!("{}{}{}{}", sign, number, NBSP, symbol),
+            }
+        }
+        (_, known) => {
+            let decimals = known.map_or(2, |&(_, _, decimals)| decimals);
+            format!("{:.*} {}", decimals, amount, currency.to_uppercase())
+        }
+    }
+}
+
+/// Formats a ratio (`0.45`) as a percentage (`45%`, or `45 %` in French), with at most two
+/// decimals.
+pub(crate) fn format_percent(lang: &str, ratio: f64) -> String {
+    let neutral = conv('.', ',', Before, Attached);
+    let conv = conventions(lang).unwrap_or(&neutral);
+    let number = trim_decimals(format_decimal(ratio * 100.0, 2, conv), conv.decimal);
+    match conv.percent {
+        Before => format!("%{}", number),
+        BeforeSpaced => format!("%{}{}", NBSP, number),
+        Attached => format!("{}%", number),
+        After => format!("{}{}%", number, NBSP),
+    }
+}
+
+/// Formats a number in scientific notation (`1.2345E4`, or `1,2345E4` in French).
+pub(crate) fn format_scientific(lang: &str, n: f64) -> String {
+    let formatted = format!("{:e}", n).replace('e', "E");
+    match conventions(lang) {
+        Some(conv) => formatted.replace('.', &conv.decimal.to_string()),
+        None => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_grouped_as_in_the_locale() {
+        assert_eq!(format_integer("en", 1234567), "1,234,567");
+        assert_eq!(format_integer("fr-CA", 1234567), "1\u{202f}234\u{202f}567");
+        assert_eq!(format_integer("de", -1234), "-1.234");
+        assert_eq!(format_integer("en", 123), "123");
+        assert_eq!(format_integer("xx", 1234567), "1234567");
+        assert_eq!(format_number("en", 1234567.891), "1,234,567.891");
+        assert_eq!(format_number("fr", 1234.5), "1\u{202f}234,5");
+        assert_eq!(format_number("de", 1234.0), "1.234");
+        assert_eq!(format_number("en", 0.12345), "0.123");
+        assert_eq!(format_number("en", -0.0001), "0");
+        assert_eq!(format_number("xx", 1234.5), "1234.5");
+    }
+
+    #[test]
+    fn currencies_follow_the_conventions_of_the_locale() {
+        assert_eq!(format_currency("en", 1234.5, "USD"), "$1,234.50");
+        assert_eq!(format_currency("fr-FR", 1234.5, "EUR"), "1\u{202f}234,50\u{a0}€");
+        assert_eq!(format_currency("pt-BR", 1234.5, "BRL"), "R$\u{a0}1.234,50");
+        assert_eq!(format_currency("de-CH", 1234.5, "chf"), "CHF\u{a0}1’234.50");
+        assert_eq!(format_currency("ja", 1234.4, "JPY"), "¥1,234");
+        assert_eq!(format_currency("en", -3.5, "EUR"), "-€3.50");
+        assert_eq!(format_currency("xx", 1234.5, "usd"), "1234.50 USD");
+        assert_eq!(format_currency("en", 2.0, "XYZ"), "2.00 XYZ");
+    }
+
+    #[test]
+    fn percentages_and_scientific_notation_follow_the_locale() {
+        assert_eq!(format_percent("en", 0.45), "45%");
+        assert_eq!(format_percent("fr", 0.4567), "45,67\u{a0}%");
+        assert_eq!(format_percent("tr", 0.5), "%50");
+        assert_eq!(format_percent("xx", 0.125), "12.5%");
+        assert_eq!(format_scientific("en", 12345.0), "1.2345E4");
+        assert_eq!(format_scientific("fr", 12345.0), "1,2345E4");
+        assert_eq!(format_scientific("de", 0.00012), "1,2E-4");
+        assert_eq!(format_scientific("xx", 12345.0), "1.2345E4");
+    }
+}
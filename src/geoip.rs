@@ -0,0 +1,131 @@
+use std::{collections::HashMap, net::IpAddr, path::Path};
+
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+
+use crate::{LocaleRequest, LocaleResolver};
+
+/// The language spoken by most people in the most common countries, by ISO 3166 code.
+const COUNTRIES: &[(&str, &str)] = &[
+    ("AR", "es"),
+    ("AT", "de"),
+    ("AU", "en"),
+    ("BE", "nl"),
+    ("BR", "pt-BR"),
+    ("CA", "en"),
+    ("CH", "de"),
+    ("CL", "es"),
+    ("CN", "zh"),
+    ("CO", "es"),
+    ("CZ", "cs"),
+    ("DE", "de"),
+    ("DK", "da"),
+    ("EG", "ar"),
+    ("ES", "es"),
+    ("FI", "fi"),
+    ("FR", "fr"),
+    ("GB", "en"),
+    ("GR", "el"),
+    ("HU", "hu"),
+    ("ID", "id"),
+    ("IE", "en"),
+    ("IL", "he"),
+    ("IN", "hi"),
+    ("IR", "fa"),
+    ("IT", "it"),
+    ("JP", "ja"),
+    ("KR", "ko"),
+    ("MX", "es"),
+    ("NL", "nl"),
+    ("NO", "nb"),
+    ("NZ", "en"),
+    ("PL", "pl"),
+    ("PT", "pt"),
+    ("RO", "ro"),
+    ("RU", "ru"),
+    ("SA", "ar"),
+    ("SE", "sv"),
+    ("TR", "tr"),
+    ("TW", "zh-TW"),
+    ("UA", "uk"),
+    ("US", "en"),
+    ("VN", "vi"),
+];
+
+/// Guesses the language of a client from its IP address, with a MaxMind GeoIP2 (or GeoLite2)
+/// country database.
+///
+/// It is meant for clients whose `Accept-Language` header is missing or doesn't match any
+/// supported language, so add it to `I18nConfig::chain` after `Resolver::AcceptLanguage`:
+///
+/// ```rust,ignore
+/// let mut chain = Resolver::default_chain();
+/// chain.push(Resolver::Custom(Box::new(
+///     GeoIpResolver::open("GeoLite2-Country.mmdb")?.ip_header("X-Forwarded-For"),
+/// )));
+/// let config = I18nConfig { chain, ..Default::default() };
+/// ```
+///
+/// The language of the most common countries is built-in, and can be changed with `country`.
+/// Clients from other countries are left to the next resolver.
+///
+/// Requires the `geoip` feature.
+pub struct GeoIpResolver {
+    reader: Reader<Vec<u8>>,
+    ip_header: Option<String>,
+    countries: HashMap<String, String>,
+}
+
+impl GeoIpResolver {
+    /// Opens a country database (a `.mmdb` file).
+    pub fn open<P: AsRef<Path>>(database: P) -> Result<GeoIpResolver, MaxMindDBError> {
+        Ok(GeoIpResolver {
+            reader: Reader::open_readfile(database)?,
+            ip_header: None,
+            countries: COUNTRIES
+                .iter()
+                .map(|(country, lang)| ((*country).to_owned(), (*lang).to_owned()))
+                .collect(),
+        })
+    }
+
+    /// Reads the IP address of the client from a header set by a reverse proxy
+    /// (`X-Forwarded-For` or `X-Real-IP`, for instance), instead of using the address of the
+    /// connection. If the header contains a list of addresses, the first one is used.
+    pub fn ip_header<H: Into<String>>(mut self, header: H) -> GeoIpResolver {
+        self.ip_header = Some(header.into());
+        self
+    }
+
+    /// Uses `lang` for clients located in `country` (an ISO 3166 code, like `FR`).
+    pub fn country(mut self, country: &str, lang: &str) -> GeoIpResolver {
+        self.countries
+            .insert(country.to_uppercase(), lang.to_owned());
+        self
+    }
+
+    /// The country of an IP address, if the database knows it.
+    pub fn lookup_country(&self, ip: IpAddr) -> Option<String> {
+        let country = self.reader.lookup::<geoip2::Country>(ip).ok()?;
+        country.country?.iso_code.map(str::to_owned)
+    }
+
+    fn client_ip(&self, request: &LocaleRequest) -> Option<IpAddr> {
+        match &self.ip_header {
+            Some(header) => request
+                .header(header)?
+                .split(',')
+                .next()?
+                .trim()
+                .parse()
+                .ok(),
+            None => request.client_ip(),
+        }
+    }
+}
+
+impl LocaleResolver for GeoIpResolver {
+    fn resolve(&self, request: &LocaleRequest) -> Option<String> {
+        let country = self.lookup_country(self.client_ip(request)?)?;
+        self.countries.get(&country.to_uppercase()).cloned()
+    }
+}
@@ -0,0 +1,166 @@
+//! Catalogs built from flat JSON objects, to share translations with a frontend.
+
+use std::{error::Error, fmt, fs, path::Path};
+
+use serde_json::Value;
+
+#[cfg(feature = "gettext")]
+use crate::{mo, parse_catalog};
+use crate::{Catalog, Translations};
+
+/// Why a JSON catalog couldn't be loaded.
+#[derive(Debug)]
+pub enum JsonError {
+    /// The JSON itself is invalid.
+    Syntax(serde_json::Error),
+    /// The JSON is not an object.
+    NotAnObject,
+    /// The translation of a message is neither a string, nor an array of strings.
+    InvalidTranslation(String),
+    /// The resulting catalog couldn't be parsed by `gettext`.
+    #[cfg(feature = "gettext")]
+    Catalog(gettext::Error),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonError::Syntax(e) => write!(f, "Invalid JSON catalog: {}", e),
+            JsonError::NotAnObject => write!(f, "A JSON catalog must be an object"),
+            JsonError::InvalidTranslation(msg_id) => write!(
+                f,
+                "The translation of \"{}\" must be a string or an array of strings",
+                msg_id
+            ),
+            #[cfg(feature = "gettext")]
+            JsonError::Catalog(e) => write!(f, "Invalid JSON catalog: {}", e),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+/// Builds a catalog from a flat JSON object, mapping each message to its translation:
+///
+/// ```json
+/// {
+///     "Hello, world!": "Bonjour, le monde !",
+///     "One new message": ["{0} nouveau message", "{0} nouveaux messages"],
+///     "menu\u0004File": "Fichier"
+/// }
+/// ```
+///
+/// Messages with a plural form are translated with an array of every form, keyed by their
+/// singular. Messages with a context are keyed by `context\u0004message`, like in `.mo` files.
+/// The plural rule of the catalog is the English one (`n != 1`), unless a `""` entry provides a
+/// gettext header with a `Plural-Forms` field.
+///
+/// Requires the `json` feature.
+pub fn catalog_from_json(json: &Value) -> Result<Catalog, JsonError> {
+    let object = json.as_object().ok_or(JsonError::NotAnObject)?;
+    let mut entries = Vec::with_capacity(object.len());
+    for (msg_id, translation) in object {
+        let forms = match translation {
+            Value::String(translation) => vec![translation.clone()],
+            Value::Array(forms) => forms
+                .iter()
+                .map(|form| form.as_str().map(str::to_owned))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| JsonError::InvalidTranslation(msg_id.clone()))?,
+            _ => return Err(JsonError::InvalidTranslation(msg_id.clone())),
+        };
+        entries.push((msg_id.clone(), forms));
+    }
+    build(entries)
+}
+
+#[cfg(feature = "gettext")]
+fn build(mut entries: Vec<(String, Vec<String>)>) -> Result<Catalog, JsonError> {
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    let entries = entries
+        .into_iter()
+        .map(|(msg_id, forms)| (msg_id, forms.join("\0")))
+        .collect::<Vec<_>>();
+    parse_catalog(&mo::write(&entries)[..], "JSON catalog").map_err(JsonError::Catalog)
+}
+
+#[cfg(not(feature = "gettext"))]
+fn build(entries: Vec<(String, Vec<String>)>) -> Result<Catalog, JsonError> {
+    let mut catalog = Catalog::new();
+    for (key, forms) in entries {
+        let mut parts = key.splitn(2, '\u{4}');
+        match (parts.next(), parts.next()) {
+            (Some(ctx), Some(msg_id)) => catalog.insert(Some(ctx), msg_id, forms),
+            _ => catalog.insert(None, &key, forms),
+        }
+    }
+    Ok(catalog)
+}
+
+/// Loads translations from JSON files: `{dir}/{lang}.json` for each language (see
+/// `catalog_from_json` for their format).
+///
+/// ```rust,ignore
+/// let translations = rocket_i18n::i18n_json("static/locales", vec!["en", "fr"]);
+/// ```
+///
+/// Like `i18n`, it panics if one of them can't be read or parsed.
+///
+/// Requires the `json` feature.
+pub fn i18n_json<P: AsRef<Path>>(dir: P, langs: Vec<&'static str>) -> Translations {
+    langs
+        .into_iter()
+        .map(|lang| {
+            let path = dir.as_ref().join(format!("{}.json", lang));
+            let source = fs::read_to_string(path).expect("Couldn't open catalog");
+            let catalog = serde_json::from_str(&source)
+                .map_err(JsonError::Syntax)
+                .and_then(|json| catalog_from_json(&json))
+                .unwrap_or_else(|e| panic!("Error while loading catalog ({}): {}", lang, e));
+            (lang, catalog)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn catalogs_are_built_from_json_objects() {
+        let catalog = catalog_from_json(&json!({
+            "Hello, world!": "Bonjour, le monde !",
+            "One new message": ["{0} nouveau message", "{0} nouveaux messages"],
+            "menu\u{4}File": "Fichier",
+        }))
+        .unwrap();
+        assert_eq!(catalog.gettext("Hello, world!"), "Bonjour, le monde !");
+        assert_eq!(
+            catalog.ngettext("One new message", "{0} new messages", 1),
+            "{0} nouveau message"
+        );
+        assert_eq!(
+            catalog.ngettext("One new message", "{0} new messages", 0),
+            "{0} nouveaux messages"
+        );
+        assert_eq!(catalog.pgettext("menu", "File"), "Fichier");
+        assert_eq!(catalog.gettext("File"), "File");
+    }
+
+    #[test]
+    fn invalid_catalogs_are_rejected() {
+        assert!(matches!(
+            catalog_from_json(&json!(["Hello"])),
+            Err(JsonError::NotAnObject)
+        ));
+        assert!(matches!(
+            catalog_from_json(&json!({ "Hello": 1 })),
+            Err(JsonError::InvalidTranslation(msg_id)) if msg_id == "Hello"
+        ));
+        assert!(matches!(
+            catalog_from_json(&json!({ "One file": ["Un fichier", null] })),
+            Err(JsonError::InvalidTranslation(msg_id)) if msg_id == "One file"
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+//! Static information about languages.
+
+use std::fmt;
+
+/// Languages written from right to left.
+const RTL: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "khw", "ks", "ps", "sd", "ug", "ur", "yi",
+];
+
+/// The script implied by the region of languages written in several scripts, when the tag
+/// doesn't have a script subtag.
+const IMPLIED_SCRIPTS: &[(&str, &str, &str)] = &[
+    ("zh", "CN", "Hans"),
+    ("zh", "HK", "Hant"),
+    ("zh", "MO", "Hant"),
+    ("zh", "MY", "Hans"),
+    ("zh", "SG", "Hans"),
+    ("zh", "TW", "Hant"),
+];
+
+/// The name of languages in their own language, as they should appear in a language picker.
+///
+/// Regional variants are listed when their name differs from the one of their base language.
+const NAMES: &[(&str, &str)] = &[
+    ("af", "Afrikaans"),
+    ("am", "አማርኛ"),
+    ("ar", "العربية"),
+    ("ast", "Asturianu"),
+    ("az", "Azərbaycan"),
+    ("be", "Беларуская"),
+    ("bg", "Български"),
+    ("bn", "বাংলা"),
+    ("br", "Brezhoneg"),
+    ("bs", "Bosanski"),
+    ("ca", "Català"),
+    ("ckb", "کوردیی ناوەندی"),
+    ("cs", "Čeština"),
+    ("cy", "Cymraeg"),
+    ("da", "Dansk"),
+    ("de", "Deutsch"),
+    ("de-AT", "Österreichisches Deutsch"),
+    ("de-CH", "Schweizer Hochdeutsch"),
+    ("el", "Ελληνικά"),
+    ("en", "English"),
+    ("en-GB", "British English"),
+    ("en-US", "American English"),
+    ("eo", "Esperanto"),
+    ("es", "Español"),
+    ("es-MX", "Español de México"),
+    ("et", "Eesti"),
+    ("eu", "Euskara"),
+    ("fa", "فارسی"),
+    ("fi", "Suomi"),
+    ("fil", "Filipino"),
+    ("fr", "Français"),
+    ("fr-CA", "Français canadien"),
+    ("ga", "Gaeilge"),
+    ("gd", "Gàidhlig"),
+    ("gl", "Galego"),
+    ("gu", "ગુજરાતી"),
+    ("he", "עברית"),
+    ("hi", "हिन्दी"),
+    ("hr", "Hrvatski"),
+    ("hu", "Magyar"),
+    ("hy", "Հայերեն"),
+    ("id", "Indonesia"),
+    ("is", "Íslenska"),
+    ("it", "Italiano"),
+    ("ja", "日本語"),
+    ("ka", "ქართული"),
+    ("kab", "Taqbaylit"),
+    ("kk", "Қазақ тілі"),
+    ("km", "ខ្មែរ"),
+    ("kn", "ಕನ್ನಡ"),
+    ("ko", "한국어"),
+    ("ku", "Kurdî"),
+    ("lb", "Lëtzebuergesch"),
+    ("lt", "Lietuvių"),
+    ("lv", "Latviešu"),
+    ("mk", "Македонски"),
+    ("ml", "മലയാളം"),
+    ("mn", "Монгол"),
+    ("mr", "मराठी"),
+    ("ms", "Melayu"),
+    ("my", "မြန်မာ"),
+    ("nb", "Norsk bokmål"),
+    ("ne", "नेपाली"),
+    ("nl", "Nederlands"),
+    ("nn", "Norsk nynorsk"),
+    ("no", "Norsk"),
+    ("oc", "Occitan"),
+    ("pa", "ਪੰਜਾਬੀ"),
+    ("pl", "Polski"),
+    ("ps", "پښتو"),
+    ("pt", "Português"),
+    ("pt-BR", "Português do Brasil"),
+    ("pt-PT", "Português europeu"),
+    ("ro", "Română"),
+    ("ru", "Русский"),
+    ("si", "සිංහල"),
+    ("sk", "Slovenčina"),
+    ("sl", "Slovenščina"),
+    ("sq", "Shqip"),
+    ("sr", "Српски"),
+    ("sr-Latn", "Srpski"),
+    ("sv", "Svenska"),
+    ("sw", "Kiswahili"),
+    ("ta", "தமிழ்"),
+    ("te", "తెలుగు"),
+    ("th", "ไทย"),
+    ("tr", "Türkçe"),
+    ("uk", "Українська"),
+    ("ur", "اردو"),
+    ("uz", "O‘zbek"),
+    ("vi", "Tiếng Việt"),
+    ("zh", "中文"),
+    ("zh-CN", "简体中文"),
+    ("zh-Hans", "简体中文"),
+    ("zh-Hant", "繁體中文"),
+    ("zh-HK", "繁體中文（香港）"),
+    ("zh-TW", "繁體中文（台灣）"),
+];
+
+/// The base language of a tag (`pt` for `pt-BR` or `pt_BR`).
+pub(crate) fn base(lang: &str) -> &str {
+    lang.split(['-', '_']).next().unwrap_or(lang)
+}
+
+/// The script of a tag: its script subtag (`Hant` for `zh-Hant-TW`, `Latn` for `sr-Latn`), or
+/// the script implied by its region (`Hant` for `zh-TW`), if any.
+pub(crate) fn script(lang: &str) -> Option<&str> {
+    let mut subtags = lang.split(['-', '_']);
+    let base = subtags.next()?;
+    let subtag = subtags.next()?;
+    if subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Some(subtag);
+    }
+    IMPLIED_SCRIPTS
+        .iter()
+        .find(|(l, region, _)| l.eq_ignore_ascii_case(base) && region.eq_ignore_ascii_case(subtag))
+        .map(|(_, _, script)| *script)
+}
+
+/// Whether a language is written from right to left.
+pub(crate) fn is_rtl(lang: &str) -> bool {
+    RTL.contains(&base(lang).to_lowercase().as_str())
+}
+
+/// The value of the HTML `dir` attribute for a language: `"rtl"` or `"ltr"`.
+pub(crate) fn dir(lang: &str) -> &'static str {
+    Dir::of(lang).as_str()
+}
+
+/// The direction in which a language is written.
+///
+/// It displays as the value of the HTML `dir` attribute (`ltr` or `rtl`), so it can be used
+/// as is in templates: `<html dir="{{ i18n.direction() }}">`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dir {
+    /// From left to right, like English.
+    Ltr,
+    /// From right to left, like Arabic or Hebrew.
+    Rtl,
+}
+
+impl Dir {
+    /// The direction of a language.
+    pub fn of(lang: &str) -> Dir {
+        if is_rtl(lang) {
+            Dir::Rtl
+        } else {
+            Dir::Ltr
+        }
+    }
+
+    /// The value of the HTML `dir` attribute for this direction: `"ltr"` or `"rtl"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Dir::Ltr => "ltr",
+            Dir::Rtl => "rtl",
+        }
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The name of a language in this language (`Polski` for `pl`), to show it in a language
+/// picker.
+///
+/// Regional variants without a name of their own (`pl-PL`) get the name of their base language.
+/// `None` means that the language is not in the built-in table, which covers the most common
+/// languages.
+///
+/// ```rust,ignore
+/// assert_eq!(rocket_i18n::lang_name("pt-BR"), Some("Português do Brasil"));
+/// assert_eq!(rocket_i18n::lang_name("fr-BE"), Some("Français"));
+/// ```
+pub fn lang_name(lang: &str) -> Option<&'static str> {
+    let name = |tag: &str| {
+        NAMES
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(tag))
+            .map(|(_, name)| *name)
+    };
+    name(&lang.replace('_', "-")).or_else(|| name(base(lang)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn languages_are_named_in_their_own_language() {
+        assert_eq!(lang_name("pl"), Some("Polski"));
+        assert_eq!(lang_name("pt-BR"), Some("Português do Brasil"));
+        assert_eq!(lang_name("pt_br"), Some("Português do Brasil"));
+        assert_eq!(lang_name("pt-AO"), Some("Português"));
+        assert_eq!(lang_name("zh-TW"), Some("繁體中文（台灣）"));
+        assert_eq!(lang_name("xx"), None);
+
+        let translations = testing::translations(&["pl", "xx"]);
+        assert_eq!(translations.for_request("pl").unwrap().lang_name(), "Polski");
+        assert_eq!(translations.for_request("xx").unwrap().lang_name(), "xx");
+    }
+
+    #[test]
+    fn right_to_left_languages_are_detected() {
+        assert_eq!(Dir::of("ar-EG"), Dir::Rtl);
+        assert_eq!(Dir::of("HE"), Dir::Rtl);
+        assert_eq!(Dir::of("fa_IR"), Dir::Rtl);
+        assert_eq!(Dir::of("en"), Dir::Ltr);
+        assert_eq!(Dir::of("xx"), Dir::Ltr);
+        assert_eq!(Dir::Rtl.to_string(), "rtl");
+
+        let translations = testing::translations(&["en", "ar"]);
+        let ar = translations.for_request("ar").unwrap();
+        assert_eq!(ar.direction(), Dir::Rtl);
+        assert!(ar.is_rtl());
+        assert_eq!(translations.for_request("en").unwrap().direction().as_str(), "ltr");
+    }
+}
@@ -0,0 +1,76 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use log::warn;
+use once_cell::sync::OnceCell;
+
+use crate::{parse_catalog, Catalog, Error};
+
+/// The catalog of a language, loaded the first time a request needs it.
+pub(crate) struct LazyCatalog {
+    path: PathBuf,
+    catalog: OnceCell<Option<Arc<Catalog>>>,
+}
+
+impl LazyCatalog {
+    pub fn new(path: PathBuf) -> LazyCatalog {
+        LazyCatalog {
+            path,
+            catalog: OnceCell::new(),
+        }
+    }
+
+    /// The catalog, loading it if it was not loaded yet.
+    ///
+    /// If it can't be loaded, the error is logged, and the failure is kept: the language is
+    /// considered as not having a catalog from then on (negotiation skips it), and the file is not
+    /// read again for every request.
+    pub fn get(&self, lang: &str) -> Option<&Arc<Catalog>> {
+        self.catalog
+            .get_or_init(|| {
+                let catalog = fs::File::open(&self.path)
+                    .map_err(Error::from)
+                    .and_then(|mo_file| parse_catalog(mo_file, lang));
+                match catalog {
+                    Ok(catalog) => Some(Arc::new(catalog)),
+                    Err(e) => {
+                        warn!(
+                            "Couldn't load the catalog of {} ({}): {}",
+                            lang,
+                            self.path.display(),
+                            e
+                        );
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{mo_file, temp_dir};
+    use crate::{negotiate_catalog, Loader};
+    use std::fs;
+
+    #[test]
+    fn languages_that_fail_to_load_are_skipped() {
+        let dir = temp_dir("lazy");
+        fs::write(dir.join("fr.mo"), mo_file(Some("fr"), "Bonjour")).unwrap();
+
+        let translations = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into())
+            .lazy(vec!["de", "fr"]);
+        let (lang, catalog) = negotiate_catalog("de, fr;q=0.5", &translations).unwrap();
+        assert_eq!(lang, "fr");
+        assert_eq!(catalog.gettext("Hello"), "Bonjour");
+        assert!(translations.find("de").is_none());
+        assert!(translations.for_request("de").is_none());
+
+        // The failure is kept: the catalog is not read again
+        fs::write(dir.join("de.mo"), mo_file(Some("de"), "Hallo")).unwrap();
+        assert!(translations.get("de").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -11,7 +11,7 @@
 //! ```toml
 //! [dependencies]
 //! rocket_i18n = "0.4"
-//! gettext-macros = "0.1" # Provides proc-macros to manage translations
+//! gettext-macros = "0.6" # Provides proc-macros to manage translations
 //! ```
 //!
 //! Then, in your `main.rs`, add the translations to you application's data:
@@ -32,11 +32,11 @@
 //! }
 //! ```
 //!
-//! For strings that may have a plural form, just add the plural and the number of element to the
-//! arguments
+//! For strings that may have a plural form, just add the plural to the arguments, and the number
+//! of elements after a `;`
 //!
 //! ```rust,ignore
-//! i18n!(i18n.catalog, "One new message", "{0} new messages", 42);
+//! i18n!(i18n.catalog, "One new message", "{0} new messages"; 42);
 //! ```
 //!
 //! Any extra argument, after a `;`, will be used for formatting.
@@ -49,54 +49,1948 @@
 //! When using it with plural, `{0}` will be the number of elements, and other arguments will start
 //! at `{1}`.
 //!
+//! In right-to-left languages, the number is wrapped in bidi isolates, so that its digits are
+//! displayed on the right side (see `I18nCatalog::ngettext`).
+//!
+//! Short strings can mean different things depending on where they are used ("Open" in a menu,
+//! or as the state of an issue). Give them a context (their `msgctxt`) to translate them
+//! separately. In templates, use `t!(ctx "file menu", "Open")`.
+//!
+//! ```rust,ignore
+//! i18n!(i18n.catalog, context = "file menu", "Open");
+//! i18n!(i18n.catalog, context = "inbox", "One message", "{0} messages"; 42);
+//! ```
+//!
+//! These arguments are inserted as is. If the result ends up in HTML, use `i18n_escaped` (or
+//! `t_escaped` in templates) instead: it takes the same arguments, but escapes every formatting
+//! argument before inserting it.
+//!
+//! ```rust,ignore
+//! let user_name = "<script>alert('Hi!')</script>";
+//! i18n_escaped!(i18n.catalog, "Hello {0}!"; user_name); // Hello &lt;script&gt;…
+//! ```
+//!
 //! Because of its design, rocket_i18n is only compatible with askama, ructe or compiled templates
 //! in general.
 //! You can use the `t` macro in your templates, as long as they have a field called `catalog` to
 //! store your catalog.
+//!
+//! ## Finding unused translations
+//!
+//! With the `track-access` feature, every message that is looked up through `I18n::catalog` is
+//! recorded, and `Translations::accessed_keys` returns all of them (messages with a context are
+//! recorded as `context\u{4}message`, like in `.mo` files). After running your application on
+//! representative traffic (or your test suite), the messages of your catalogs that are missing
+//! from this set are probably not used anymore.
+//!
+//! This feature is meant for development: without it, lookups are not recorded at all.
+//!
+//! ## Caching lookups
+//!
+//! Messages of languages with overlays or a fallback chain may be looked up in several catalogs
+//! before one translates them. With the `lookup-cache` feature, the translations of the most
+//! recently used messages are remembered in a small LRU cache shared by all the requests (see
+//! `Translations::with_lookup_cache_size`), which is emptied whenever catalogs change.
+//!
+//! ## Without gettext
+//!
+//! The `gettext` feature (enabled by default) is only needed to load `.mo` files. Without it,
+//! `Catalog` is a minimal built-in type that is filled in memory with `Catalog::insert`, and
+//! everything else works the same way. Code that only translates messages can use the `Lookup`
+//! trait to work with any catalog.
 
 
+#[cfg(not(feature = "gettext"))]
+pub use crate::builtin::Catalog;
+pub use crate::{
+    builder::CatalogBuilder,
+    catalog::{I18nCatalog, Lookup, WithCatalog},
+    config::{FailureResponse, GeoFallback, I18nConfig, I18nConfigBuilder, OnFailure},
+    langs::{lang_name, Dir},
+    locale::Locale,
+    message::LazyTranslation,
+    negotiation::{negotiate_catalog, negotiate_language},
+    resolver::{LocaleRequest, LocaleResolver, Resolver},
+};
+#[cfg(any(
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+pub use crate::user::UserRequest;
+#[cfg(all(feature = "gettext", feature = "tokio"))]
+pub use crate::load_async::i18n_async;
+#[cfg(feature = "backend")]
+pub use crate::backend::{
+    catalog_from_backend, i18n_backend, BackendError, BackendFuture, CatalogBackend, StoredMessage,
+};
+#[cfg(all(feature = "backend", feature = "gettext"))]
+pub use crate::backend::reload_from_backend;
+#[cfg(feature = "postgres")]
+pub use crate::backend::PostgresBackend;
+#[cfg(feature = "chrono")]
+pub use crate::date::DateStyle;
+#[cfg(feature = "embed")]
+pub use crate::embed::{i18n_embed, try_i18n_embed};
+#[cfg(feature = "fluent")]
+pub use crate::fluent::{i18n_fluent, FluentCatalog};
+#[cfg(feature = "fluent")]
+pub use fluent_bundle::{FluentArgs, FluentValue};
+#[cfg(feature = "geoip")]
+pub use crate::geoip::GeoIpResolver;
+#[cfg(feature = "json")]
+pub use crate::json::{catalog_from_json, i18n_json, JsonError};
+#[cfg(feature = "serde")]
+pub use crate::localized::{Localized, SerializeLocalized};
+#[cfg(feature = "mmap")]
+pub use crate::mmap::MappedCatalog;
+#[cfg(feature = "remote")]
+pub use crate::remote::{RemoteCatalogs, RemoteError};
+#[cfg(feature = "gettext")]
+pub use crate::{
+    error::I18nError,
+    loader::Loader,
+    reload::ReloadableTranslations,
+    validate::{validate_placeholders, PlaceholderMismatch},
+};
+#[cfg(feature = "gettext")]
 pub use gettext::*;
-use std::fs;
+use log::warn;
+#[cfg(feature = "track-access")]
+use std::collections::HashSet;
+#[cfg(feature = "lazy")]
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+#[cfg(any(
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+use std::future::Future;
+#[cfg(feature = "gettext")]
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+use std::{
+    fmt,
+    iter::{self, FromIterator},
+    ops::Deref,
+    slice,
+    sync::Arc,
+};
+
+#[cfg(feature = "backend")]
+mod backend;
+#[cfg(not(feature = "gettext"))]
+mod builtin;
+mod builder;
+mod catalog;
+mod config;
+#[cfg(feature = "chrono")]
+mod date;
+#[cfg(feature = "embed")]
+mod embed;
+#[cfg(feature = "gettext")]
+mod error;
+#[cfg(any(
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket"
+))]
+mod error_body;
+#[cfg(feature = "fluent")]
+mod fluent;
+#[cfg(feature = "format")]
+mod format;
+#[cfg(feature = "geoip")]
+mod geoip;
+mod langs;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "lazy")]
+mod lazy;
+#[cfg(feature = "gettext")]
+mod loader;
+#[cfg(all(feature = "gettext", feature = "tokio"))]
+mod load_async;
+mod locale;
+#[cfg(feature = "serde")]
+mod localized;
+mod message;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "gettext")]
+mod mo;
+mod negotiation;
+mod plural;
+#[cfg(feature = "po")]
+mod po;
+#[cfg(feature = "pseudo")]
+mod pseudo;
+#[cfg(feature = "gettext")]
+mod reload;
+#[cfg(feature = "remote")]
+mod remote;
+mod resolver;
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+mod signing;
+#[cfg(feature = "spell")]
+mod spell;
+#[cfg(any(feature = "actix-web", feature = "rocket"))]
+mod switch;
+pub mod testing;
+#[cfg(feature = "gettext")]
+mod validate;
+#[cfg(any(
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+mod user;
 
 #[cfg(feature = "actix-web")]
 mod with_actix;
+#[cfg(feature = "actix-web")]
+pub use crate::with_actix::vary_accept_language;
+
+#[cfg(feature = "actix-web-4")]
+mod with_actix4;
+#[cfg(feature = "actix-web-4")]
+pub use crate::with_actix4::{VaryAcceptLanguage, VaryAcceptLanguageMiddleware};
+
+#[cfg(feature = "axum")]
+mod with_axum;
+#[cfg(feature = "axum")]
+pub use crate::with_axum::I18nRejection;
+
+#[cfg(feature = "tower")]
+mod with_tower;
+#[cfg(feature = "tower")]
+pub use crate::with_tower::{I18nLayer, I18nService};
+
+#[cfg(feature = "warp")]
+pub mod warp;
+
+#[cfg(feature = "poem")]
+mod with_poem;
+
+#[cfg(feature = "tide")]
+mod with_tide;
+#[cfg(feature = "tide")]
+pub use crate::with_tide::{I18nMiddleware, RequestExt};
+
+#[cfg(feature = "handlebars")]
+mod with_handlebars;
+#[cfg(feature = "handlebars")]
+pub use crate::with_handlebars::register_handlebars;
+
+#[cfg(feature = "maud")]
+mod with_maud;
+#[cfg(feature = "maud")]
+#[doc(hidden)]
+pub use maud as __maud;
+
+#[cfg(feature = "tera")]
+mod with_tera;
+#[cfg(feature = "tera")]
+pub use crate::with_tera::register_tera;
 
 #[cfg(feature = "rocket")]
 mod with_rocket;
+#[cfg(feature = "rocket")]
+pub use crate::with_rocket::{catcher, GuardError, I18nFairing};
+#[cfg(all(feature = "rocket", feature = "gettext"))]
+pub use crate::with_rocket::TranslationsFairing;
 
-const ACCEPT_LANG: &'static str = "Accept-Language";
+/// Ready-made handlers to switch the language of the user.
+#[cfg(any(feature = "actix-web", feature = "rocket"))]
+pub mod routes {
+    #[cfg(feature = "actix-web")]
+    pub use crate::with_actix::set_lang_service;
+    #[cfg(feature = "rocket")]
+    pub use crate::with_rocket::set_lang;
+}
+
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+const ACCEPT_LANG: &str = "Accept-Language";
 
 /// A request guard to get the right translation catalog for the current request.
+#[derive(Clone)]
 pub struct I18n {
     /// The catalog containing the translated messages, in the correct locale for this request.
-    pub catalog: Catalog,
+    ///
+    /// It is shared with the other requests, not copied: with a catalog of 5,000 messages,
+    /// extracting the `I18n` of a request takes about 0.5 µs, where copying the catalog took
+    /// about 1 ms (see `benches/for_request.rs`).
+    pub catalog: I18nCatalog,
+    /// The language of the current request.
+    pub lang: &'static str,
+    /// All the supported languages, in order of preference.
+    langs: Arc<Vec<&'static str>>,
+    /// The other gettext domains, registered with `Translations::with_domain`.
+    domains: Arc<Vec<(&'static str, Translations)>>,
+    /// The Fluent messages of this language, if any.
+    #[cfg(feature = "fluent")]
+    fluent: Option<Arc<FluentCatalog>>,
+}
+
+impl I18n {
+    /// Formats a Fluent message in the language of the current request. See `fluent!`.
+    ///
+    /// If there are no Fluent messages for this language, or if this message doesn't exist, its
+    /// id is returned.
+    ///
+    /// Requires the `fluent` feature.
+    #[cfg(feature = "fluent")]
+    pub fn fluent(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        match &self.fluent {
+            Some(catalog) => catalog.format(id, args),
+            None => id.to_owned(),
+        }
+    }
+
+    /// All the supported languages, in order of preference, to list them in a language picker
+    /// for instance.
+    pub fn available_langs(&self) -> &[&'static str] {
+        &self.langs
+    }
+
+    /// The catalog of another gettext domain, registered with `Translations::with_domain`, in
+    /// the language of the current request.
+    ///
+    /// ```rust,ignore
+    /// i18n!(i18n.domain("emails"), "Welcome to Plume!")
+    /// ```
+    ///
+    /// If this domain is unknown, or has no catalog for this language, messages are left
+    /// untranslated.
+    pub fn domain(&self, name: &str) -> I18nCatalog {
+        self.domains
+            .iter()
+            .find(|domain| domain.0 == name)
+            .and_then(|domain| domain.1.for_request(self.lang))
+            .map(|i18n| i18n.catalog)
+            .unwrap_or_else(|| I18nCatalog {
+                catalog: Arc::new(empty_catalog()),
+                overlays: Vec::new(),
+                fallbacks: Vec::new(),
+                lang: self.lang,
+                on_missing: None,
+                #[cfg(feature = "lookup-cache")]
+                lookups: None,
+                isolated: self.catalog.isolated.clone(),
+                #[cfg(feature = "pseudo")]
+                pseudo: self.catalog.pseudo.clone(),
+                #[cfg(feature = "track-access")]
+                accessed: Default::default(),
+            })
+    }
+
+    /// Returns every plural form of a message in the current language, in the order in which
+    /// the catalog defines them (two for English, three for Polish, only one for Japanese…).
+    ///
+    /// The number of forms is read from the `nplurals` value of the `Plural-Forms` header of the
+    /// catalog. If the message isn't translated, `singular` and `plural` are used instead.
+    pub fn all_plural_forms(&self, singular: &str, plural: &str) -> Vec<String> {
+        let forms = plural::PluralForms::of(&self.catalog);
+        (0..forms.nplurals)
+            .map(|i| match forms.example(i) {
+                Some(n) => self.catalog.ngettext(singular, plural, n).to_owned(),
+                None => plural.to_owned(),
+            })
+            .collect()
+    }
+
+    /// Returns a function translating messages with the catalog of the current request, for code
+    /// that isn't a template struct but needs to translate a lot of strings.
+    ///
+    /// ```rust,ignore
+    /// let tr = i18n.translator();
+    /// let menu = vec![tr("Home"), tr("Settings"), tr("Log out")];
+    /// ```
+    pub fn translator(&self) -> impl Fn(&str) -> String + '_ {
+        move |msg_id| self.catalog.gettext(msg_id).to_owned()
+    }
+
+    /// Same as `translator`, but for messages with a plural form: the returned function takes
+    /// the singular, the plural and the number of elements.
+    pub fn plural_translator(&self) -> impl Fn(&str, &str, u64) -> String + '_ {
+        move |msg_id, msg_id_plural, n| {
+            self.catalog
+                .ngettext(msg_id, msg_id_plural, n)
+                .to_owned()
+        }
+    }
+
+    /// Translates a message that has a plural form, and inserts the number of elements in place
+    /// of `{0}`.
+    ///
+    /// In right-to-left languages, the number is wrapped in Unicode bidi isolates (U+2068 and
+    /// U+2069), so that its digits are displayed on the right side of the surrounding text, like
+    /// with `i18n!` and `t!` (see `I18nCatalog::ngettext`). Nothing changes for left-to-right
+    /// languages.
+    pub fn plural(&self, msg_id: &str, msg_id_plural: &str, n: u64) -> String {
+        self.catalog
+            .ngettext(msg_id, msg_id_plural, n)
+            .replace("{0}", &n.to_string())
+    }
+
+    /// Same as `plural`, for a message with a context (`msgctxt`). See `I18nCatalog::npgettext`.
+    pub fn plural_in_context(
+        &self,
+        msg_context: &str,
+        msg_id: &str,
+        msg_id_plural: &str,
+        n: u64,
+    ) -> String {
+        self.catalog
+            .npgettext(msg_context, msg_id, msg_id_plural, n)
+            .replace("{0}", &n.to_string())
+    }
+
+    /// The name of the language of the request, in this language (`Polski` for `pl`). See
+    /// `lang_name`.
+    ///
+    /// Languages that are not in the built-in table are named by their tag.
+    pub fn lang_name(&self) -> &'static str {
+        lang_name(self.lang).unwrap_or(self.lang)
+    }
+
+    /// The direction in which the language of the request is written, for the `dir` attribute of
+    /// HTML elements.
+    pub fn direction(&self) -> Dir {
+        Dir::of(self.lang)
+    }
+
+    /// Whether the language of the request is written from right to left.
+    pub fn is_rtl(&self) -> bool {
+        langs::is_rtl(self.lang)
+    }
+
+    /// The language of the request, split into its language, script and region, to make
+    /// decisions that depend on the region (`en-GB` or `en-US`) for instance.
+    pub fn locale(&self) -> Locale {
+        Locale::new(self.lang)
+    }
+
+    /// Translates a message that has several variants, chosen by a value known at runtime (the
+    /// gender of someone, for instance).
+    ///
+    /// `variants` associates each possible value of `selector` with the source text of the
+    /// variant to use. If `selector` is not one of them, the `"other"` variant is used (or the
+    /// first one if there is no `"other"` variant).
+    ///
+    /// Each variant is a normal catalog entry, with `msg_id` as its context (`msgctxt`) and its
+    /// source text as its `msgid`, so translators see them as alternatives of the same message.
+    /// Untranslated variants are returned as is.
+    ///
+    /// ```rust,ignore
+    /// i18n.select("post-liked", &user.gender, &[
+    ///     ("male", "He liked your post"),
+    ///     ("female", "She liked your post"),
+    ///     ("other", "They liked your post"),
+    /// ])
+    /// ```
+    pub fn select<'a>(
+        &'a self,
+        msg_id: &'a str,
+        selector: &str,
+        variants: &[(&str, &'a str)],
+    ) -> &'a str {
+        let variant = variants
+            .iter()
+            .find(|v| v.0 == selector)
+            .or_else(|| variants.iter().find(|v| v.0 == "other"))
+            .or_else(|| variants.first());
+        match variant {
+            Some(variant) => self.catalog.pgettext(msg_id, variant.1),
+            None => msg_id,
+        }
+    }
+
+    /// The number of plural forms of the current language, as declared by the `nplurals` value
+    /// of the `Plural-Forms` header of its catalog.
+    ///
+    /// It defaults to 2 if the header is missing or can't be parsed.
+    pub fn nplurals(&self) -> usize {
+        plural::PluralForms::of(&self.catalog).nplurals
+    }
+
+    /// Formats an integer in the current language, separating groups of thousands:
+    /// `1,234,567` in English, `1 234 567` in French.
+    ///
+    /// Languages whose conventions are not built-in get `1234567`.
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format_integer(&self, n: i64) -> String {
+        format::format_integer(self.lang, n)
+    }
+
+    /// Formats a number in the current language, with its grouping and decimal separators:
+    /// `1234567.891` becomes `1,234,567.891` in English, and `1 234 567,891` in French. At most
+    /// three decimals are kept, without trailing zeros.
+    ///
+    /// Languages whose conventions are not built-in get `1234567.891`.
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format_number(&self, n: f64) -> String {
+        format::format_number(self.lang, n)
+    }
+
+    /// Formats a date in the current language, with the names of its months and days of the
+    /// week: `Tuesday, March 5, 2024` in English, `mardi 5 mars 2024` in French (with
+    /// `DateStyle::Long`).
+    ///
+    /// It accepts any `chrono` value with a date (`NaiveDate`, `DateTime`, …). The conventions
+    /// of the most common languages are built-in. The other ones get `2024-03-05`.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn format_date<D: chrono::Datelike>(&self, date: &D, style: DateStyle) -> String {
+        date::format_date(self.lang, date, style)
+    }
+
+    /// Formats a date and a time (hours and minutes) in the current language:
+    /// `Mar 5, 2024, 2:30 PM` in English, `5 mars 2024, 14:30` in French (with
+    /// `DateStyle::Medium`). See `format_date`.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn format_datetime<T: chrono::Datelike + chrono::Timelike>(
+        &self,
+        datetime: &T,
+        style: DateStyle,
+    ) -> String {
+        date::format_datetime(self.lang, datetime, style)
+    }
+
+    /// Formats an amount of money in the current language, with the symbol of its currency (an
+    /// ISO 4217 code like `EUR` or `USD`): `$1,234.56` in English, `1 234,56 €` in French.
+    ///
+    /// The conventions of the most common languages and currencies are built-in. For the other
+    /// ones, the amount is formatted as `1234.56 USD`.
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format_currency(&self, amount: f64, currency: &str) -> String {
+        format::format_currency(self.lang, amount, currency)
+    }
+
+    /// Formats a ratio as a percentage in the current language: `0.45` becomes `45%` in English,
+    /// but `45 %` in French. At most two decimals are kept.
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format_percent(&self, ratio: f64) -> String {
+        format::format_percent(self.lang, ratio)
+    }
+
+    /// Formats a number in scientific notation, with the decimal separator of the current
+    /// language (`1.2345E4` in English, `1,2345E4` in French).
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format_scientific(&self, n: f64) -> String {
+        format::format_scientific(self.lang, n)
+    }
+
+    /// Spells out a number in the current language ("forty-two").
+    ///
+    /// Only numbers below 1000 are supported, in English, French and German. In other languages,
+    /// or for bigger numbers, the number is written with digits.
+    ///
+    /// Requires the `spell` feature.
+    #[cfg(feature = "spell")]
+    pub fn spell_number(&self, n: u64) -> String {
+        spell::spell_number(self.lang, n).unwrap_or_else(|| n.to_string())
+    }
+
+    /// Removes the language of the request from the start of a path, if it is there:
+    /// `/fr/posts/1` becomes `/posts/1` for a request in French. Other paths are returned as is.
+    ///
+    /// It is meant to be used with `I18nConfig::path_prefix`, to get the path of the requested
+    /// page without its language.
+    pub fn strip_lang_prefix<'a>(&self, path: &'a str) -> &'a str {
+        let trimmed = path.trim_start_matches('/');
+        match trimmed.find('/') {
+            Some(end) if trimmed[..end].eq_ignore_ascii_case(self.lang) => &trimmed[end..],
+            None if trimmed.eq_ignore_ascii_case(self.lang) => "/",
+            _ => path,
+        }
+    }
+
+    /// Creates the `I18n` of a request in `lang`, translated with `catalog`, to call handlers in
+    /// unit tests. See also the `testing` module.
+    pub fn new_for_tests(lang: &'static str, catalog: Catalog) -> I18n {
+        Translations::from(vec![(lang, catalog)])
+            .for_request(lang)
+            .expect("The language of the catalog is supported")
+    }
+
+    /// Creates the context to give to a template. See `I18nContext`.
+    pub fn context(&self) -> I18nContext {
+        I18nContext {
+            catalog: self.catalog.clone(),
+            lang: self.lang,
+            dir: langs::dir(self.lang),
+            langs: self.langs.clone(),
+        }
+    }
+}
+
+/// What a template needs to be translated: the catalog to use with `t!`, and the language and
+/// text direction to use in `lang` and `dir` attributes.
+///
+/// It is meant to be embedded in your template structs. Dereferencing them to it makes its
+/// `catalog` field available to `t!`:
+///
+/// ```rust,ignore
+/// #[derive(Template)]
+/// #[template(path = "index.html")] // <html lang="{{ lang }}" dir="{{ dir }}">…
+/// struct Index {
+///     i18n: I18nContext,
+/// }
+///
+/// impl Deref for Index {
+///     type Target = I18nContext;
+///
+///     fn deref(&self) -> &I18nContext {
+///         &self.i18n
+///     }
+/// }
+///
+/// fn index(i18n: I18n) -> Index {
+///     Index { i18n: i18n.into() }
+/// }
+/// ```
+pub struct I18nContext {
+    /// The catalog containing the translated messages.
+    pub catalog: I18nCatalog,
     /// The language of the current request.
     pub lang: &'static str,
+    /// The direction of the language of the current request: `"ltr"` or `"rtl"`.
+    pub dir: &'static str,
+    langs: Arc<Vec<&'static str>>,
 }
 
-pub type Translations = Vec<(&'static str, Catalog)>;
+impl I18nContext {
+    /// All the supported languages, in order of preference. See `I18n::available_langs`.
+    pub fn available_langs(&self) -> &[&'static str] {
+        &self.langs
+    }
+}
+
+impl From<I18n> for I18nContext {
+    fn from(i18n: I18n) -> I18nContext {
+        I18nContext {
+            catalog: i18n.catalog,
+            lang: i18n.lang,
+            dir: langs::dir(i18n.lang),
+            langs: i18n.langs,
+        }
+    }
+}
+
+/// A request guard like `I18n`, that never fails: if there are no translations in the state of
+/// the application, or if the negotiated language has no catalog, the catalog of the default
+/// language is used instead (or no catalog at all, leaving messages untranslated).
+///
+/// It is meant for the routes that should work anyway, like health checks or APIs. To know
+/// whether the language could be negotiated, use an `Option<I18n>` instead.
+///
+/// ```rust,ignore
+/// #[get("/health")]
+/// fn health(i18n: DefaultI18n) -> String {
+///     i18n!(i18n.catalog, "Everything is fine")
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DefaultI18n(pub I18n);
+
+impl DefaultI18n {
+    /// The `I18n` of the default language of `langs`, or an untranslated one.
+    #[cfg(any(
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket"
+    ))]
+    pub(crate) fn fallback(
+        langs: Option<&Translations>,
+        config: Option<&I18nConfig>,
+    ) -> DefaultI18n {
+        let lang = langs.map_or("en", |langs| negotiation::default_lang(langs, config));
+        let i18n = langs.and_then(|langs| langs.for_request(lang)).unwrap_or_else(|| {
+            Translations::from(vec![(lang, empty_catalog())])
+                .for_request(lang)
+                .expect("The language of the catalog is supported")
+        });
+        DefaultI18n(i18n)
+    }
+}
+
+impl Deref for DefaultI18n {
+    type Target = I18n;
+
+    fn deref(&self) -> &I18n {
+        &self.0
+    }
+}
+
+/// The catalogs of all the languages supported by an application, in order of preference.
+///
+/// It can be loaded from `.mo` files with `i18n`, collected from any iterator of
+/// `(language, catalog)` pairs, or converted from the `Vec` returned by
+/// `gettext_macros::include_i18n`.
+#[derive(Clone, Default)]
+pub struct Translations {
+    /// The catalogs are shared with the `I18n` of every request, instead of being copied for each
+    /// of them.
+    langs: Vec<(&'static str, Arc<Catalog>)>,
+    /// Where to find each language, by lowercase name, so that languages can be found without
+    /// scanning all of them.
+    index: HashMap<String, Slot>,
+    /// All the supported languages, in order of preference, including the ones whose catalog is
+    /// not loaded yet. They are shared with the `I18n` of every request.
+    names: Arc<Vec<&'static str>>,
+    /// Other gettext domains, with their own catalogs for each language.
+    domains: Arc<Vec<(&'static str, Translations)>>,
+    /// The Fluent messages of each language.
+    #[cfg(feature = "fluent")]
+    fluent: Vec<(&'static str, Arc<FluentCatalog>)>,
+    /// Regional variants to use when their base language is requested, but not supported.
+    region_defaults: Vec<(&'static str, &'static str)>,
+    /// Other names of the supported languages, see `with_alias`.
+    aliases: Vec<(&'static str, &'static str)>,
+    /// The languages to look messages up in, in order, when a language doesn't translate them.
+    fallback_chains: Vec<(&'static str, Vec<&'static str>)>,
+    /// Catalogs overriding some messages of a language, in the order they were added.
+    overlays: Vec<(&'static str, Arc<Catalog>)>,
+    /// Called for the messages that no catalog translates.
+    on_missing: Option<catalog::MissingHook>,
+    /// The translations of the most recently used messages, for languages with overlays or a
+    /// fallback chain. It is replaced whenever catalogs are added or replaced.
+    #[cfg(feature = "lookup-cache")]
+    lookups: catalog::LookupCache,
+    /// The plural translations of right-to-left languages, with their count isolated.
+    isolated: catalog::IsolatedCounts,
+    /// Finds the language saved by the user of a request, see `with_user_resolver`.
+    #[cfg(any(
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    user_resolver: Option<user::UserResolver>,
+    /// The pseudo-locale, see `with_pseudo_locale`.
+    #[cfg(feature = "pseudo")]
+    pseudo: Option<(&'static str, pseudo::Pseudo)>,
+    #[cfg(feature = "track-access")]
+    accessed: catalog::AccessLog,
+}
+
+/// Where the catalog of a language is.
+#[derive(Clone)]
+enum Slot {
+    /// At this position in `Translations::langs`.
+    Loaded(usize),
+    /// Not loaded until it is needed.
+    #[cfg(feature = "lazy")]
+    Lazy(&'static str, Arc<lazy::LazyCatalog>),
+}
+
+impl Translations {
+    /// Parses a `.mo` catalog from each reader, for catalogs that don't come from the file system
+    /// (an archive, an object storage, a generated buffer…).
+    ///
+    /// ```rust,ignore
+    /// let translations = Translations::from_readers(vec![
+    ///     ("en", archive.by_name("en.mo")?),
+    ///     ("fr", archive.by_name("fr.mo")?),
+    /// ])?;
+    /// ```
+    ///
+    /// Languages are supported in the order of the iterator, and the first catalog that can't be
+    /// read or parsed is returned as an error.
+    #[cfg(feature = "gettext")]
+    pub fn from_readers<R: Read>(
+        catalogs: impl IntoIterator<Item = (&'static str, R)>,
+    ) -> Result<Translations, Error> {
+        catalogs
+            .into_iter()
+            .map(|(lang, reader)| Ok((lang, parse_catalog(reader, lang)?)))
+            .collect::<Result<Vec<_>, Error>>()
+            .map(Translations::from)
+    }
+
+    /// Same as `from_readers`, but parses catalogs that are already in memory.
+    ///
+    /// ```rust,ignore
+    /// let translations = Translations::from_bytes(vec![("fr", &fr_mo[..])])?;
+    /// ```
+    #[cfg(feature = "gettext")]
+    pub fn from_bytes<'a>(
+        catalogs: impl IntoIterator<Item = (&'static str, &'a [u8])>,
+    ) -> Result<Translations, Error> {
+        Translations::from_readers(catalogs)
+    }
+
+    /// Iterates over the supported languages and their catalogs.
+    ///
+    /// Languages loaded lazily (see `Loader::lazy`) are only listed once their catalog is loaded.
+    pub fn iter(&self) -> slice::Iter<'_, (&'static str, Arc<Catalog>)> {
+        self.langs.iter()
+    }
+
+    /// The catalog for a given language, if it is supported.
+    ///
+    /// If this language is loaded lazily, its catalog is loaded now if it wasn't already.
+    pub fn get(&self, lang: &str) -> Option<&Catalog> {
+        self.entry(lang).map(|l| &**l.1)
+    }
+
+    /// The supported language named `lang`, ignoring its case.
+    ///
+    /// A language loaded lazily is only supported if its catalog can be loaded: it is loaded now if
+    /// it wasn't already, so that a language whose catalog is broken is never negotiated.
+    ///
+    /// The pseudo-locale is not included, see `find_explicit`.
+    pub(crate) fn find(&self, lang: &str) -> Option<&'static str> {
+        self.entry(lang)
+            .map(|l| l.0)
+            .filter(|lang| !self.is_pseudo(lang))
+    }
+
+    /// Same as `find`, but for a language explicitly chosen by the user (in the query string or
+    /// a cookie), which can also be the pseudo-locale (see `with_pseudo_locale`).
+    pub(crate) fn find_explicit(&self, lang: &str) -> Option<&'static str> {
+        #[cfg(feature = "pseudo")]
+        {
+            if let Some((pseudo, _)) = &self.pseudo {
+                if pseudo.eq_ignore_ascii_case(lang) {
+                    return Some(*pseudo);
+                }
+            }
+        }
+        self.find(lang)
+    }
+
+    /// Whether `lang` is the pseudo-locale (see `with_pseudo_locale`).
+    #[cfg(feature = "pseudo")]
+    pub(crate) fn is_pseudo(&self, lang: &str) -> bool {
+        self.pseudo.as_ref().is_some_and(|(pseudo, _)| *pseudo == lang)
+    }
+
+    #[cfg(not(feature = "pseudo"))]
+    pub(crate) fn is_pseudo(&self, _: &str) -> bool {
+        false
+    }
+
+    /// All the supported languages, in order of preference, even the ones that are not loaded yet.
+    pub(crate) fn all_langs(&self) -> slice::Iter<'_, &'static str> {
+        self.names.iter()
+    }
+
+    fn entry(&self, lang: &str) -> Option<(&'static str, &Arc<Catalog>)> {
+        match self.index.get(&lang.to_ascii_lowercase())? {
+            Slot::Loaded(position) => self.langs.get(*position).map(|l| (l.0, &l.1)),
+            #[cfg(feature = "lazy")]
+            Slot::Lazy(name, catalog) => catalog.get(name).map(|catalog| (*name, catalog)),
+        }
+    }
+
+    /// Replaces the catalog of a language, or adds this language after the other ones.
+    pub(crate) fn insert(&mut self, lang: &'static str, catalog: Arc<Catalog>) {
+        #[cfg(feature = "lookup-cache")]
+        {
+            self.lookups = self.lookups.emptied();
+        }
+        let key = lang.to_ascii_lowercase();
+        let position = self.langs.len();
+        match self.index.get(&key) {
+            Some(Slot::Loaded(existing)) => self.langs[*existing].1 = catalog,
+            #[cfg(feature = "lazy")]
+            Some(Slot::Lazy(name, _)) => {
+                let name = *name;
+                self.index.insert(key, Slot::Loaded(position));
+                self.langs.push((name, catalog));
+            }
+            None => {
+                self.index.insert(key, Slot::Loaded(position));
+                self.langs.push((lang, catalog));
+                Arc::make_mut(&mut self.names).push(lang);
+            }
+        }
+    }
+
+    /// Adds a language whose catalog will only be loaded the first time it is needed.
+    ///
+    /// Languages that are already there are left as they are.
+    #[cfg(feature = "lazy")]
+    pub(crate) fn insert_lazy(&mut self, lang: &'static str, catalog: Arc<lazy::LazyCatalog>) {
+        if let Entry::Vacant(entry) = self.index.entry(lang.to_ascii_lowercase()) {
+            #[cfg(feature = "lookup-cache")]
+            {
+                self.lookups = self.lookups.emptied();
+            }
+            entry.insert(Slot::Lazy(lang, catalog));
+            Arc::make_mut(&mut self.names).push(lang);
+        }
+    }
+
+    /// Prepares the catalog of a language to be used for a request.
+    ///
+    /// If there is no catalog for `lang`, the first language of its fallback chain that has one
+    /// is used instead.
+    pub(crate) fn for_request(&self, lang: &str) -> Option<I18n> {
+        let mut chain = iter::once(lang)
+            .chain(self.fallback_chain(lang).iter().copied())
+            .filter_map(|lang| self.entry(lang));
+        let (lang, catalog) = chain.next()?;
+        let fallbacks: Vec<_> = chain
+            .filter(|l| l.0 != lang)
+            .map(|l| l.1.clone())
+            .collect();
+        let overlays = self.overlays(lang);
+        #[cfg(feature = "lookup-cache")]
+        let lookups = Some(self.lookups.clone()).filter(|lookups| {
+            (!overlays.is_empty() || !fallbacks.is_empty()) && lookups.capacity() > 0
+        });
+        Some(I18n {
+            catalog: I18nCatalog {
+                catalog: catalog.clone(),
+                overlays,
+                fallbacks,
+                lang,
+                on_missing: self.on_missing.clone(),
+                #[cfg(feature = "lookup-cache")]
+                lookups,
+                isolated: Some(self.isolated.clone()).filter(|_| langs::is_rtl(lang)),
+                #[cfg(feature = "pseudo")]
+                pseudo: self
+                    .pseudo
+                    .as_ref()
+                    .filter(|(pseudo_lang, _)| *pseudo_lang == lang)
+                    .map(|(_, pseudo)| pseudo.clone()),
+                #[cfg(feature = "track-access")]
+                accessed: self.accessed.clone(),
+            },
+            lang,
+            langs: self.names.clone(),
+            domains: self.domains.clone(),
+            #[cfg(feature = "fluent")]
+            fluent: self.fluent_catalog(lang),
+        })
+    }
+
+    /// Every message that was looked up through `I18n::catalog` since the application started.
+    ///
+    /// Messages with a context are recorded as `context\u{4}message`. See the crate documentation
+    /// to learn how to use it to find unused translations.
+    ///
+    /// Requires the `track-access` feature.
+    #[cfg(feature = "track-access")]
+    pub fn accessed_keys(&self) -> HashSet<String> {
+        self.accessed.keys()
+    }
+
+    /// Adds the languages of `other` to these translations, after the ones that are already
+    /// there. `policy` decides what happens to languages that both of them contain.
+    ///
+    /// With `MergePolicy::Error`, nothing is merged if there is any conflict. Languages that
+    /// `other` loads lazily never replace existing ones.
+    pub fn merge_with(
+        &mut self,
+        other: Translations,
+        policy: MergePolicy,
+    ) -> Result<(), MergeConflict> {
+        if policy == MergePolicy::Error {
+            if let Some(lang) = other.all_langs().find(|l| self.find(l).is_some()) {
+                return Err(MergeConflict(lang));
+            }
+        }
+
+        for lang in other.all_langs() {
+            if policy != MergePolicy::Overwrite && self.find(lang).is_some() {
+                continue;
+            }
+            match &other.index[&lang.to_ascii_lowercase()] {
+                Slot::Loaded(position) => self.insert(lang, other.langs[*position].1.clone()),
+                #[cfg(feature = "lazy")]
+                Slot::Lazy(_, catalog) => self.insert_lazy(lang, catalog.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the catalogs of another gettext domain, that `I18n::domain` can then use.
+    ///
+    /// ```rust,ignore
+    /// let langs = vec!["en", "fr"];
+    /// let translations = rocket_i18n::i18n("web", langs.clone())
+    ///     .with_domain("emails", rocket_i18n::i18n("emails", langs.clone()))
+    ///     .with_domain("admin", rocket_i18n::i18n("admin", langs));
+    /// ```
+    ///
+    /// The languages of requests are still negotiated with the main catalogs only.
+    pub fn with_domain(mut self, name: &'static str, translations: Translations) -> Translations {
+        let domains = Arc::make_mut(&mut self.domains);
+        domains.retain(|domain| domain.0 != name);
+        domains.push((name, translations));
+        self
+    }
+
+    /// Serves requests for `lang` with the catalog of one of its regional variants, when there
+    /// is no catalog for `lang` itself.
+    ///
+    /// With `pt-BR` and `pt-PT`, but no `pt`, `with_region_default("pt", "pt-BR")` makes requests
+    /// for `pt` (or any other variant, like `pt-AO`) negotiate `pt-BR`, instead of falling back to
+    /// the default language.
+    pub fn with_region_default(mut self, lang: &'static str, region: &'static str) -> Translations {
+        self.region_defaults.push((lang, region));
+        self
+    }
+
+    /// The regional variant to use for `lang`, registered with `with_region_default`.
+    pub(crate) fn region_default(&self, lang: &str) -> Option<&'static str> {
+        self.region_defaults
+            .iter()
+            .find(|(base, _)| base.eq_ignore_ascii_case(lang))
+            .map(|(_, region)| *region)
+    }
+
+    /// Serves requests for `alias` with the catalog of `lang`, for the legacy or non-canonical tags
+    /// that some browsers still send.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "he", "id", "nb", "zh-Hans"])
+    ///     .with_alias("iw", "he")
+    ///     .with_alias("in", "id")
+    ///     .with_alias("no", "nb")
+    ///     .with_alias("zh-CN", "zh-Hans");
+    /// ```
+    ///
+    /// Aliases are matched like the names of the catalogs, so `no-NO` is served `nb` too, unless
+    /// there is a catalog for `no-NO` itself.
+    pub fn with_alias(mut self, alias: &'static str, lang: &'static str) -> Translations {
+        self.aliases.retain(|(a, _)| !a.eq_ignore_ascii_case(alias));
+        self.aliases.push((alias, lang));
+        self
+    }
+
+    /// The language that `alias` stands for, registered with `with_alias`.
+    pub(crate) fn alias(&self, alias: &str) -> Option<&'static str> {
+        self.aliases
+            .iter()
+            .find(|(a, _)| a.eq_ignore_ascii_case(alias))
+            .map(|(_, lang)| *lang)
+    }
+
+    /// Declares the languages to use, in order, for the messages that `lang` doesn't translate,
+    /// instead of leaving them untranslated.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "es", "gl", "no", "nb"])
+    ///     .with_fallback_chain("gl", &["es", "en"])
+    ///     .with_fallback_chain("nb", &["no", "en"]);
+    /// ```
+    ///
+    /// Languages of the chain without a catalog are skipped. If `lang` itself has no catalog,
+    /// requests for it are served the first language of the chain that has one, and messages that
+    /// this language doesn't translate are still looked up in the rest of the chain.
+    pub fn with_fallback_chain(
+        mut self,
+        lang: &'static str,
+        chain: &[&'static str],
+    ) -> Translations {
+        self.fallback_chains.retain(|(l, _)| !l.eq_ignore_ascii_case(lang));
+        self.fallback_chains.push((lang, chain.to_vec()));
+        #[cfg(feature = "lookup-cache")]
+        {
+            self.lookups = self.lookups.emptied();
+        }
+        self
+    }
+
+    /// Sets how many translations the lookup cache remembers, for all the languages (4096 by
+    /// default). `0` disables it.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "es", "gl"])
+    ///     .with_fallback_chain("gl", &["es", "en"])
+    ///     .with_lookup_cache_size(512);
+    /// ```
+    ///
+    /// Only the messages of languages with overlays or a fallback chain are cached: the other
+    /// ones are found with a single lookup anyway.
+    ///
+    /// Requires the `lookup-cache` feature.
+    #[cfg(feature = "lookup-cache")]
+    pub fn with_lookup_cache_size(mut self, capacity: usize) -> Translations {
+        self.lookups = catalog::LookupCache::with_capacity(capacity);
+        self
+    }
+
+    /// Stacks a catalog on top of the one of `lang`: the messages it translates override the
+    /// ones of the base catalog, and the other ones are still translated by the base catalog.
+    ///
+    /// It is useful to customize a few messages for a deployment or a tenant (to change the name
+    /// of the application, for instance), without copying the whole catalog.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "fr"])
+    ///     .overlay("fr", Catalog::parse(File::open("branding/fr.mo")?)?);
+    /// ```
+    ///
+    /// Several overlays can be stacked: the last one takes precedence. Overlays of languages that
+    /// are not supported are ignored.
+    pub fn overlay(mut self, lang: &'static str, catalog: Catalog) -> Translations {
+        self.overlays.push((lang, Arc::new(catalog)));
+        #[cfg(feature = "lookup-cache")]
+        {
+            self.lookups = self.lookups.emptied();
+        }
+        self
+    }
+
+    /// The overlays of `lang`, the last one added first.
+    fn overlays(&self, lang: &str) -> Vec<Arc<Catalog>> {
+        self.overlays
+            .iter()
+            .rev()
+            .filter(|(l, _)| l.eq_ignore_ascii_case(lang))
+            .map(|(_, catalog)| catalog.clone())
+            .collect()
+    }
+
+    /// Calls `callback` with the language, the context (`msgctxt`) and the id of every message
+    /// looked up through `I18n::catalog` that is not translated, by the catalog of its language
+    /// nor by its fallback chain, to find out which messages translators missed.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "fr"])
+    ///     .on_missing_translation(|lang, _, msg_id| {
+    ///         if lang != "en" {
+    ///             metrics::increment_counter!("missing_translations", "lang" => lang);
+    ///         }
+    ///     });
+    /// ```
+    ///
+    /// It is called for each lookup, so it should be cheap. Note that catalogs of the language
+    /// the messages are written in usually don't translate them at all.
+    pub fn on_missing_translation<F>(mut self, callback: F) -> Translations
+    where
+        F: Fn(&'static str, Option<&str>, &str) + Send + Sync + 'static,
+    {
+        self.on_missing = Some(Arc::new(callback));
+        self
+    }
+
+    /// Logs a warning for every message that is not translated. See `on_missing_translation`.
+    pub fn log_missing_translations(self) -> Translations {
+        self.on_missing_translation(|lang, msg_context, msg_id| match msg_context {
+            Some(ctx) => warn!("Missing {} translation for \"{}\" ({})", lang, msg_id, ctx),
+            None => warn!("Missing {} translation for \"{}\"", lang, msg_id),
+        })
+    }
+
+    /// Finds the language saved by the user of a request (in their profile, for instance) with
+    /// an asynchronous function, so that logged-in users get it regardless of the settings of
+    /// their browser.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "fr"])
+    ///     .with_user_resolver(move |req: UserRequest| {
+    ///         let session = req.cookie("session").map(str::to_owned);
+    ///         let db = db.clone();
+    ///         async move { db.user_lang(session?).await }
+    ///     });
+    /// ```
+    ///
+    /// It is a step of the negotiation (`Resolver::User`), that comes after the query parameter,
+    /// the path, the subdomain and the cookie, but before `Accept-Language`. Returning `None`,
+    /// or a language that is not supported, lets the negotiation continue.
+    ///
+    /// Every integration (actix-web 1 and 4, axum, poem, Rocket, tide, tower and warp) awaits this
+    /// resolver before negotiating the language. Rocket guards are synchronous, so Rocket blocks
+    /// the thread handling the request until the future is ready.
+    #[cfg(any(
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub fn with_user_resolver<F, Fut>(mut self, resolver: F) -> Translations
+    where
+        F: Fn(UserRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        self.user_resolver = Some(Arc::new(move |req| Box::pin(resolver(req))));
+        self
+    }
+
+    /// Runs the resolver of `with_user_resolver`, if any, with the headers of a request.
+    #[cfg(any(
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub(crate) fn resolve_user<H>(&self, headers: H) -> Option<user::UserFuture>
+    where
+        H: FnOnce() -> Vec<(String, String)>,
+    {
+        let resolve = self.user_resolver.as_ref()?;
+        Some(resolve(UserRequest { headers: headers() }))
+    }
+
+    /// Adds a pseudo-locale, named `lang`, in which every message is "translated" by accenting
+    /// its letters and surrounding it with markers: `Hello world` becomes
+    /// `[!!! Ĥéļļö ŵöŕļð !!!]`.
+    ///
+    /// ```rust,ignore
+    /// let translations = rocket_i18n::i18n("plume", vec!["en", "fr"]).with_pseudo_locale("en-XA");
+    /// ```
+    ///
+    /// Requesting this language makes texts that don't go through the catalog stand out, and
+    /// shows layouts that break with longer texts, without waiting for real translations. It is
+    /// only used when it is explicitly requested by its exact name, in the query string or in the
+    /// cookie of `I18nConfig::cookie` (`Accept-Language: en-XA` is served `en`), and it is not
+    /// listed in `I18n::available_langs`, so language pickers don't show it.
+    ///
+    /// Pseudo-translations are kept in memory forever, so this is only meant for development.
+    ///
+    /// Requires the `pseudo` feature.
+    #[cfg(feature = "pseudo")]
+    pub fn with_pseudo_locale(mut self, lang: &'static str) -> Translations {
+        self.insert(lang, Arc::new(empty_catalog()));
+        Arc::make_mut(&mut self.names).retain(|name| *name != lang);
+        self.pseudo = Some((lang, pseudo::Pseudo::default()));
+        self
+    }
+
+    /// The fallback chain of `lang`, registered with `with_fallback_chain`.
+    pub(crate) fn fallback_chain(&self, lang: &str) -> &[&'static str] {
+        self.fallback_chains
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(lang))
+            .map_or(&[], |(_, chain)| &chain[..])
+    }
+
+    /// `lang`, as named in `with_fallback_chain`, if it has no catalog but a language of its
+    /// fallback chain does. `for_request` serves it this language, with the rest of the chain.
+    pub(crate) fn chained(&self, lang: &str) -> Option<&'static str> {
+        let (name, chain) = self
+            .fallback_chains
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(lang))?;
+        Some(*name).filter(|_| chain.iter().any(|l| self.find(l).is_some()))
+    }
+
+    /// The supported language whose catalog `for_request` uses for `lang`: `lang` itself, or the
+    /// first language of its fallback chain that is supported.
+    pub(crate) fn served(&self, lang: &str) -> Option<&'static str> {
+        self.find(lang).or_else(|| {
+            self.fallback_chain(lang)
+                .iter()
+                .find_map(|l| self.find(l))
+        })
+    }
+
+    /// The number of supported languages.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether there is no supported language at all.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The language to use according to the environment (`LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and
+    /// `LANG`), for command line tools or background jobs.
+    ///
+    /// The `C` and `POSIX` locales resolve to the default language.
+    pub fn env_lang(&self) -> &'static str {
+        negotiation::negotiate_env(self)
+    }
+
+    /// The supported languages that are acceptable according to an `Accept-Language` header,
+    /// with their quality, from the most to the least preferred.
+    ///
+    /// Requested regions fall back to their language as they would during negotiation, and
+    /// wildcards are ignored. It can be used to tell users in which other languages a page is
+    /// available.
+    pub fn matching(&self, header: &str) -> Vec<(&'static str, f32)> {
+        negotiation::ranked(self, None, header)
+    }
+
+    /// The catalog of the `n`th supported language matching an `Accept-Language` header, starting
+    /// from `0` for the preferred one, as ranked by `Translations::matching`.
+    ///
+    /// It can be used to show a message in another language the user understands, next to the
+    /// main content.
+    pub fn nth_catalog(&self, header: &str, n: usize) -> Option<(&'static str, &Catalog)> {
+        let lang = self.matching(header).get(n)?.0;
+        self.get(lang).map(|catalog| (lang, catalog))
+    }
+
+    /// Translates a message in a given language, regardless of the one that was negotiated for
+    /// the current request (to write an email in the language of its recipient, for instance).
+    ///
+    /// `lang` is resolved the same way as the language of a request would be, with the same
+    /// fallbacks.
+    pub fn translate_in(&self, lang: &str, msg_id: &str) -> String {
+        self.catalog_in(lang)
+            .map_or(msg_id, |catalog| catalog.gettext(msg_id))
+            .to_owned()
+    }
+
+    /// The catalog to use for `lang`, resolved the same way as the language of a request.
+    pub(crate) fn catalog_in(&self, lang: &str) -> Option<&Catalog> {
+        let request = negotiation::RequestInfo {
+            accept_language: Some(lang),
+            ..Default::default()
+        };
+        let lang = negotiation::negotiate(self, None, &request);
+        self.get(self.served(lang)?)
+    }
+}
+
+/// What to do when merging translations that both contain the same language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the catalog that was already there.
+    KeepExisting,
+    /// Replace it with the new one.
+    Overwrite,
+    /// Don't merge anything, and return an error.
+    Error,
+}
+
+/// The error returned by `Translations::merge_with` when a language is present on both sides.
+#[derive(Debug)]
+pub struct MergeConflict(pub &'static str);
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Translations for {} are defined twice", self.0)
+    }
+}
+
+impl std::error::Error for MergeConflict {
+    fn description(&self) -> &str {
+        "Translations are defined twice"
+    }
+}
+
+impl FromIterator<(&'static str, Catalog)> for Translations {
+    fn from_iter<I: IntoIterator<Item = (&'static str, Catalog)>>(iter: I) -> Translations {
+        Translations::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl From<Vec<(&'static str, Catalog)>> for Translations {
+    /// If a language is present more than once, only its first catalog is used.
+    fn from(langs: Vec<(&'static str, Catalog)>) -> Translations {
+        let mut translations = Translations::default();
+        for (lang, catalog) in langs {
+            if translations.find(lang).is_none() {
+                translations.insert(lang, Arc::new(catalog));
+            }
+        }
+        translations
+    }
+}
+
+impl<'a> IntoIterator for &'a Translations {
+    type Item = &'a (&'static str, Arc<Catalog>);
+    type IntoIter = slice::Iter<'a, (&'static str, Arc<Catalog>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
 
 /// Loads translations at runtime. Usually used with `actix_web::web::App::data`.
 ///
 /// Note that the `.mo` files should be present with your binary. If you want to embed them,
 /// use `gettext_macros::include_i18n`.
+///
+/// If the `Plural-Forms` header of a catalog can't be parsed, a warning is logged and the
+/// English rule (`n != 1`) is used for this catalog instead.
+///
+/// Catalogs may or may not contain a hash table (see `msgfmt --no-hash`): it is not used to look
+/// up messages, so both are supported.
+///
+/// Catalogs are read from `translations/<lang>/LC_MESSAGES/<domain>.mo`, relative to the current
+/// working directory. To read them from another directory, use `i18n_from`, or `Loader` for more
+/// options (like resolving this path from the directory of the executable).
+///
+/// The directory and the languages can be overridden with environment variables, see `Loader`.
+#[cfg(feature = "gettext")]
 pub fn i18n(domain: &str, lang: Vec<&'static str>) -> Translations {
-    lang.iter().fold(Vec::new(), |mut trans, l| {
-        let mo_file = fs::File::open(format!("translations/{}/LC_MESSAGES/{}.mo", l, domain))
-            .expect("Couldn't open catalog");
-        let cat = Catalog::parse(mo_file).expect(format!("Error while loading catalog ({})", l).as_str());
-        trans.push((l, cat));
-        trans
+    Loader::new(domain).load(lang)
+}
+
+/// Same as `i18n`, but returns an error instead of panicking when a catalog can't be read or
+/// parsed, so that the application can report it, or keep running with the other languages:
+///
+/// ```rust,ignore
+/// let translations = match rocket_i18n::try_i18n("your-domain", vec!["en", "fr"]) {
+///     Ok(translations) => translations,
+///     Err(e) => {
+///         log::error!("{}", e);
+///         rocket_i18n::i18n("your-domain", vec!["en"])
+///     }
+/// };
+/// ```
+#[cfg(feature = "gettext")]
+pub fn try_i18n(domain: &str, lang: Vec<&'static str>) -> Result<Translations, I18nError> {
+    Loader::new(domain).try_load(lang)
+}
+
+/// Same as `i18n`, but reads the catalogs from `{dir}/{lang}/LC_MESSAGES/{domain}.mo`.
+///
+/// ```rust,ignore
+/// let translations = rocket_i18n::i18n_from("/usr/share/locale", "your-domain", vec!["en", "fr"]);
+/// ```
+///
+/// To use another layout, see `Loader::layout`.
+#[cfg(feature = "gettext")]
+pub fn i18n_from<P: AsRef<Path>>(dir: P, domain: &str, lang: Vec<&'static str>) -> Translations {
+    Loader::new(domain).dir(dir).load(lang)
+}
+
+/// Where `i18n` looks for the catalog of a language.
+#[cfg(feature = "gettext")]
+fn catalog_path(domain: &str, lang: &str) -> PathBuf {
+    loader::default_dir().join(gettext_layout(domain, lang))
+}
+
+/// The usual layout of gettext catalogs: `{lang}/LC_MESSAGES/{domain}.mo`.
+#[cfg(feature = "gettext")]
+fn gettext_layout(domain: &str, lang: &str) -> PathBuf {
+    Path::new(lang)
+        .join("LC_MESSAGES")
+        .join(format!("{}.mo", domain))
+}
+
+/// Parses a catalog, falling back to the English plural rule if its `Plural-Forms` header is
+/// malformed, instead of failing.
+#[cfg(feature = "gettext")]
+fn parse_catalog<R: Read>(mut reader: R, name: &str) -> Result<Catalog, Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    match Catalog::parse(&bytes[..]) {
+        Err(Error::PluralParsing) => {
+            warn!(
+                "Malformed Plural-Forms header in the catalog of {}, using n != 1 instead",
+                name
+            );
+            ParseOptions::new()
+                .force_plural(english_plural)
+                .parse(&bytes[..])
+        }
+        result => result,
+    }
+}
+
+/// A catalog without any message.
+#[cfg(feature = "gettext")]
+fn empty_catalog() -> Catalog {
+    Catalog::empty()
+}
+
+/// A catalog without any message.
+#[cfg(not(feature = "gettext"))]
+fn empty_catalog() -> Catalog {
+    Catalog::new()
+}
+
+fn english_plural(n: u64) -> usize {
+    if n != 1 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Loads every catalog found in `dir`, registering each of them under the language declared in
+/// its `Language:` header instead of the name of the directory it was found in.
+///
+/// Both the usual `{dir}/{lang}/LC_MESSAGES/{domain}.mo` layout and a flat directory of `.mo`
+/// files (`{dir}/fr.mo`, `{dir}/de.mo`, …) are supported.
+///
+/// The `Language:` header always takes precedence: the directory name (or the file name, for
+/// flat directories) is only used for catalogs that don't declare their language. When both are
+/// present and disagree, a warning is logged, as it usually means that the catalog was misfiled.
+///
+/// Catalogs are loaded in the order of their paths, so the first one is the default language, and
+/// when two catalogs declare the same language, the first one is kept.
+///
+/// It panics if the directory or one of the catalogs can't be read, see `try_i18n_declared`.
+#[cfg(feature = "gettext")]
+pub fn i18n_declared<P: AsRef<Path>>(dir: P, domain: &str) -> Translations {
+    try_i18n_declared(dir, domain).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as `i18n_declared`, but returns an error instead of panicking.
+///
+/// Catalogs that can't be read or parsed are reported with the name of the directory (or file)
+/// they were found in, since their declared language can't be read.
+#[cfg(feature = "gettext")]
+pub fn try_i18n_declared<P: AsRef<Path>>(dir: P, domain: &str) -> Result<Translations, I18nError> {
+    let dir = dir.as_ref();
+    let dir_error = |error| I18nError::Dir {
+        path: dir.to_path_buf(),
+        error,
+    };
+    let mut paths = fs::read_dir(dir)
+        .and_then(|entries| entries.map(|e| e.map(|e| e.path())).collect::<Result<Vec<_>, _>>())
+        .map_err(dir_error)?;
+    paths.sort();
+
+    let mut trans = Vec::new();
+    for path in paths {
+        let (mo_file, name) = if path.is_dir() {
+            let mo_file = path.join("LC_MESSAGES").join(format!("{}.mo", domain));
+            if !mo_file.is_file() {
+                continue;
+            }
+            (mo_file, path.file_name())
+        } else if path.extension().is_some_and(|ext| ext == "mo") {
+            (path.clone(), path.file_stem())
+        } else {
+            continue;
+        };
+        let name = name.and_then(|n| n.to_str()).map(str::to_owned);
+        let found_as = || leak(name.clone().unwrap_or_else(|| mo_file.display().to_string()));
+
+        let mo_name = mo_file.display().to_string();
+        let cat = match fs::File::open(&mo_file) {
+            Ok(file) => parse_catalog(file, &mo_name)
+                .map_err(|error| loader::catalog_error(found_as(), mo_file.clone(), error))?,
+            Err(error) => {
+                return Err(I18nError::Io {
+                    lang: found_as(),
+                    path: mo_file,
+                    error,
+                })
+            }
+        };
+        let lang = match (header_field(&cat, "Language").map(str::to_owned), name) {
+            (Some(declared), Some(name)) => {
+                if declared != name {
+                    warn!(
+                        "{} declares its language as \"{}\", but was found as \"{}\"",
+                        mo_file.display(),
+                        declared,
+                        name
+                    );
+                }
+                declared
+            }
+            (Some(declared), None) => declared,
+            (None, Some(name)) => name,
+            (None, None) => {
+                warn!("Couldn't find the language of {}, ignoring it", mo_file.display());
+                continue;
+            }
+        };
+        trans.push((leak(lang), cat));
+    }
+    Ok(trans.into())
+}
+
+/// Reads a field (`Language`, `Plural-Forms`, …) from the header entry of a catalog.
+fn header_field<'a>(catalog: &'a Catalog, field: &str) -> Option<&'a str> {
+    catalog.gettext("").lines().find_map(|line| {
+        let mut parts = line.splitn(2, ':');
+        if parts.next()?.trim().eq_ignore_ascii_case(field) {
+            parts.next().map(str::trim).filter(|value| !value.is_empty())
+        } else {
+            None
+        }
     })
 }
 
+/// Catalogs are kept for the whole lifetime of the program, so languages that are only known at
+/// runtime are leaked to get the `&'static str` that `Translations` expects.
+#[cfg(feature = "gettext")]
+fn leak(lang: String) -> &'static str {
+    Box::leak(lang.into_boxed_str())
+}
+
 /// Works the same way as `gettext_macros::i18n`, but without needing to give a `gettext::Catalog`
-/// as first argument.
+/// as first argument: the catalog of `self` is used (see `WithCatalog`).
+///
+/// Messages can be given a context (their `msgctxt`), to translate the same string differently
+/// depending on where it is used: `t!(ctx "file menu", "Open")`.
+/// Messages with a plural form can have one too:
+/// `t!(ctx "inbox", "One message", "{0} messages"; n)`.
 ///
 /// For use in askama templates.
 #[macro_export]
 macro_rules! t {
+    (ctx $ctx:tt, $( $args:tt )+) => {
+        i18n!({ use $crate::WithCatalog as _; self.catalog() }, context = $ctx, $( $args )+)
+    };
+    ($( $args:tt )+) => {
+        i18n!({ use $crate::WithCatalog as _; self.catalog() }, $( $args )+)
+    };
+}
+
+/// Works the same way as `t`, but HTML-escapes the formatting arguments. See `i18n_escaped`.
+///
+/// For use in askama templates.
+#[macro_export]
+macro_rules! t_escaped {
     ($( $args:tt )+) => {
-        i18n!(self.catalog, $( $args )+)
+        i18n_escaped!({ use $crate::WithCatalog as _; self.catalog() }, $( $args )+)
+    };
+}
+
+/// Works the same way as `gettext_macros::i18n`, but HTML-escapes every formatting argument (the
+/// ones after the `;`, apart from the count of plural messages) before inserting them in the
+/// translated string.
+///
+/// Use it when the result is going to be inserted in HTML without being escaped again. The
+/// translated strings themselves are not escaped, only the arguments.
+#[macro_export]
+macro_rules! i18n_escaped {
+    ($cat:expr, $msg:tt ; $( $arg:expr ),* $(,)*) => {
+        i18n!($cat, $msg ; $( $crate::escape_arg($arg) ),*)
+    };
+    ($cat:expr, $msg:tt, $plural:tt ; $count:expr $(, $arg:expr )* $(,)*) => {
+        i18n!($cat, $msg, $plural ; $count $(, $crate::escape_arg($arg) )*)
+    };
+    ($cat:expr, ctx $ctx:tt, $msg:tt ; $( $arg:expr ),* $(,)*) => {
+        i18n!($cat, context = $ctx, $msg ; $( $crate::escape_arg($arg) ),*)
+    };
+    ($cat:expr, ctx $ctx:tt, $msg:tt, $plural:tt ; $count:expr $(, $arg:expr )* $(,)*) => {
+        i18n!($cat, context = $ctx, $msg, $plural ; $count $(, $crate::escape_arg($arg) )*)
     };
+    ($cat:expr, ctx $ctx:tt, $( $args:tt )+) => {
+        i18n!($cat, context = $ctx, $( $args )+)
+    };
+    ($cat:expr, $( $args:tt )+) => {
+        i18n!($cat, $( $args )+)
+    };
+}
+
+/// Escapes a value so that it can be safely inserted in HTML, as text or in an attribute.
+pub fn escape_html<T: fmt::Display + ?Sized>(value: &T) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a formatting argument of `i18n_escaped`.
+///
+/// `gettext_macros::i18n` only accepts paths, literals and function calls as arguments (and moves
+/// them), so the argument is taken by value rather than borrowed.
+#[doc(hidden)]
+pub fn escape_arg<T: fmt::Display>(value: T) -> String {
+    escape_html(&value)
+}
+
+#[cfg(all(test, feature = "gettext"))]
+mod tests {
+    use super::*;
+    use gettext_macros::{i18n, init_i18n};
+    use std::io::Cursor;
+
+    init_i18n!("rocket_i18n_tests", fr, ar);
+
+    /// An empty directory for the files of a test.
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rocket_i18n-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A `.mo` file declaring `lang` in its header (if any), and translating `Hello`.
+    pub(crate) fn mo_file(lang: Option<&str>, hello: &str) -> Vec<u8> {
+        let mut header = "Content-Type: text/plain; charset=UTF-8\n".to_owned();
+        if let Some(lang) = lang {
+            header.push_str(&format!("Language: {}\n", lang));
+        }
+        mo::write(&[(String::new(), header), ("Hello".to_owned(), hello.to_owned())])
+    }
+
+    #[test]
+    fn declared_catalogs_are_loaded_in_path_order() {
+        let dir = temp_dir("declared");
+        fs::write(dir.join("zz.mo"), mo_file(Some("de"), "Hallo")).unwrap();
+        fs::write(dir.join("aa.mo"), mo_file(Some("fr"), "Bonjour")).unwrap();
+        fs::write(dir.join("ab.mo"), mo_file(Some("fr"), "Salut")).unwrap();
+        fs::write(dir.join("mm.mo"), mo_file(None, "Hola")).unwrap();
+        fs::create_dir_all(dir.join("it").join("LC_MESSAGES")).unwrap();
+        fs::write(dir.join("it/LC_MESSAGES/app.mo"), mo_file(Some("it"), "Ciao")).unwrap();
+        fs::write(dir.join("notes.txt"), "Not a catalog").unwrap();
+
+        let translations = try_i18n_declared(&dir, "app").unwrap();
+        let langs = translations.all_langs().copied().collect::<Vec<_>>();
+        assert_eq!(langs, vec!["fr", "it", "mm", "de"]);
+        assert_eq!(translations.get("fr").unwrap().gettext("Hello"), "Bonjour");
+        assert_eq!(translations.get("mm").unwrap().gettext("Hello"), "Hola");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn declared_catalogs_report_errors() {
+        let dir = temp_dir("declared-errors");
+        assert!(matches!(
+            try_i18n_declared(dir.join("missing"), "app"),
+            Err(I18nError::Dir { .. })
+        ));
+
+        fs::write(dir.join("fr.mo"), "Not a catalog").unwrap();
+        let error = try_i18n_declared(&dir, "app").err().unwrap();
+        assert_eq!(error.lang(), Some("fr"));
+        assert_eq!(error.path(), dir.join("fr.mo"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn catalogs_can_be_read_from_memory() {
+        let (en, fr) = (mo_file(None, "Hi"), mo_file(Some("fr"), "Bonjour"));
+        let readers = vec![("fr", Cursor::new(fr.clone())), ("en", Cursor::new(en))];
+        let translations = Translations::from_readers(readers).unwrap();
+        assert_eq!(translations.all_langs().copied().collect::<Vec<_>>(), vec!["fr", "en"]);
+        assert_eq!(translations.get("en").unwrap().gettext("Hello"), "Hi");
+
+        let translations = Translations::from_bytes(vec![("fr", &fr[..])]).unwrap();
+        assert_eq!(translations.get("fr").unwrap().gettext("Hello"), "Bonjour");
+
+        let broken = Translations::from_bytes(vec![("fr", &fr[..]), ("de", &b"Hallo"[..])]);
+        assert!(matches!(broken.err().unwrap(), Error::Eof));
+    }
+
+    #[test]
+    fn languages_are_found_by_name_and_keep_their_order() {
+        let hello = |hello| CatalogBuilder::new().msg("Hello", hello).build();
+        let mut translations =
+            Translations::from(vec![("en", hello("Hello")), ("pt-BR", hello("Olá"))]);
+        translations.insert("fr", Arc::new(hello("Bonjour")));
+        translations.insert("EN", Arc::new(hello("Hi")));
+
+        let langs = translations.iter().map(|l| l.0).collect::<Vec<_>>();
+        assert_eq!(langs, vec!["en", "pt-BR", "fr"]);
+        assert_eq!(translations.len(), 3);
+        assert_eq!(translations.get("pt-br").unwrap().gettext("Hello"), "Olá");
+        assert_eq!(translations.get("en").unwrap().gettext("Hello"), "Hi");
+        assert_eq!(translations.find("FR"), Some("fr"));
+        assert!(translations.get("de").is_none());
+    }
+
+    #[test]
+    fn translations_are_merged_according_to_the_policy() {
+        let hello = |hello| CatalogBuilder::new().msg("Hello", hello).build();
+        let base = || Translations::from(vec![("en", hello("Hi")), ("fr", hello("Salut"))]);
+        let other = || Translations::from(vec![("FR", hello("Bonjour")), ("de", hello("Hallo"))]);
+        let hello_in = |t: &Translations, lang| t.get(lang).unwrap().gettext("Hello").to_owned();
+
+        let mut kept = base();
+        kept.merge_with(other(), MergePolicy::KeepExisting).unwrap();
+        assert_eq!(kept.all_langs().copied().collect::<Vec<_>>(), vec!["en", "fr", "de"]);
+        assert_eq!(hello_in(&kept, "fr"), "Salut");
+        assert_eq!(hello_in(&kept, "de"), "Hallo");
+
+        let mut overwritten = base();
+        overwritten.merge_with(other(), MergePolicy::Overwrite).unwrap();
+        assert_eq!(hello_in(&overwritten, "fr"), "Bonjour");
+        assert_eq!(overwritten.len(), 3);
+
+        let mut failed = base();
+        let conflict = failed.merge_with(other(), MergePolicy::Error).unwrap_err();
+        assert_eq!(conflict.0, "FR");
+        assert!(failed.get("de").is_none());
+    }
+
+    #[test]
+    fn messages_can_be_translated_in_other_domains() {
+        let hello = |hello| CatalogBuilder::new().msg("Hello", hello).build();
+        let emails = |welcome| {
+            Translations::from(vec![
+                ("fr", CatalogBuilder::new().msg("Welcome", welcome).build()),
+            ])
+        };
+        let translations = Translations::from(vec![("en", hello("Hi")), ("fr", hello("Salut"))])
+            .with_domain("emails", emails("Bienvenu"))
+            .with_domain("emails", emails("Bienvenue"));
+
+        let fr = translations.for_request("fr").unwrap();
+        assert_eq!(fr.catalog.gettext("Hello"), "Salut");
+        assert_eq!(fr.domain("emails").gettext("Welcome"), "Bienvenue");
+        assert_eq!(fr.domain("emails").gettext("Hello"), "Hello");
+        assert_eq!(fr.domain("admin").gettext("Welcome"), "Welcome");
+
+        let en = translations.for_request("en").unwrap();
+        assert_eq!(en.domain("emails").gettext("Welcome"), "Welcome");
+    }
+
+    #[test]
+    fn requests_list_every_supported_language() {
+        let translations = testing::translations(&["en", "fr"])
+            .with_alias("fr-CA", "fr")
+            .with_domain("emails", testing::translations(&["en", "de"]));
+        let i18n = translations.for_request("fr").unwrap();
+        assert_eq!(i18n.available_langs(), &["en", "fr"]);
+        assert_eq!(I18nContext::from(i18n).available_langs(), &["en", "fr"]);
+    }
+
+    #[test]
+    fn macros_translate_messages_in_their_context() {
+        let catalog = CatalogBuilder::new()
+            .msg("Open", "Ouvert")
+            .msg_in_context("file menu", "Open", "Ouvrir")
+            .msg_in_context("file menu", "Open {0}", "Ouvrir {0}")
+            .build();
+        let i18n = I18n::new_for_tests("fr", catalog);
+        assert_eq!(i18n_escaped!(i18n.catalog, ctx "file menu", "Open"), "Ouvrir");
+        assert_eq!(
+            i18n_escaped!(i18n.catalog, ctx "file menu", "Open {0}"; "<b>"),
+            "Ouvrir &lt;b&gt;"
+        );
+        assert_eq!(i18n.catalog.gettext("Open"), "Ouvert");
+    }
+
+    #[test]
+    fn plural_messages_can_have_a_context() {
+        let catalog = CatalogBuilder::new()
+            .plural("One message", "{0} messages", &["Un message", "{0} messages"])
+            .entry(
+                Some("inbox"),
+                "One message",
+                Some("{0} messages"),
+                &["Un message de {1}", "{0} messages de {1}"],
+            )
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        let i18n = I18n::new_for_tests("fr", catalog);
+        assert_eq!(
+            i18n.plural_in_context("inbox", "One message", "{0} messages", 3),
+            "3 messages de {1}"
+        );
+        assert_eq!(i18n.plural("One message", "{0} messages", 0), "Un message");
+        assert_eq!(
+            i18n_escaped!(i18n.catalog, ctx "inbox", "One message", "{0} messages"; 2, "<Ana>"),
+            "2 messages de &lt;Ana&gt;"
+        );
+    }
+
+    #[test]
+    fn counts_are_isolated_in_right_to_left_languages() {
+        let catalog = || {
+            CatalogBuilder::new()
+                .plural("One message", "{0} messages", &["رسالة {0}", "{0} رسائل"])
+                .entry(
+                    Some("inbox"),
+                    "One message",
+                    Some("{0} messages"),
+                    &["", "{0} رسائل من {1}"],
+                )
+                .plural_rule(|n| if n == 1 { 0 } else { 1 })
+                .build()
+        };
+        let ar = I18n::new_for_tests("ar", catalog());
+        assert_eq!(ar.plural("One message", "{0} messages", 3), "\u{2068}3\u{2069} رسائل");
+        assert_eq!(
+            ar.plural_in_context("files", "One file", "{0} files", 2),
+            "\u{2068}2\u{2069} files"
+        );
+        assert_eq!(
+            ar.catalog.ngettext("One message", "{0} messages", 3),
+            "\u{2068}{0}\u{2069} رسائل"
+        );
+        assert_eq!(
+            i18n_escaped!(ar.catalog, ctx "inbox", "One message", "{0} messages"; 2, "<Ali>"),
+            "\u{2068}2\u{2069} رسائل من &lt;Ali&gt;"
+        );
+
+        let en = I18n::new_for_tests("en", catalog());
+        assert_eq!(en.plural("One message", "{0} messages", 3), "3 رسائل");
+        assert_eq!(en.catalog.ngettext("One message", "{0} messages", 3), "{0} رسائل");
+    }
+
+    #[test]
+    fn malformed_plural_forms_fall_back_to_english() {
+        let mo = mo::write(&[
+            (String::new(), "Plural-Forms: nplurals=2; plural=(n != ;\n".to_owned()),
+            ("One file\0{0} files".to_owned(), "Un fichier\0{0} fichiers".to_owned()),
+        ]);
+        assert!(Catalog::parse(&mo[..]).is_err());
+
+        let catalog = parse_catalog(&mo[..], "fr").unwrap();
+        assert_eq!(catalog.ngettext("One file", "{0} files", 1), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "{0} fichiers");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 2), "{0} fichiers");
+    }
 }
@@ -0,0 +1,54 @@
+use crate::{I18nError, Loader, Translations};
+
+/// Loads translations like `i18n`, but without blocking: the `.mo` files are read with
+/// `tokio::fs`, which makes it usable from the asynchronous initialization of an application.
+///
+/// Instead of panicking, it returns the first error it encounters, like `try_i18n`. The
+/// environment variables of `Loader` are used too. To read the catalogs from another directory,
+/// or with another layout, use `Loader::load_async`.
+///
+/// Requires the `tokio` feature.
+pub async fn i18n_async(
+    domain: &str,
+    langs: Vec<&'static str>,
+) -> Result<Translations, I18nError> {
+    Loader::new(domain).load_async(langs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{mo_file, temp_dir};
+    use std::fs;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn errors_tell_which_catalog_failed() {
+        match block_on(i18n_async("app", vec!["xx-missing"])) {
+            Err(I18nError::Io { lang, path, .. }) => {
+                assert_eq!(lang, "xx-missing");
+                assert!(path.ends_with("xx-missing/LC_MESSAGES/app.mo"));
+            }
+            _ => panic!("The catalog should be missing"),
+        }
+    }
+
+    #[test]
+    fn loader_settings_apply() {
+        let dir = temp_dir("load-async");
+        fs::write(dir.join("fr.mo"), mo_file(Some("fr"), "Bonjour")).unwrap();
+
+        let loader = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into());
+        let translations = block_on(loader.load_async(vec!["fr"])).unwrap();
+        assert_eq!(translations.get("fr").unwrap().gettext("Hello"), "Bonjour");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,352 @@
+#[cfg(feature = "lazy")]
+use std::sync::Arc;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "lazy")]
+use crate::lazy::LazyCatalog;
+#[cfg(feature = "po")]
+use crate::po;
+use crate::{
+    gettext_layout, leak, parse_catalog, Catalog, Error, I18nError, ReloadableTranslations,
+    Translations,
+};
+
+/// Overrides the directory of the catalogs.
+const DIR_VAR: &str = "ROCKET_I18N_DIR";
+/// Overrides the languages to load, separated by commas.
+const LANGS_VAR: &str = "ROCKET_I18N_LANGS";
+/// Moves a language to the front of the loaded ones, to make it the default language.
+const DEFAULT_LANG_VAR: &str = "ROCKET_I18N_DEFAULT_LANG";
+
+/// Loads translations from `.mo` files, with more options than `i18n`.
+///
+/// ```rust,ignore
+/// let translations = Loader::new("your-domain")
+///     .dir("/usr/share/locale")
+///     .load(vec!["en", "fr"]);
+/// ```
+///
+/// Some options can be overridden by environment variables, so that the same build (a container
+/// image, for instance) can be deployed with different languages:
+///
+/// - `ROCKET_I18N_DIR` replaces the directory of the catalogs;
+/// - `ROCKET_I18N_LANGS` replaces the languages to load, separated by commas (`de,en,fr`);
+/// - `ROCKET_I18N_DEFAULT_LANG` moves a language to the front of the loaded ones, making it the
+///   default language (unless `I18nConfig::default_lang` is set). It is ignored if this language
+///   is not loaded.
+pub struct Loader<'a> {
+    domain: &'a str,
+    dir: PathBuf,
+    layout: fn(&str, &str) -> PathBuf,
+    relative_to_exe: bool,
+}
+
+impl<'a> Loader<'a> {
+    /// Creates a loader for a gettext domain. By default, it behaves like `i18n`.
+    pub fn new(domain: &'a str) -> Loader<'a> {
+        Loader {
+            domain,
+            dir: PathBuf::from("translations"),
+            layout: gettext_layout,
+            relative_to_exe: false,
+        }
+    }
+
+    /// The directory containing the catalogs (`translations` by default).
+    pub fn dir<P: AsRef<Path>>(mut self, dir: P) -> Loader<'a> {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// The path of the catalog of a language in this directory, given the domain and the
+    /// language.
+    ///
+    /// By default, it is `{lang}/LC_MESSAGES/{domain}.mo`, as in `/usr/share/locale`. For a flat
+    /// directory of `.mo` files, it could be:
+    ///
+    /// ```rust,ignore
+    /// Loader::new("your-domain").layout(|_, lang| format!("{}.mo", lang).into())
+    /// ```
+    pub fn layout(mut self, layout: fn(&str, &str) -> PathBuf) -> Loader<'a> {
+        self.layout = layout;
+        self
+    }
+
+    /// Whether the directory of the catalogs should be looked up next to the executable, instead
+    /// of in the current working directory (the default).
+    ///
+    /// With this option, `/opt/app/bin/server` reads its catalogs from
+    /// `/opt/app/bin/translations`, wherever it is launched from (a systemd unit, a cron job…).
+    /// It has no effect if `dir` is an absolute path.
+    pub fn relative_to_exe(mut self, relative_to_exe: bool) -> Loader<'a> {
+        self.relative_to_exe = relative_to_exe;
+        self
+    }
+
+    /// Loads the catalogs of the given languages.
+    ///
+    /// Like `i18n`, it panics if one of them can't be read or parsed.
+    pub fn load(&self, langs: Vec<&'static str>) -> Translations {
+        self.try_load(langs).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Loads the catalogs of the given languages, or tells which one couldn't be read or parsed.
+    ///
+    /// With the `po` feature, a `.po` file next to the `.mo` file of a language (with the same
+    /// name, but the `po` extension) is used instead of it, so that catalogs don't have to be
+    /// compiled with `msgfmt` during development, and edited translations can be tried
+    /// immediately.
+    pub fn try_load(&self, langs: Vec<&'static str>) -> Result<Translations, I18nError> {
+        let dir = self.catalogs_dir()?;
+        with_env_langs(langs)
+            .into_iter()
+            .map(|lang| {
+                let path = dir.join((self.layout)(self.domain, lang));
+                #[cfg(feature = "po")]
+                {
+                    let po = path.with_extension("po");
+                    if po.is_file() {
+                        let source = fs::read_to_string(&po);
+                        return parse_po(lang, po, source);
+                    }
+                }
+                let bytes = fs::read(&path);
+                parse_mo(lang, path, bytes)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Translations::from)
+    }
+
+    /// Same as `try_load`, but without blocking: the catalogs are read with `tokio::fs`, which
+    /// makes it usable from the asynchronous initialization of an application. See `i18n_async`.
+    ///
+    /// Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub async fn load_async(&self, langs: Vec<&'static str>) -> Result<Translations, I18nError> {
+        let dir = self.catalogs_dir()?;
+        let langs = with_env_langs(langs);
+        let mut trans = Vec::with_capacity(langs.len());
+        for lang in langs {
+            let path = dir.join((self.layout)(self.domain, lang));
+            #[cfg(feature = "po")]
+            {
+                let po = path.with_extension("po");
+                if tokio::fs::metadata(&po).await.is_ok_and(|m| m.is_file()) {
+                    let source = tokio::fs::read_to_string(&po).await;
+                    trans.push(parse_po(lang, po, source)?);
+                    continue;
+                }
+            }
+            let bytes = tokio::fs::read(&path).await;
+            trans.push(parse_mo(lang, path, bytes)?);
+        }
+        Ok(trans.into())
+    }
+
+    /// Loads the catalogs of the given languages like `load`, as translations that can be
+    /// reloaded: `ReloadableTranslations::reload` and `watch` then read them from the directory
+    /// and with the layout of this loader.
+    pub fn reloadable(&self, langs: Vec<&'static str>) -> ReloadableTranslations {
+        let dir = self.catalogs_dir().unwrap_or_else(|e| panic!("{}", e));
+        ReloadableTranslations::with_layout(self.domain, dir, self.layout, self.load(langs))
+    }
+
+    /// Prepares translations for the given languages, without loading any catalog yet: the catalog
+    /// of a language is only loaded the first time a request negotiates it, and then kept.
+    ///
+    /// It saves memory (and startup time) for applications supporting many languages, that are
+    /// not all used. As errors are only found later, a catalog that can't be read or parsed is
+    /// logged, and its language then behaves as if it had no catalog.
+    ///
+    /// Catalogs are read synchronously, by the first request that needs them: with an
+    /// asynchronous framework, this request blocks a thread of the runtime while the file is read.
+    /// If that matters, load the catalogs eagerly at startup, with `load_async` for instance.
+    ///
+    /// Like `load`, it panics if the directory of the executable can't be found (see
+    /// `relative_to_exe`).
+    ///
+    /// Requires the `lazy` feature.
+    #[cfg(feature = "lazy")]
+    pub fn lazy(&self, langs: Vec<&'static str>) -> Translations {
+        let dir = self.catalogs_dir().unwrap_or_else(|e| panic!("{}", e));
+        let mut translations = Translations::default();
+        for lang in with_env_langs(langs) {
+            let path = dir.join((self.layout)(self.domain, lang));
+            translations.insert_lazy(lang, Arc::new(LazyCatalog::new(path)));
+        }
+        translations
+    }
+
+    /// The directory containing the catalogs, unless `ROCKET_I18N_DIR` overrides it.
+    fn catalogs_dir(&self) -> Result<PathBuf, I18nError> {
+        let dir = env_dir().unwrap_or_else(|| self.dir.clone());
+        Ok(self.base(&dir)?.join(dir))
+    }
+
+    /// The directory relative paths are resolved from.
+    fn base(&self, dir: &Path) -> Result<PathBuf, I18nError> {
+        if !self.relative_to_exe {
+            return Ok(PathBuf::new());
+        }
+        env::current_exe()
+            .and_then(|exe| {
+                exe.parent().map(PathBuf::from).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "The executable has no parent")
+                })
+            })
+            .map_err(|error| I18nError::Dir {
+                path: dir.to_path_buf(),
+                error,
+            })
+    }
+}
+
+pub(crate) fn catalog_error(lang: &'static str, path: PathBuf, error: Error) -> I18nError {
+    match error {
+        Error::Io(error) => I18nError::Io { lang, path, error },
+        error => I18nError::Parse { lang, path, error },
+    }
+}
+
+/// Parses the catalog of a language, read from the `.mo` file at `path`.
+fn parse_mo(
+    lang: &'static str,
+    path: PathBuf,
+    bytes: io::Result<Vec<u8>>,
+) -> Result<(&'static str, Catalog), I18nError> {
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(error) => return Err(I18nError::Io { lang, path, error }),
+    };
+    match parse_catalog(&bytes[..], lang) {
+        Ok(cat) => Ok((lang, cat)),
+        Err(error) => Err(catalog_error(lang, path, error)),
+    }
+}
+
+/// Parses the catalog of a language, read from the `.po` file at `path`.
+#[cfg(feature = "po")]
+fn parse_po(
+    lang: &'static str,
+    path: PathBuf,
+    source: io::Result<String>,
+) -> Result<(&'static str, Catalog), I18nError> {
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => return Err(I18nError::Io { lang, path, error }),
+    };
+    let mo = match po::to_mo(&source) {
+        Ok(mo) => mo,
+        Err(po::SyntaxError(line)) => return Err(I18nError::PoSyntax { lang, path, line }),
+    };
+    match parse_catalog(&mo[..], lang) {
+        Ok(cat) => Ok((lang, cat)),
+        Err(error) => Err(catalog_error(lang, path, error)),
+    }
+}
+
+/// The directory of the catalogs set in `ROCKET_I18N_DIR`, if any.
+fn env_dir() -> Option<PathBuf> {
+    env::var_os(DIR_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The directory `i18n` reads the catalogs from: `translations`, unless `ROCKET_I18N_DIR`
+/// overrides it.
+pub(crate) fn default_dir() -> PathBuf {
+    env_dir().unwrap_or_else(|| PathBuf::from("translations"))
+}
+
+/// The languages to load, as overridden by `ROCKET_I18N_LANGS` and `ROCKET_I18N_DEFAULT_LANG`.
+pub(crate) fn with_env_langs(langs: Vec<&'static str>) -> Vec<&'static str> {
+    override_langs(langs, |name| env::var(name).ok())
+}
+
+/// Applies the overrides of `with_env_langs`, reading the variables with `var`.
+fn override_langs(
+    langs: Vec<&'static str>,
+    var: impl Fn(&str) -> Option<String>,
+) -> Vec<&'static str> {
+    let mut langs = match var(LANGS_VAR).filter(|l| !l.trim().is_empty()) {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .map(|lang| leak(lang.to_owned()))
+            .collect(),
+        None => langs,
+    };
+    if let Some(default) = var(DEFAULT_LANG_VAR) {
+        if let Some(i) = langs
+            .iter()
+            .position(|lang| lang.eq_ignore_ascii_case(default.trim()))
+        {
+            let default = langs.remove(i);
+            langs.insert(0, default);
+        }
+    }
+    langs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{mo_file, temp_dir};
+
+    #[test]
+    fn catalogs_are_read_with_the_layout() {
+        let dir = temp_dir("loader-layout");
+        fs::write(dir.join("fr.mo"), mo_file(Some("fr"), "Bonjour")).unwrap();
+
+        let translations = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into())
+            .try_load(vec!["fr"])
+            .unwrap();
+        assert_eq!(translations.get("fr").unwrap().gettext("Hello"), "Bonjour");
+
+        match Loader::new("app").dir(&dir).try_load(vec!["fr"]) {
+            Err(I18nError::Io { lang, path, .. }) => {
+                assert_eq!(lang, "fr");
+                assert_eq!(path, dir.join("fr/LC_MESSAGES/app.mo"));
+            }
+            _ => panic!("The catalog should be missing"),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn relative_to_exe_resolves_from_the_executable() {
+        let exe_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        let error = Loader::new("app")
+            .dir("missing-translations")
+            .relative_to_exe(true)
+            .try_load(vec!["fr"])
+            .err()
+            .unwrap();
+        assert!(error.path().starts_with(exe_dir.join("missing-translations")));
+    }
+
+    #[test]
+    fn languages_can_be_overridden_by_the_environment() {
+        let with_vars = |vars: &[(&str, &str)]| {
+            let var = |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| (*value).to_owned())
+            };
+            override_langs(vec!["en", "fr"], var)
+        };
+        assert_eq!(with_vars(&[]), ["en", "fr"]);
+        assert_eq!(with_vars(&[(LANGS_VAR, " de, ,it ")]), ["de", "it"]);
+        assert_eq!(with_vars(&[(LANGS_VAR, " ")]), ["en", "fr"]);
+        assert_eq!(with_vars(&[(DEFAULT_LANG_VAR, "FR")]), ["fr", "en"]);
+        assert_eq!(with_vars(&[(DEFAULT_LANG_VAR, "ja")]), ["en", "fr"]);
+        let both = [(LANGS_VAR, "de,en,it"), (DEFAULT_LANG_VAR, "it")];
+        assert_eq!(with_vars(&both), ["it", "de", "en"]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Language tags, split into their components.
+
+use std::fmt;
+
+/// A language tag (`zh-Hant-TW`), split into its language, script and region.
+///
+/// ```rust,ignore
+/// let locale = i18n.locale();
+/// if locale.region() == Some("US") {
+///     // Use imperial units
+/// }
+/// ```
+///
+/// Tags are parsed leniently: the first subtag is always the language, and the script and region
+/// are only recognized where they are expected. Other subtags (variants, extensions, or the
+/// `.UTF-8` and `@modifier` of POSIX locales) are ignored.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale {
+    language: String,
+    script: Option<String>,
+    region: Option<String>,
+}
+
+impl Locale {
+    /// Parses a language tag (`pt-BR`), or a POSIX locale (`pt_BR.UTF-8`).
+    ///
+    /// The case of each component is normalized: `zh_hant_tw` becomes `zh-Hant-TW`.
+    pub fn new(tag: &str) -> Locale {
+        let tag = tag.split(['.', '@']).next().unwrap_or(tag);
+        let mut subtags = tag.split(['-', '_']).peekable();
+        let language = subtags.next().unwrap_or("").to_lowercase();
+        let script = subtags
+            .peek()
+            .filter(|s| s.len() == 4 && s.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(|s| s[..1].to_uppercase() + &s[1..].to_lowercase());
+        if script.is_some() {
+            subtags.next();
+        }
+        let region = subtags
+            .next()
+            .filter(|s| {
+                (s.len() == 2 && s.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(str::to_uppercase);
+        Locale {
+            language,
+            script,
+            region,
+        }
+    }
+
+    /// The language (`pt` for `pt-BR`).
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The script, if the tag has one (`Hant` for `zh-Hant-TW`, but `None` for `zh-TW`).
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    /// The region, if the tag has one (`BR` for `pt-BR`, `419` for `es-419`).
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+}
+
+/// Formats the locale as a language tag (`zh-Hant-TW`).
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.language)?;
+        for subtag in self.script.iter().chain(&self.region) {
+            write!(f, "-{}", subtag)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogBuilder, I18n};
+
+    #[test]
+    fn tags_are_split_and_normalized() {
+        let locale = Locale::new("zh_hant_tw");
+        assert_eq!(locale.language(), "zh");
+        assert_eq!(locale.script(), Some("Hant"));
+        assert_eq!(locale.region(), Some("TW"));
+        assert_eq!(locale.to_string(), "zh-Hant-TW");
+
+        assert_eq!(Locale::new("pt_BR.UTF-8@euro").to_string(), "pt-BR");
+        assert_eq!(Locale::new("es-419").region(), Some("419"));
+        assert_eq!(Locale::new("sr-Latn").region(), None);
+        assert_eq!(Locale::new("de-DE-1996").to_string(), "de-DE");
+        assert_eq!(Locale::new("en-x-private").to_string(), "en");
+        assert_eq!(Locale::new("FR"), Locale::new("fr"));
+
+        let i18n = I18n::new_for_tests("en-GB", CatalogBuilder::new().build());
+        assert_eq!(i18n.locale().region(), Some("GB"));
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+
+use crate::{I18n, LazyTranslation};
+
+/// Values that can be serialized in the language of a request, to return translated messages
+/// from a JSON API.
+///
+/// `LazyTranslation`s are serialized as their translation, and slices, `Vec`s and `Option`s of
+/// values implementing this trait are supported too. Implement it for the types of your API
+/// responses, translating their fields with `I18n::localized`:
+///
+/// ```rust,ignore
+/// struct ApiError {
+///     code: u16,
+///     message: LazyTranslation,
+/// }
+///
+/// impl SerializeLocalized for ApiError {
+///     fn serialize_localized<S: Serializer>(
+///         &self,
+///         i18n: &I18n,
+///         serializer: S,
+///     ) -> Result<S::Ok, S::Error> {
+///         let mut error = serializer.serialize_struct("ApiError", 2)?;
+///         error.serialize_field("code", &self.code)?;
+///         error.serialize_field("message", &i18n.localized(&self.message))?;
+///         error.end()
+///     }
+/// }
+///
+/// let error = ApiError { code: 404, message: LazyTranslation::new("This post doesn't exist") };
+/// serde_json::to_string(&i18n.localized(&error))
+/// ```
+///
+/// Requires the `serde` feature.
+pub trait SerializeLocalized {
+    /// Serializes this value, translating its messages with `i18n`.
+    fn serialize_localized<S: Serializer>(
+        &self,
+        i18n: &I18n,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+}
+
+/// A value serialized in the language of a request. See `SerializeLocalized`.
+///
+/// Requires the `serde` feature.
+pub struct Localized<'a, T: ?Sized> {
+    value: &'a T,
+    i18n: &'a I18n,
+}
+
+impl<'a, T: ?Sized> Localized<'a, T> {
+    /// Serializes `value` in the language of `i18n`.
+    pub fn new(value: &'a T, i18n: &'a I18n) -> Localized<'a, T> {
+        Localized { value, i18n }
+    }
+}
+
+impl<'a, T: SerializeLocalized + ?Sized> Serialize for Localized<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize_localized(self.i18n, serializer)
+    }
+}
+
+impl I18n {
+    /// Wraps a value to serialize it in the language of the current request. See
+    /// `SerializeLocalized`.
+    ///
+    /// Requires the `serde` feature.
+    pub fn localized<'a, T: SerializeLocalized + ?Sized>(
+        &'a self,
+        value: &'a T,
+    ) -> Localized<'a, T> {
+        Localized::new(value, self)
+    }
+}
+
+impl SerializeLocalized for LazyTranslation {
+    fn serialize_localized<S: Serializer>(
+        &self,
+        i18n: &I18n,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.translate(&i18n.catalog))
+    }
+}
+
+impl<T: SerializeLocalized> SerializeLocalized for [T] {
+    fn serialize_localized<S: Serializer>(
+        &self,
+        i18n: &I18n,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for value in self {
+            seq.serialize_element(&Localized::new(value, i18n))?;
+        }
+        seq.end()
+    }
+}
+
+impl<T: SerializeLocalized> SerializeLocalized for Vec<T> {
+    fn serialize_localized<S: Serializer>(
+        &self,
+        i18n: &I18n,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self[..].serialize_localized(i18n, serializer)
+    }
+}
+
+impl<T: SerializeLocalized> SerializeLocalized for Option<T> {
+    fn serialize_localized<S: Serializer>(
+        &self,
+        i18n: &I18n,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => serializer.serialize_some(&Localized::new(value, i18n)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::CatalogBuilder;
+    use serde::ser::SerializeStruct;
+
+    struct ApiError {
+        code: u16,
+        message: LazyTranslation,
+        hints: Vec<LazyTranslation>,
+    }
+
+    impl SerializeLocalized for ApiError {
+        fn serialize_localized<S: Serializer>(
+            &self,
+            i18n: &I18n,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let mut error = serializer.serialize_struct("ApiError", 3)?;
+            error.serialize_field("code", &self.code)?;
+            error.serialize_field("message", &i18n.localized(&self.message))?;
+            error.serialize_field("hints", &i18n.localized(&self.hints))?;
+            error.end()
+        }
+    }
+
+    #[test]
+    fn messages_are_serialized_in_the_language_of_the_request() {
+        let catalog = CatalogBuilder::new()
+            .msg("Not found", "Introuvable")
+            .msg("Check the URL", "Vérifiez l'URL")
+            .build();
+        let i18n = I18n::new_for_tests("fr", catalog);
+        let error = ApiError {
+            code: 404,
+            message: LazyTranslation::new("Not found"),
+            hints: vec![
+                LazyTranslation::new("Check the URL"),
+                LazyTranslation::new("Go back"),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_string(&i18n.localized(&error)).unwrap(),
+            r#"{"code":404,"message":"Introuvable","hints":["Vérifiez l'URL","Go back"]}"#
+        );
+
+        let missing: Option<LazyTranslation> = None;
+        assert_eq!(serde_json::to_string(&i18n.localized(&missing)).unwrap(), "null");
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::{Lookup, Translations};
+
+/// A message to translate later, once the language to use is known.
+///
+/// It is useful to prepare messages where there is no request (notifications, emails sent in the
+/// background, …), and to translate them in the language of their recipient.
+///
+/// ```rust,ignore
+/// let message = LazyTranslation::plural("One new follower", "{0} new followers", 3)
+///     .context("notifications");
+/// let mail = LazyTranslation::new("Hello {0}!").arg(&user.name);
+///
+/// // Later
+/// let body = mail.translate_in(&translations, &user.lang);
+/// let title = message.translate(&i18n.catalog);
+/// ```
+///
+/// Formatting arguments work like with `i18n!`: they replace `{0}`, `{1}`, … and for messages
+/// with a plural form, `{0}` is the number of elements and the other arguments start at `{1}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LazyTranslation {
+    msg_context: Option<String>,
+    msg_id: String,
+    /// The plural form of the message, and the number of elements.
+    plural: Option<(String, u64)>,
+    args: Vec<String>,
+}
+
+impl LazyTranslation {
+    /// A message without plural form.
+    pub fn new<S: Into<String>>(msg_id: S) -> LazyTranslation {
+        LazyTranslation {
+            msg_context: None,
+            msg_id: msg_id.into(),
+            plural: None,
+            args: Vec::new(),
+        }
+    }
+
+    /// A message with a plural form, that will be chosen for `n`.
+    pub fn plural<S: Into<String>, P: Into<String>>(
+        msg_id: S,
+        msg_id_plural: P,
+        n: u64,
+    ) -> LazyTranslation {
+        LazyTranslation {
+            plural: Some((msg_id_plural.into(), n)),
+            ..LazyTranslation::new(msg_id)
+        }
+    }
+
+    /// Gives a context (`msgctxt`) to this message.
+    pub fn context<S: Into<String>>(mut self, msg_context: S) -> LazyTranslation {
+        self.msg_context = Some(msg_context.into());
+        self
+    }
+
+    /// Adds a formatting argument.
+    pub fn arg<T: fmt::Display + ?Sized>(mut self, arg: &T) -> LazyTranslation {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// The id of this message.
+    pub fn msg_id(&self) -> &str {
+        &self.msg_id
+    }
+
+    /// Translates this message with a catalog, and inserts its formatting arguments.
+    pub fn translate<C: Lookup + ?Sized>(&self, catalog: &C) -> String {
+        let ctx = self.msg_context.as_deref();
+        match (&self.plural, ctx) {
+            (None, None) => format(catalog.gettext(&self.msg_id), None, &self.args),
+            (None, Some(ctx)) => format(catalog.pgettext(ctx, &self.msg_id), None, &self.args),
+            (Some((plural, n)), None) => format(
+                catalog.ngettext(&self.msg_id, plural, *n),
+                Some(*n),
+                &self.args,
+            ),
+            (Some((plural, n)), Some(ctx)) => format(
+                catalog.npgettext(ctx, &self.msg_id, plural, *n),
+                Some(*n),
+                &self.args,
+            ),
+        }
+    }
+
+    /// Translates this message in a given language, resolved the same way as the language of a
+    /// request would be (see `Translations::translate_in`).
+    ///
+    /// If no language matches, the message is left untranslated.
+    pub fn translate_in(&self, translations: &Translations, lang: &str) -> String {
+        match translations.catalog_in(lang) {
+            Some(catalog) => self.translate(catalog),
+            None => self.translate(&crate::empty_catalog()),
+        }
+    }
+}
+
+/// Replaces `{0}`, `{1}`, … with the number of elements, if any, and the arguments.
+fn format(translated: &str, count: Option<u64>, args: &[String]) -> String {
+    let count = count.map(|n| n.to_string());
+    let values = count.iter().chain(args).collect::<Vec<_>>();
+    let mut res = String::with_capacity(translated.len());
+    let mut rest = translated;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let index = rest[1..end].parse::<usize>().ok()?;
+            Some((end, values.get(index)?))
+        });
+        match value {
+            Some((end, value)) => {
+                res.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CatalogBuilder;
+
+    fn translations() -> Translations {
+        let fr = CatalogBuilder::new()
+            .msg("Hello {0}!", "Bonjour {0} !")
+            .msg_in_context("button", "Follow", "Suivre")
+            .plural("One new follower", "{0} new followers", &["Un abonné", "{0} abonnés"])
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        let en = CatalogBuilder::new().msg("Hello {0}!", "Hi {0}!").build();
+        Translations::from(vec![("en", en), ("fr", fr)])
+    }
+
+    #[test]
+    fn messages_are_translated_once_the_language_is_known() {
+        let translations = translations();
+        let hello = LazyTranslation::new("Hello {0}!").arg("Ana");
+        assert_eq!(hello.translate_in(&translations, "fr-FR"), "Bonjour Ana !");
+        assert_eq!(hello.translate_in(&translations, "de"), "Hi Ana!");
+        assert_eq!(hello.translate_in(&Translations::default(), "fr"), "Hello Ana!");
+
+        let followers = LazyTranslation::plural("One new follower", "{0} new followers", 3);
+        assert_eq!(followers.translate_in(&translations, "fr"), "3 abonnés");
+        let follow = LazyTranslation::new("Follow").context("button");
+        assert_eq!(follow.translate(translations.get("fr").unwrap()), "Suivre");
+        assert_eq!(follow.msg_id(), "Follow");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        let formatted = format("{1} and {0} {x} {2} {", Some(3), &["a".to_owned()]);
+        assert_eq!(formatted, "a and 3 {x} {2} {");
+    }
+}
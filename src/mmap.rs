@@ -0,0 +1,210 @@
+//! Catalogs read directly from memory-mapped `.mo` files.
+//!
+//! Messages are looked up in the table of original strings (which `msgfmt` sorts) and returned
+//! from the mapped file, so that only the pages of the file that are actually used are loaded in
+//! memory, and shared between processes.
+
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{self, ErrorKind},
+    path::Path,
+    str,
+};
+
+use memmap2::Mmap;
+
+use crate::english_plural;
+
+/// A catalog of translated messages, read from a memory-mapped `.mo` file.
+///
+/// It has the same lookup methods as `gettext::Catalog`, and implements `Lookup`, so it works
+/// with `i18n!`, `t!` and code generic over catalogs. Like the built-in `Catalog`, it doesn't
+/// read the `Plural-Forms` header: its plural rule is set with `set_plural_rule`.
+///
+/// Requires the `mmap` feature.
+pub struct MappedCatalog {
+    map: Mmap,
+    big_endian: bool,
+    count: usize,
+    originals: usize,
+    translations: usize,
+    /// The entries, sorted by original string, if the file doesn't already sort them.
+    order: Option<Vec<usize>>,
+    plural: fn(u64) -> usize,
+}
+
+impl MappedCatalog {
+    /// Maps a `.mo` file, using the English plural rule (`n != 1`).
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while it is mapped (even by another process), or lookups
+    /// may return garbage or crash. To update a catalog, write the new version to another file
+    /// and rename it over the old one, which leaves the mapped file untouched.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedCatalog> {
+        let map = Mmap::map(&File::open(path)?)?;
+        let big_endian = match read_word(&map, 0, false) {
+            Some(0x9504_12de) => false,
+            Some(0xde12_0495) => true,
+            _ => return Err(io::Error::new(ErrorKind::InvalidData, "Not a .mo file")),
+        };
+        let header = |offset| {
+            read_word(&map, offset, big_endian)
+                .ok_or_else(|| io::Error::new(ErrorKind::UnexpectedEof, "Truncated .mo file"))
+        };
+        let (count, originals, translations) = (header(8)?, header(12)?, header(16)?);
+
+        let mut catalog = MappedCatalog {
+            map,
+            big_endian,
+            count,
+            originals,
+            translations,
+            order: None,
+            plural: english_plural,
+        };
+        if (1..count).any(|i| catalog.msg_id(i - 1) > catalog.msg_id(i)) {
+            let mut order = (0..count).collect::<Vec<_>>();
+            order.sort_by(|a, b| catalog.msg_id(*a).cmp(&catalog.msg_id(*b)));
+            catalog.order = Some(order);
+        }
+        Ok(catalog)
+    }
+
+    /// Changes the function choosing the plural form to use for a number.
+    pub fn set_plural_rule(&mut self, plural: fn(u64) -> usize) {
+        self.plural = plural;
+    }
+
+    /// Translates a message.
+    pub fn gettext<'a>(&'a self, msg_id: &'a str) -> &'a str {
+        self.find(None, msg_id, 0).unwrap_or(msg_id)
+    }
+
+    /// Translates a message that has a plural form, choosing the form to use for `n`.
+    pub fn ngettext<'a>(&'a self, msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+        self.find(None, msg_id, (self.plural)(n))
+            .unwrap_or_else(|| untranslated(msg_id, msg_id_plural, n))
+    }
+
+    /// Translates a message in a given context.
+    pub fn pgettext<'a>(&'a self, msg_context: &'a str, msg_id: &'a str) -> &'a str {
+        self.find(Some(msg_context), msg_id, 0).unwrap_or(msg_id)
+    }
+
+    /// Translates a message that has a plural form in a given context.
+    pub fn npgettext<'a>(
+        &'a self,
+        msg_context: &'a str,
+        msg_id: &'a str,
+        msg_id_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.find(Some(msg_context), msg_id, (self.plural)(n))
+            .unwrap_or_else(|| untranslated(msg_id, msg_id_plural, n))
+    }
+
+    /// The `form`th translation of a message, if it is translated.
+    fn find(&self, msg_context: Option<&str>, msg_id: &str, form: usize) -> Option<&str> {
+        let key = match msg_context {
+            Some(ctx) => format!("{}\u{4}{}", ctx, msg_id),
+            None => msg_id.to_owned(),
+        };
+        let entry = self.entry(&key)?;
+        let translations = self.string(self.translations, entry)?;
+        let translation = translations.split(|b| *b == 0).nth(form)?;
+        if translation.is_empty() {
+            None
+        } else {
+            str::from_utf8(translation).ok()
+        }
+    }
+
+    /// The entry whose original string is `key`, found by binary search.
+    fn entry(&self, key: &str) -> Option<usize> {
+        let sorted = |i: usize| self.order.as_ref().map_or(i, |order| order[i]);
+        let (mut low, mut high) = (0, self.count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.msg_id(sorted(middle)).cmp(&Some(key.as_bytes())) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(sorted(middle)),
+            }
+        }
+        None
+    }
+
+    /// The original string of an entry, without its plural form.
+    fn msg_id(&self, entry: usize) -> Option<&[u8]> {
+        let original = self.string(self.originals, entry)?;
+        original.split(|b| *b == 0).next()
+    }
+
+    /// The `i`th string of a table of the file.
+    fn string(&self, table: usize, i: usize) -> Option<&[u8]> {
+        let len = read_word(&self.map, table + i * 8, self.big_endian)?;
+        let offset = read_word(&self.map, table + i * 8 + 4, self.big_endian)?;
+        self.map.get(offset..offset.checked_add(len)?)
+    }
+}
+
+fn read_word(bytes: &[u8], offset: usize, big_endian: bool) -> Option<usize> {
+    let b = bytes.get(offset..offset.checked_add(4)?)?;
+    let b = [b[0], b[1], b[2], b[3]];
+    Some(if big_endian {
+        u32::from_be_bytes(b)
+    } else {
+        u32::from_le_bytes(b)
+    } as usize)
+}
+
+/// What gettext returns for untranslated messages: the singular for `1`, the plural otherwise.
+fn untranslated<'a>(msg_id: &'a str, msg_id_plural: &'a str, n: u64) -> &'a str {
+    if n == 1 {
+        msg_id
+    } else {
+        msg_id_plural
+    }
+}
+
+#[cfg(all(test, feature = "gettext"))]
+mod tests {
+    use super::*;
+    use crate::{mo, tests::temp_dir};
+    use std::fs;
+
+    #[test]
+    fn messages_are_read_from_the_mapped_file() {
+        let dir = temp_dir("mmap");
+        let entries = [
+            ("Hello", "Bonjour"),
+            ("One file\0{0} files", "Un fichier\0{0} fichiers"),
+            ("menu\u{4}Open", "Ouvrir"),
+            ("Untranslated", ""),
+        ];
+        let entries = entries
+            .iter()
+            .map(|(id, tr)| (id.to_string(), tr.to_string()))
+            .collect::<Vec<_>>();
+        fs::write(dir.join("fr.mo"), mo::write(&entries)).unwrap();
+        fs::write(dir.join("notes.txt"), "Not a catalog").unwrap();
+
+        let mut catalog = unsafe { MappedCatalog::open(dir.join("fr.mo")) }.unwrap();
+        assert_eq!(catalog.gettext("Hello"), "Bonjour");
+        assert_eq!(catalog.gettext("Goodbye"), "Goodbye");
+        assert_eq!(catalog.gettext("Untranslated"), "Untranslated");
+        assert_eq!(catalog.pgettext("menu", "Open"), "Ouvrir");
+        assert_eq!(catalog.gettext("Open"), "Open");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 1), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "{0} fichiers");
+        assert_eq!(catalog.ngettext("One dir", "{0} dirs", 2), "{0} dirs");
+        catalog.set_plural_rule(|n| if n > 1 { 1 } else { 0 });
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "Un fichier");
+
+        let error = unsafe { MappedCatalog::open(dir.join("notes.txt")) }.err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+//! A minimal reader for `.mo` files, to list their entries (which `gettext` doesn't allow), and
+//! writer, to build catalogs from other formats.
+//!
+//! The optional hash table of `.mo` files is never used: entries are always read from the tables
+//! of original and translated strings, which every `msgfmt` writes. Catalogs compiled with or
+//! without a hash table (`msgfmt --no-hash`) are thus read the same way, which is also what
+//! `gettext` does when it parses them.
+
+use crate::Error;
+
+/// A message of a catalog.
+pub(crate) struct Entry {
+    pub context: Option<String>,
+    pub id: String,
+    pub plural: Option<String>,
+    pub translations: Vec<String>,
+}
+
+/// Lists the entries of a `.mo` file, including its header (the entry with an empty `id`).
+pub(crate) fn entries(bytes: &[u8]) -> Result<Vec<Entry>, Error> {
+    let word = |offset: usize, big_endian: bool| -> Result<usize, Error> {
+        let b = bytes.get(offset..offset + 4).ok_or(Error::Eof)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(if big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        } as usize)
+    };
+    let big_endian = match word(0, false)? {
+        0x9504_12de => false,
+        0xde12_0495 => true,
+        _ => return Err(Error::BadMagic),
+    };
+    let string = |table: usize, i: usize| -> Result<String, Error> {
+        let len = word(table + i * 8, big_endian)?;
+        let offset = word(table + i * 8 + 4, big_endian)?;
+        let b = bytes.get(offset..offset + len).ok_or(Error::Eof)?;
+        Ok(String::from_utf8_lossy(b).into_owned())
+    };
+
+    let count = word(8, big_endian)?;
+    let originals = word(12, big_endian)?;
+    let translations = word(16, big_endian)?;
+    (0..count)
+        .map(|i| {
+            let original = string(originals, i)?;
+            let (context, original) = match original.find('\u{4}') {
+                Some(sep) => (
+                    Some(original[..sep].to_owned()),
+                    original[sep + 1..].to_owned(),
+                ),
+                None => (None, original),
+            };
+            let mut ids = original.split('\0');
+            Ok(Entry {
+                context,
+                id: ids.next().unwrap_or_default().to_owned(),
+                plural: ids.next().map(str::to_owned),
+                translations: string(translations, i)?
+                    .split('\0')
+                    .map(str::to_owned)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Writes a `.mo` file (without hash table) containing the given entries, sorted by original
+/// string.
+pub(crate) fn write(entries: &[(String, String)]) -> Vec<u8> {
+    // Readers without a hash table may look originals up with a binary search
+    let mut entries = entries.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    let count = entries.len();
+    let originals = 28;
+    let translations = originals + count * 8;
+    let hash_table = translations + count * 8;
+    let mut offset = hash_table;
+
+    let mut tables = Vec::new();
+    let mut strings = Vec::new();
+    for string in entries
+        .iter()
+        .map(|e| &e.0)
+        .chain(entries.iter().map(|e| &e.1))
+    {
+        tables.push((string.len(), offset));
+        strings.extend_from_slice(string.as_bytes());
+        strings.push(0);
+        offset += string.len() + 1;
+    }
+
+    let mut mo = Vec::with_capacity(offset);
+    for word in &[
+        0x9504_12de,
+        0,
+        count,
+        originals,
+        translations,
+        0,
+        hash_table,
+    ] {
+        mo.extend_from_slice(&(*word as u32).to_le_bytes());
+    }
+    for (len, offset) in tables {
+        mo.extend_from_slice(&(len as u32).to_le_bytes());
+        mo.extend_from_slice(&(offset as u32).to_le_bytes());
+    }
+    mo.extend_from_slice(&strings);
+    mo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_catalog;
+
+    /// `tests/fixtures/fr.po`, compiled by `msgfmt` with and without a hash table.
+    const HASH: &[u8] = include_bytes!("../tests/fixtures/hash.mo");
+    const NO_HASH: &[u8] = include_bytes!("../tests/fixtures/no-hash.mo");
+
+    #[test]
+    fn catalogs_are_read_with_or_without_a_hash_table() {
+        let hash_table_size = |mo: &[u8]| u32::from_le_bytes([mo[20], mo[21], mo[22], mo[23]]);
+        assert!(hash_table_size(HASH) > 0);
+        assert_eq!(hash_table_size(NO_HASH), 0);
+
+        for mo in &[HASH, NO_HASH] {
+            let catalog = parse_catalog(*mo, "fr").unwrap();
+            assert_eq!(catalog.gettext("Hello"), "Bonjour");
+            assert_eq!(catalog.gettext("Goodbye"), "Goodbye");
+            assert_eq!(catalog.gettext("Open"), "Ouvert");
+            assert_eq!(catalog.pgettext("file menu", "Open"), "Ouvrir");
+            assert_eq!(catalog.ngettext("One file", "{0} files", 1), "Un fichier");
+            assert_eq!(catalog.ngettext("One file", "{0} files", 2), "{0} fichiers");
+            let received = catalog.npgettext("inbox", "One message", "{0} messages", 0);
+            assert_eq!(received, "Un message reçu");
+
+            let entries = entries(mo).unwrap();
+            assert_eq!(entries.len(), 6);
+            let plural = entries.iter().find(|e| e.id == "One message").unwrap();
+            assert_eq!(plural.context.as_deref(), Some("inbox"));
+            assert_eq!(plural.plural.as_deref(), Some("{0} messages"));
+            assert_eq!(plural.translations, ["Un message reçu", "{0} messages reçus"]);
+        }
+    }
+
+    #[test]
+    fn written_entries_are_sorted_by_original_string() {
+        let entries = [("b", "B"), ("", "Language: fr\n"), ("a\u{4}b", "AB"), ("a", "A")];
+        let entries = entries
+            .iter()
+            .map(|(id, tr)| (id.to_string(), tr.to_string()))
+            .collect::<Vec<_>>();
+        let mo = write(&entries);
+        let ids = super::entries(&mo).unwrap().into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids, ["", "a", "b", "b"]);
+        assert_eq!(parse_catalog(&mo[..], "fr").unwrap().pgettext("a", "b"), "AB");
+    }
+}
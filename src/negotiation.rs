@@ -0,0 +1,924 @@
+use log::debug;
+use std::{borrow::Cow, cmp::Ordering, env, iter, net::IpAddr, slice};
+
+use crate::{
+    resolver::{LocaleRequest, Resolver, DEFAULT_CHAIN},
+    Catalog, I18nConfig, Translations,
+};
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+use crate::ACCEPT_LANG;
+
+/// Chooses the language to use for an `Accept-Language` header, among a list of supported
+/// languages ordered by preference, exactly like the language of a request is chosen without
+/// `I18nConfig`.
+///
+/// Contrary to the negotiation of a request, there is no default language: `None` means that the
+/// client accepts none of the supported languages (not even a variant of them).
+///
+/// ```rust,ignore
+/// let lang = rocket_i18n::negotiate_language("fr-CH, fr;q=0.9, en;q=0.8", &["en", "fr"]);
+/// // Some("fr")
+/// ```
+pub fn negotiate_language<'a>(header: &str, supported: &[&'a str]) -> Option<&'a str> {
+    preferences(header)
+        .into_iter()
+        .find_map(|(lang, _)| accepted(supported, None, header, lang))
+}
+
+/// Like `negotiate_language`, but chooses among `Translations`, and returns the catalog of the
+/// chosen language with it.
+pub fn negotiate_catalog<'t>(
+    header: &str,
+    translations: &'t Translations,
+) -> Option<(&'static str, &'t Catalog)> {
+    let lang = preferences(header)
+        .into_iter()
+        .find_map(|(lang, _)| accepted(translations, None, header, lang))?;
+    let lang = translations.served(lang)?;
+    translations.get(lang).map(|catalog| (lang, catalog))
+}
+
+/// The supported languages, as seen by `matching`: `Translations`, or a plain list of languages.
+trait Supported<'a>: Copy {
+    type Names: Iterator<Item = &'a str>;
+
+    /// The supported languages, from the most to the least preferred.
+    fn names(self) -> Self::Names;
+
+    /// The regional variant registered for `lang` with `Translations::with_region_default`.
+    fn region_default(self, lang: &str) -> Option<&'a str>;
+
+    /// The language that `lang` stands for, registered with `Translations::with_alias`.
+    fn alias(self, lang: &str) -> Option<&'a str>;
+
+    /// A language to serve for `lang`, thanks to the fallback chain registered with
+    /// `Translations::with_fallback_chain`.
+    fn fallback(self, lang: &str) -> Option<&'a str>;
+
+    /// The supported language named `lang`, ignoring its case.
+    fn find(self, lang: &str) -> Option<&'a str> {
+        self.names().find(|l| l.eq_ignore_ascii_case(lang))
+    }
+}
+
+impl<'t> Supported<'static> for &'t Translations {
+    type Names = iter::Copied<slice::Iter<'t, &'static str>>;
+
+    fn names(self) -> Self::Names {
+        self.all_langs().copied()
+    }
+
+    fn region_default(self, lang: &str) -> Option<&'static str> {
+        Translations::region_default(self, lang)
+    }
+
+    fn alias(self, lang: &str) -> Option<&'static str> {
+        Translations::alias(self, lang)
+    }
+
+    fn fallback(self, lang: &str) -> Option<&'static str> {
+        // `lang` itself, so that the request is served the rest of its chain too
+        self.chained(lang)
+    }
+
+    fn find(self, lang: &str) -> Option<&'static str> {
+        Translations::find(self, lang)
+    }
+}
+
+impl<'s, 'a> Supported<'a> for &'s [&'a str] {
+    type Names = iter::Copied<slice::Iter<'s, &'a str>>;
+
+    fn names(self) -> Self::Names {
+        self.iter().copied()
+    }
+
+    fn region_default(self, _: &str) -> Option<&'a str> {
+        None
+    }
+
+    fn alias(self, _: &str) -> Option<&'a str> {
+        None
+    }
+
+    fn fallback(self, _: &str) -> Option<&'a str> {
+        None
+    }
+}
+
+/// What we know about a request to choose its language.
+#[derive(Default)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct RequestInfo<'a> {
+    /// The `Accept-Language` header.
+    pub accept_language: Option<&'a str>,
+    /// The country of the client, as detected by an edge CDN.
+    pub geo_country: Option<&'a str>,
+    /// The language stored in the cookie configured in `I18nConfig::cookie`.
+    pub cookie: Option<String>,
+    /// The value of the query parameter configured in `I18nConfig::query`.
+    pub query: Option<&'a str>,
+    /// The path of the request, when `I18nConfig::path_prefix` is enabled.
+    pub path: Option<&'a str>,
+    /// The language in the subdomain of the request, as found by `subdomain`.
+    pub subdomain: Option<&'a str>,
+    /// Reads any header of the request, for custom resolvers.
+    pub header: Option<&'a dyn Fn(&str) -> Option<&'a str>>,
+    /// Reads any cookie of the request, for custom resolvers.
+    pub cookies: Option<&'a dyn Fn(&str) -> Option<String>>,
+    /// The IP address of the client, for custom resolvers, if the framework gives it.
+    pub client_ip: Option<IpAddr>,
+    /// The language found by the resolver of `Translations::with_user_resolver`.
+    pub user: Option<String>,
+}
+
+impl<'a> RequestInfo<'a> {
+    /// Gathers what the configuration needs to know about a request, given a way to read its
+    /// headers and cookies, its query string and its path.
+    #[cfg(any(
+        test,
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub fn new(
+        config: Option<&I18nConfig>,
+        header: &'a dyn Fn(&str) -> Option<&'a str>,
+        cookies: &'a dyn Fn(&str) -> Option<String>,
+        query: Option<&'a str>,
+        path: &'a str,
+    ) -> RequestInfo<'a> {
+        RequestInfo {
+            accept_language: header(ACCEPT_LANG),
+            geo_country: config
+                .and_then(|c| c.geo.as_ref())
+                .and_then(|geo| header(geo.header())),
+            cookie: config.and_then(|c| {
+                let name = c.cookie.as_ref()?;
+                c.cookie_lang(name, cookies(name)?)
+            }),
+            query: config
+                .and_then(|c| c.query.as_ref())
+                .and_then(|name| query_value(query?, name)),
+            path: config.filter(|c| c.path_prefix).map(|_| path),
+            subdomain: config
+                .and_then(|c| c.subdomain_suffix.as_ref())
+                .and_then(|suffix| subdomain(header("Host")?, suffix)),
+            header: Some(header),
+            cookies: Some(cookies),
+            client_ip: None,
+            user: None,
+        }
+    }
+
+    /// Sets the language found by the resolver of `Translations::with_user_resolver`.
+    #[cfg(any(
+        feature = "actix-web",
+        feature = "actix-web-4",
+        feature = "axum",
+        feature = "poem",
+        feature = "rocket",
+        feature = "tide",
+        feature = "tower",
+        feature = "warp"
+    ))]
+    pub fn with_user(mut self, user: Option<String>) -> RequestInfo<'a> {
+        self.user = user;
+        self
+    }
+
+    /// Sets the language stored in the cookie of `I18nConfig::cookie`, for frameworks that read
+    /// it another way (from a private cookie, with Rocket).
+    #[cfg(feature = "rocket")]
+    pub fn with_cookie(mut self, cookie: Option<String>) -> RequestInfo<'a> {
+        self.cookie = cookie;
+        self
+    }
+
+    /// Sets the IP address of the client.
+    #[cfg(any(feature = "actix-web", feature = "actix-web-4", feature = "rocket"))]
+    pub fn with_client_ip(mut self, client_ip: Option<IpAddr>) -> RequestInfo<'a> {
+        self.client_ip = client_ip;
+        self
+    }
+}
+
+/// Chooses the language to use for a request, walking the chain of resolvers of the
+/// configuration (see `Resolver`) until one of them finds a supported language.
+///
+/// A requested language that has no catalog but a fallback chain is chosen as is, so that
+/// `Translations::for_request` serves it the first supported language of its chain, with the rest
+/// of the chain as fallbacks (see `Translations::served`).
+///
+/// By default, a language explicitly chosen by the user (in the query string, the path, the
+/// subdomain, or a cookie) takes precedence. `auto`, or an empty value, means that the user has
+/// no preference.
+///
+/// Languages of `Accept-Language` are tried from the highest to the lowest quality (`q=`), and in
+/// the order of the header for equal qualities. Languages with a quality of `0` are never chosen.
+///
+/// A wildcard (`*`) in `Accept-Language` means "any other language": it stops the
+/// `Accept-Language` step, so that the next resolvers (`Geo`, for instance) can choose. If none of
+/// them does, it resolves to the default language, or to the first supported language that the
+/// header doesn't name otherwise.
+pub(crate) fn negotiate(
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    request: &RequestInfo,
+) -> &'static str {
+    let log = config.is_some_and(|c| c.log_candidates);
+    let chain = config.map_or(DEFAULT_CHAIN, |c| &c.chain[..]);
+    match chain
+        .iter()
+        .find_map(|resolver| resolve(resolver, langs, config, request, log))
+    {
+        Some(lang) => lang,
+        None => {
+            let lang = request
+                .accept_language
+                .filter(|_| chain.iter().any(|r| matches!(r, Resolver::AcceptLanguage)))
+                .filter(|header| preferences(header).iter().any(|(lang, _)| *lang == "*"))
+                .and_then(|header| wildcard(langs, config, header))
+                .unwrap_or_else(|| default_lang(langs, config));
+            if log {
+                debug!("No requested language is supported, chose {}", lang);
+            }
+            lang
+        }
+    }
+}
+
+/// The supported language found by a step of the negotiation, if any.
+fn resolve(
+    resolver: &Resolver,
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    request: &RequestInfo,
+    log: bool,
+) -> Option<&'static str> {
+    let (source, requested) = match resolver {
+        Resolver::AcceptLanguage => return accept_language(langs, config, request, log),
+        Resolver::Geo => {
+            // Guess the language from the location of the client
+            let country = request.geo_country?;
+            let lang = supported(langs, config?.geo.as_ref()?.lang(country)?);
+            if log {
+                if let Some(lang) = lang {
+                    debug!("Chose {} from the country of the client ({})", lang, country);
+                }
+            }
+            return lang;
+        }
+        Resolver::Query => ("query parameter", request.query.map(Cow::from)),
+        Resolver::Path => ("path", request.path.and_then(first_segment).map(Cow::from)),
+        Resolver::Subdomain => ("subdomain", request.subdomain.map(Cow::from)),
+        Resolver::Cookie => ("cookie", request.cookie.as_ref().map(|c| Cow::from(c.as_str()))),
+        Resolver::User => ("user profile", request.user.as_ref().map(|u| Cow::from(u.as_str()))),
+        Resolver::Custom(custom) => (
+            "custom resolver",
+            custom.resolve(&LocaleRequest(request)).map(Cow::from),
+        ),
+    };
+
+    let requested = requested?;
+    let requested = requested.trim();
+    if is_auto(requested) {
+        return None;
+    }
+    let explicit = match resolver {
+        Resolver::Query | Resolver::Cookie => langs.find_explicit(requested),
+        _ => None,
+    };
+    let lang = explicit.or_else(|| matching(langs, config, requested));
+    if log {
+        match lang {
+            Some(lang) => debug!("Chose {} from the {} ({})", lang, source, requested),
+            None => debug!("The language of the {} ({}) is not supported", source, requested),
+        }
+    }
+    lang
+}
+
+/// The most preferred supported language of `Accept-Language`, if any.
+///
+/// The languages that are less preferred than a wildcard are not tried: the wildcard accepts any
+/// language, which is only chosen once the other resolvers had a chance (see `negotiate`).
+fn accept_language(
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    request: &RequestInfo,
+    log: bool,
+) -> Option<&'static str> {
+    let header = request.accept_language?;
+    if !log {
+        // Get the most preferred locale we support
+        for (lang, _) in preferences(header) {
+            if lang == "*" {
+                return None;
+            }
+            if let Some(lang) = matching(langs, config, lang) {
+                return Some(lang);
+            }
+        }
+        return None;
+    }
+
+    for (lang, quality) in header.split(',').filter_map(parse_preference) {
+        if !lang.is_empty() && quality <= 0.0 {
+            debug!("Candidate language {}: not acceptable (q=0)", lang);
+        }
+    }
+
+    let mut found = None;
+    let mut any = false;
+    for (lang, quality) in preferences(header) {
+        let disposition = if found.is_some() {
+            "ignored, a preferred language was already found".to_owned()
+        } else if any {
+            "ignored, the wildcard is preferred".to_owned()
+        } else if lang == "*" {
+            any = true;
+            "any language, chosen after the other resolvers".to_owned()
+        } else {
+            match matching(langs, config, lang) {
+                Some(matched) => {
+                    found = Some(matched);
+                    format!("matched {}", matched)
+                }
+                None if config.is_some_and(|c| c.is_region_critical(lang)) => {
+                    "not supported (region critical, so its base language was not tried)".to_owned()
+                }
+                None => "not supported".to_owned(),
+            }
+        };
+        debug!("Candidate language {} (q={}): {}", lang, quality, disposition);
+    }
+    if let Some(lang) = found {
+        debug!("Chose {} from Accept-Language", lang);
+    }
+    found
+}
+
+/// The value of a parameter in a query string (`lang=fr&page=2`), if it is present.
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+pub(crate) fn query_value<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| {
+        let mut kv = pair.splitn(2, '=');
+        if kv.next()? == name {
+            Some(kv.next().unwrap_or(""))
+        } else {
+            None
+        }
+    })
+}
+
+/// The first segment of a path (`fr` for `/fr/posts/1`), if it is not empty.
+fn first_segment(path: &str) -> Option<&str> {
+    path.trim_start_matches('/')
+        .split('/')
+        .next()
+        .filter(|segment| !segment.is_empty())
+}
+
+/// The subdomain of a `Host` header (`fr` for `fr.example.com:8000`), if this host ends with
+/// `suffix` (`example.com`).
+#[cfg(any(
+    test,
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+fn subdomain<'a>(host: &'a str, suffix: &str) -> Option<&'a str> {
+    let host = host.split(':').next().unwrap_or(host);
+    let suffix = suffix.trim_start_matches('.');
+    let dot = host.len().checked_sub(suffix.len() + 1)?;
+    if host.is_char_boundary(dot)
+        && host[dot..].starts_with('.')
+        && host[dot + 1..].eq_ignore_ascii_case(suffix)
+    {
+        Some(&host[..dot])
+    } else {
+        None
+    }
+}
+
+/// The value of a cookie in a `Cookie` header (`lang=fr; session=…`), for frameworks that don't
+/// parse them, and for `UserRequest`.
+#[cfg(any(
+    feature = "actix-web",
+    feature = "actix-web-4",
+    feature = "axum",
+    feature = "poem",
+    feature = "rocket",
+    feature = "tide",
+    feature = "tower",
+    feature = "warp"
+))]
+pub(crate) fn cookie_value<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header.split(';').find_map(|pair| {
+        let mut kv = pair.splitn(2, '=');
+        if kv.next()?.trim() == name {
+            Some(kv.next()?.trim().trim_matches('"'))
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether the values of the `Vary` headers of a response already include `Accept-Language`.
+#[cfg(any(feature = "actix-web", feature = "actix-web-4", feature = "rocket"))]
+pub(crate) fn varies_on_accept_language<'a>(mut values: impl Iterator<Item = &'a str>) -> bool {
+    values.any(|value| {
+        value
+            .split(',')
+            .any(|name| name.trim() == "*" || name.trim().eq_ignore_ascii_case(ACCEPT_LANG))
+    })
+}
+
+/// Whether a language chosen by the user actually means "no preference".
+fn is_auto(lang: &str) -> bool {
+    lang.is_empty() || lang.eq_ignore_ascii_case("auto")
+}
+
+/// The language to use when nothing else matched: the one configured in `I18nConfig`, or the
+/// first supported one (or `en` if there is none).
+pub(crate) fn default_lang(langs: &Translations, config: Option<&I18nConfig>) -> &'static str {
+    config
+        .and_then(|c| c.default_lang.as_ref())
+        .and_then(|lang| supported(langs, lang))
+        .or_else(|| langs.all_langs().copied().find(|l| langs.find(l).is_some()))
+        .unwrap_or("en")
+}
+
+/// The supported languages matching an `Accept-Language` header, with the quality of the
+/// requested language they match, from the most to the least preferred.
+pub(crate) fn ranked(
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    header: &str,
+) -> Vec<(&'static str, f32)> {
+    let mut ranked: Vec<(&'static str, f32)> = Vec::new();
+    for (lang, quality) in preferences(header) {
+        if lang == "*" {
+            continue;
+        }
+        if let Some(lang) = matching(langs, config, lang).and_then(|l| langs.served(l)) {
+            if !ranked.iter().any(|l| l.0 == lang) {
+                ranked.push((lang, quality));
+            }
+        }
+    }
+    ranked
+}
+
+/// The languages listed in an `Accept-Language` header, with their quality, from the most to the
+/// least preferred.
+///
+/// Languages with a quality of `0` are not acceptable, and are left out.
+pub(crate) fn preferences(header: &str) -> Vec<(&str, f32)> {
+    let mut preferences: Vec<_> = header
+        .split(',')
+        .filter_map(parse_preference)
+        .filter(|(lang, quality)| !lang.is_empty() && *quality > 0.0)
+        .collect();
+    // The sort is stable, so languages with the same quality keep the order of the header
+    preferences.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    preferences
+}
+
+/// Parses an item of `Accept-Language` (`fr-CH;q=0.9`), defaulting to a quality of `1`.
+fn parse_preference(item: &str) -> Option<(&str, f32)> {
+    let mut params = item.split(';');
+    let lang = params.next()?.trim();
+    let quality = params
+        .find_map(|param| {
+            let mut kv = param.splitn(2, '=');
+            if kv.next()?.trim() == "q" {
+                kv.next()?.trim().parse().ok()
+            } else {
+                None
+            }
+        })
+        .unwrap_or(1.0);
+    Some((lang, quality))
+}
+
+/// The supported language to use for a language requested by the client, if any.
+///
+/// An exact match is always preferred. Otherwise, the language falls back to another one with the
+/// same primary language: `pt-PT` can be served `pt` or `pt-BR`, and `pt` can be served `pt-BR`.
+///
+/// If the language has a script (`zh-Hant-HK`, or `zh-HK` which implies it), a language in the
+/// same script (`zh-Hant`, `zh-Hant-TW` or `zh-TW`) is tried first, so that Simplified and
+/// Traditional Chinese are not mixed up. A language in another script is only used as a last
+/// resort, when there is no catalog for the primary language itself.
+fn matching<'a>(
+    langs: impl Supported<'a>,
+    config: Option<&I18nConfig>,
+    lang: &str,
+) -> Option<&'a str> {
+    if let Some(lang) = supported(langs, lang) {
+        return Some(lang);
+    }
+    if config.is_some_and(|c| c.is_region_critical(lang)) {
+        return None;
+    }
+
+    // Get the locale, not the country code
+    let base = crate::langs::base(lang);
+    if let Some(script) = crate::langs::script(lang) {
+        let scripted = format!("{}-{}", base, script);
+        if let Some(lang) =
+            supported(langs, &scripted).or_else(|| scripted_variant(langs, base, script))
+        {
+            return Some(lang);
+        }
+    }
+    let base_catalog = || supported(langs, base);
+    let variant = || regional_variant(langs, base);
+    if config.map(|c| c.prefer_base_over_region).unwrap_or(true) {
+        base_catalog().or_else(variant)
+    } else {
+        variant().or_else(base_catalog)
+    }
+}
+
+/// The supported language to use for a language of an `Accept-Language` header, which may be a
+/// wildcard.
+fn accepted<'a>(
+    langs: impl Supported<'a>,
+    config: Option<&I18nConfig>,
+    header: &str,
+    lang: &str,
+) -> Option<&'a str> {
+    if lang == "*" {
+        wildcard(langs, config, header)
+    } else {
+        matching(langs, config, lang)
+    }
+}
+
+/// The supported language matched by a wildcard in an `Accept-Language` header: the default
+/// language of the configuration, or the first supported language (as they are ordered by
+/// preference), unless the header names it.
+///
+/// As in RFC 9110, `*` only matches the languages that no other range of the header matches, so
+/// that `fr;q=0.2, *;q=0.5` prefers anything to French, and `en;q=0, *` never chooses English.
+fn wildcard<'a>(
+    langs: impl Supported<'a>,
+    config: Option<&I18nConfig>,
+    header: &str,
+) -> Option<&'a str> {
+    let named = header
+        .split(',')
+        .filter_map(parse_preference)
+        .map(|(range, _)| range)
+        .filter(|range| !range.is_empty() && *range != "*")
+        .collect::<Vec<_>>();
+    let is_named = |lang: &str| {
+        named.iter().any(|range| {
+            lang.eq_ignore_ascii_case(range)
+                || (lang.len() > range.len()
+                    && lang.is_char_boundary(range.len())
+                    && lang[..range.len()].eq_ignore_ascii_case(range)
+                    && lang[range.len()..].starts_with('-'))
+        })
+    };
+    let default = config
+        .and_then(|c| c.default_lang.as_ref())
+        .and_then(|lang| supported(langs, lang));
+    default
+        .into_iter()
+        .chain(langs.names())
+        .find(|lang| !is_named(lang) && langs.find(lang).is_some())
+}
+
+/// The first supported variant of a language written in `script` (`zh-TW` or `zh-Hant-HK` for
+/// `zh` and `Hant`), if any.
+fn scripted_variant<'a>(langs: impl Supported<'a>, base: &str, script: &str) -> Option<&'a str> {
+    langs.names().find(|l| {
+        crate::langs::base(l).eq_ignore_ascii_case(base)
+            && crate::langs::script(l).is_some_and(|s| s.eq_ignore_ascii_case(script))
+            && langs.find(l).is_some()
+    })
+}
+
+/// The first supported regional variant of a language (`pt-BR` for `pt`), if any.
+fn regional_variant<'a>(langs: impl Supported<'a>, base: &str) -> Option<&'a str> {
+    langs
+        .names()
+        .find(|l| {
+            l.len() > base.len()
+                && crate::langs::base(l).eq_ignore_ascii_case(base)
+                && langs.find(l).is_some()
+        })
+}
+
+fn supported<'a>(langs: impl Supported<'a>, lang: &str) -> Option<&'a str> {
+    langs
+        .find(lang)
+        .or_else(|| langs.find(langs.alias(lang)?))
+        .or_else(|| langs.find(langs.region_default(lang)?))
+        .or_else(|| langs.fallback(lang))
+}
+
+/// Chooses a language from the environment, like gettext does: `LANGUAGE` (a list of languages
+/// separated by colons), then `LC_ALL`, `LC_MESSAGES` and `LANG`.
+///
+/// `C` and `POSIX` mean that messages should not be translated: they resolve to the default
+/// language, and disable `LANGUAGE` as they would with gettext.
+pub(crate) fn negotiate_env(langs: &Translations) -> &'static str {
+    let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    let locale = var("LC_ALL")
+        .or_else(|| var("LC_MESSAGES"))
+        .or_else(|| var("LANG"));
+
+    let mut candidates = Vec::new();
+    if !locale.as_ref().is_some_and(|l| is_posix(l)) {
+        if let Some(list) = var("LANGUAGE") {
+            candidates.extend(list.split(':').filter(|l| !is_posix(l)).map(posix_to_tag));
+        }
+        candidates.extend(locale.as_ref().map(|l| posix_to_tag(l)));
+    }
+
+    let header = candidates.join(",");
+    let request = RequestInfo {
+        accept_language: Some(&header),
+        ..Default::default()
+    };
+    let lang = negotiate(langs, None, &request);
+    langs.served(lang).unwrap_or(lang)
+}
+
+fn is_posix(locale: &str) -> bool {
+    let name = locale.split(['.', '@']).next().unwrap_or(locale);
+    name == "C" || name == "POSIX"
+}
+
+/// Converts a POSIX locale (`pt_BR.UTF-8@variant`) to a language tag (`pt-BR`).
+fn posix_to_tag(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or(locale)
+        .replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing, GeoFallback};
+
+    /// Negotiates the language of a request with the given headers.
+    fn negotiate_with(
+        langs: &Translations,
+        config: Option<&I18nConfig>,
+        headers: &[(&str, &str)],
+    ) -> &'static str {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, value)| *value)
+        };
+        let cookies = |_: &str| None;
+        negotiate(langs, config, &RequestInfo::new(config, &header, &cookies, None, "/"))
+    }
+
+    #[test]
+    fn cookies_with_a_wrong_signature_are_ignored() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .cookie_key(&b"0123456789abcdef0123456789abcdef"[..])
+            .build();
+        let with_cookie = |value: String| {
+            let header = |_: &str| None;
+            let cookies = move |name: &str| Some(value.clone()).filter(|_| name == "lang");
+            let request = RequestInfo::new(Some(&config), &header, &cookies, None, "/");
+            negotiate(&langs, Some(&config), &request)
+        };
+        assert_eq!(with_cookie(config.cookie_value("fr")), "fr");
+        assert_eq!(with_cookie("de".to_owned()), "en");
+        assert_eq!(with_cookie(config.cookie_value("fr").replacen("fr", "de", 1)), "en");
+    }
+
+    #[test]
+    fn a_bare_wildcard_lets_geo_choose() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let config = I18nConfig::builder()
+            .geo(GeoFallback::new("CF-IPCountry").country("DE", "de"))
+            .build();
+        let headers = [("Accept-Language", "*"), ("CF-IPCountry", "DE")];
+        assert_eq!(negotiate_with(&langs, Some(&config), &headers), "de");
+        assert_eq!(negotiate_with(&langs, Some(&config), &[("Accept-Language", "*")]), "en");
+    }
+
+    #[test]
+    fn languages_less_preferred_than_the_wildcard_are_not_chosen() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let config = I18nConfig::builder()
+            .geo(GeoFallback::new("CF-IPCountry").country("DE", "de"))
+            .build();
+        let headers = [("Accept-Language", "fr;q=0.2, *;q=0.5"), ("CF-IPCountry", "DE")];
+        assert_eq!(negotiate_with(&langs, Some(&config), &headers), "de");
+        let headers = [("Accept-Language", "fr;q=0.2, *;q=0.5")];
+        assert_eq!(negotiate_with(&langs, Some(&config), &headers), "en");
+        assert_eq!(negotiate_with(&langs, None, &[("Accept-Language", "fr, *")]), "fr");
+    }
+
+    #[test]
+    fn the_wildcard_default_avoids_the_named_languages() {
+        let langs = testing::translations(&["en", "fr"]);
+        assert_eq!(negotiate_with(&langs, None, &[("Accept-Language", "en;q=0, *")]), "fr");
+        assert_eq!(negotiate_with(&langs, None, &[("Accept-Language", "xx")]), "en");
+        assert_eq!(negotiate_language("en;q=0, *", &["en", "fr"]), Some("fr"));
+        assert_eq!(negotiate_language("xx", &["en", "fr"]), None);
+    }
+
+    #[test]
+    fn auto_in_the_query_negotiates_from_the_headers() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let config = I18nConfig::builder().query("lang").build();
+        let with_query = |query: &str| {
+            let header = |name: &str| Some("de, fr;q=0.5").filter(|_| name == ACCEPT_LANG);
+            let cookies = |_: &str| None;
+            let request = RequestInfo::new(Some(&config), &header, &cookies, Some(query), "/");
+            negotiate(&langs, Some(&config), &request)
+        };
+        assert_eq!(with_query("lang=fr"), "fr");
+        assert_eq!(with_query("lang=auto"), "de");
+        assert_eq!(with_query("lang=AUTO"), "de");
+        assert_eq!(with_query("lang="), "de");
+    }
+
+    #[test]
+    fn auto_in_the_cookie_negotiates_from_the_headers() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let unsigned = I18nConfig::builder().cookie("lang").build();
+        let signed = I18nConfig::builder()
+            .cookie("lang")
+            .cookie_key(&b"0123456789abcdef0123456789abcdef"[..])
+            .build();
+        let with_cookie = |config: &I18nConfig, value: String| {
+            let header = |name: &str| Some("de").filter(|_| name == ACCEPT_LANG);
+            let cookies = move |name: &str| Some(value.clone()).filter(|_| name == "lang");
+            let request = RequestInfo::new(Some(config), &header, &cookies, None, "/");
+            negotiate(&langs, Some(config), &request)
+        };
+        assert_eq!(with_cookie(&unsigned, "fr".to_owned()), "fr");
+        assert_eq!(with_cookie(&unsigned, "auto".to_owned()), "de");
+        assert_eq!(with_cookie(&unsigned, String::new()), "de");
+        assert_eq!(with_cookie(&signed, signed.cookie_value("fr")), "fr");
+        assert_eq!(with_cookie(&signed, signed.cookie_value("auto")), "de");
+    }
+
+    #[test]
+    fn languages_are_tried_by_quality() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let accept = |header| negotiate_with(&langs, None, &[(ACCEPT_LANG, header)]);
+        assert_eq!(accept("fr;q=0.5, de;q=0.9"), "de");
+        assert_eq!(accept("fr, de"), "fr");
+        assert_eq!(accept("de;q=0.8, fr;q=0.8, en;q=0.1"), "de");
+        assert_eq!(accept("de;q=0, fr;q=0.1"), "fr");
+        assert_eq!(accept("fr;q=0, de;q=0"), "en");
+        assert_eq!(negotiate_language("fr;q=0.5, de;q=0.9", &["fr", "de"]), Some("de"));
+    }
+
+    #[test]
+    fn regional_variants_fall_back_to_each_other() {
+        let langs = testing::translations(&["en", "pt", "fr-CA", "fr-FR"]);
+        let accept = |header| negotiate_with(&langs, None, &[(ACCEPT_LANG, header)]);
+        assert_eq!(accept("pt-BR"), "pt");
+        assert_eq!(accept("pt_PT"), "pt");
+        assert_eq!(accept("fr"), "fr-CA");
+        assert_eq!(accept("fr-BE"), "fr-CA");
+        assert_eq!(accept("FR-fr"), "fr-FR");
+        assert_eq!(negotiate_language("pt-BR", &["en", "pt"]), Some("pt"));
+        assert_eq!(negotiate_language("pt", &["en", "pt-BR"]), Some("pt-BR"));
+    }
+
+    #[test]
+    fn the_default_language_is_configurable() {
+        let langs = testing::translations(&["es", "en", "fr"]);
+        let unsupported = [(ACCEPT_LANG, "ja")];
+        assert_eq!(negotiate_with(&langs, None, &unsupported), "es");
+        assert_eq!(negotiate_with(&langs, None, &[]), "es");
+
+        let config = I18nConfig::builder().default_lang("fr").build();
+        assert_eq!(negotiate_with(&langs, Some(&config), &unsupported), "fr");
+        assert_eq!(negotiate_with(&langs, Some(&config), &[(ACCEPT_LANG, "en")]), "en");
+
+        let config = I18nConfig::builder().default_lang("de").build();
+        assert_eq!(negotiate_with(&langs, Some(&config), &unsupported), "es");
+        assert_eq!(default_lang(&testing::translations(&[]), None), "en");
+    }
+
+    #[test]
+    fn the_language_can_be_read_from_the_path() {
+        let langs = testing::translations(&["en", "fr", "pt-BR"]);
+        let config = I18nConfig::builder().path_prefix(true).build();
+        let with_path = |config: &I18nConfig, path| {
+            let header = |name: &str| Some("en").filter(|_| name == ACCEPT_LANG);
+            let cookies = |_: &str| None;
+            let request = RequestInfo::new(Some(config), &header, &cookies, None, path);
+            negotiate(&langs, Some(config), &request)
+        };
+        assert_eq!(with_path(&config, "/fr/posts/1"), "fr");
+        assert_eq!(with_path(&config, "fr"), "fr");
+        assert_eq!(with_path(&config, "/pt/"), "pt-BR");
+        assert_eq!(with_path(&config, "/posts/1"), "en");
+        assert_eq!(with_path(&config, "/"), "en");
+        assert_eq!(with_path(&I18nConfig::default(), "/fr/posts/1"), "en");
+        assert_eq!(first_segment("//fr/posts"), Some("fr"));
+    }
+
+    #[test]
+    fn the_language_can_be_read_from_the_subdomain() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let config = I18nConfig::builder().subdomain_suffix(".example.com").build();
+        let with_host = |host| {
+            let headers = [("Host", host), (ACCEPT_LANG, "de")];
+            negotiate_with(&langs, Some(&config), &headers)
+        };
+        assert_eq!(with_host("fr.example.com"), "fr");
+        assert_eq!(with_host("fr.Example.COM:8000"), "fr");
+        assert_eq!(with_host("example.com"), "de");
+        assert_eq!(with_host("fr.notexample.com"), "de");
+        assert_eq!(with_host("www.example.com"), "de");
+        assert_eq!(subdomain("a.b.example.com", "example.com"), Some("a.b"));
+    }
+
+    #[test]
+    fn languages_can_be_negotiated_without_a_request() {
+        let supported = ["en", "fr", "pt-BR"];
+        assert_eq!(negotiate_language("fr-CH, fr;q=0.9, en;q=0.8", &supported), Some("fr"));
+        assert_eq!(negotiate_language("de, en;q=0.5", &supported), Some("en"));
+        assert_eq!(negotiate_language("pt", &supported), Some("pt-BR"));
+        assert_eq!(negotiate_language("de", &supported), None);
+        assert_eq!(negotiate_language("", &supported), None);
+
+        let langs = testing::translations(&supported);
+        let (lang, catalog) = negotiate_catalog("de, fr;q=0.5", &langs).unwrap();
+        assert_eq!(lang, "fr");
+        assert!(std::ptr::eq(catalog, langs.get("fr").unwrap()));
+        assert!(negotiate_catalog("de", &langs).is_none());
+    }
+
+    #[test]
+    fn languages_in_the_same_script_are_preferred() {
+        let supported = ["en", "zh-Hans", "zh-TW", "sr", "sr-Latn"];
+        assert_eq!(negotiate_language("zh-HK", &supported), Some("zh-TW"));
+        assert_eq!(negotiate_language("zh-Hant-HK", &supported), Some("zh-TW"));
+        assert_eq!(negotiate_language("zh-CN", &supported), Some("zh-Hans"));
+        assert_eq!(negotiate_language("zh-SG", &supported), Some("zh-Hans"));
+        assert_eq!(negotiate_language("zh", &supported), Some("zh-Hans"));
+        assert_eq!(negotiate_language("sr-Latn-RS", &supported), Some("sr-Latn"));
+        assert_eq!(negotiate_language("sr-Cyrl-RS", &supported), Some("sr"));
+        assert_eq!(negotiate_language("zh-HK", &["en", "zh-CN"]), Some("zh-CN"));
+    }
+
+    #[test]
+    fn aliases_are_served_the_catalog_they_stand_for() {
+        let langs = testing::translations(&["en", "he", "nb", "no-NO"])
+            .with_alias("iw", "he")
+            .with_alias("no", "nb");
+        let negotiated = |accept| negotiate_with(&langs, None, &[(ACCEPT_LANG, accept)]);
+        assert_eq!(negotiated("iw"), "he");
+        assert_eq!(negotiated("IW-IL"), "he");
+        assert_eq!(negotiated("no"), "nb");
+        assert_eq!(negotiated("no-NO"), "no-NO");
+        assert_eq!(negotiated("in"), "en");
+        assert_eq!(negotiate_catalog("iw", &langs).map(|l| l.0), Some("he"));
+    }
+}
@@ -0,0 +1,307 @@
+//! Parsing and evaluation of the `Plural-Forms` header of gettext catalogs.
+//!
+//! `gettext` doesn't expose the plural rules of a catalog, so they are parsed again here when we
+//! need to know how many forms a language has, or which form a given number uses.
+
+use crate::{header_field, Catalog};
+
+/// The plural rules declared by a catalog.
+pub(crate) struct PluralForms {
+    /// The number of plural forms of the language.
+    pub nplurals: usize,
+    rule: Expr,
+}
+
+impl Default for PluralForms {
+    /// The English rules (`nplurals=2; plural=(n != 1);`), which gettext also uses when a catalog
+    /// doesn't declare anything.
+    fn default() -> PluralForms {
+        PluralForms {
+            nplurals: 2,
+            rule: Expr::Binary(Op::Ne, Box::new(Expr::N), Box::new(Expr::Int(1))),
+        }
+    }
+}
+
+impl PluralForms {
+    /// Reads the plural rules of a catalog, defaulting to the English ones if its header is
+    /// missing or can't be parsed.
+    pub fn of(catalog: &Catalog) -> PluralForms {
+        header_field(catalog, "Plural-Forms")
+            .and_then(PluralForms::parse)
+            .unwrap_or_default()
+    }
+
+    /// Parses the value of a `Plural-Forms` header.
+    pub fn parse(header: &str) -> Option<PluralForms> {
+        let mut nplurals = None;
+        let mut rule = None;
+        for part in header.split(';') {
+            let mut kv = part.splitn(2, '=');
+            match kv.next().map(str::trim) {
+                Some("nplurals") => nplurals = kv.next()?.trim().parse().ok(),
+                Some("plural") => rule = Parser::new(kv.next()?)?.parse(),
+                _ => {}
+            }
+        }
+
+        match (nplurals.filter(|n| *n > 0), rule) {
+            (Some(nplurals), Some(rule)) => Some(PluralForms { nplurals, rule }),
+            _ => None,
+        }
+    }
+
+    /// The index of the plural form to use for `n`.
+    pub fn index(&self, n: u64) -> usize {
+        (self.rule.eval(n) as usize).min(self.nplurals - 1)
+    }
+
+    /// The smallest number using the plural form at `index`, if any.
+    pub fn example(&self, index: usize) -> Option<u64> {
+        (0..1000).find(|n| self.index(*n) == index)
+    }
+}
+
+/// A C expression, as used in `plural=`.
+enum Expr {
+    N,
+    Int(u64),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Binary operators, from the lowest to the highest precedence.
+const LEVELS: &[&[(&str, Op)]] = &[
+    &[("||", Op::Or)],
+    &[("&&", Op::And)],
+    &[("==", Op::Eq), ("!=", Op::Ne)],
+    &[("<=", Op::Le), (">=", Op::Ge), ("<", Op::Lt), (">", Op::Gt)],
+    &[("+", Op::Add), ("-", Op::Sub)],
+    &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+];
+
+impl Expr {
+    fn eval(&self, n: u64) -> u64 {
+        match self {
+            Expr::N => n,
+            Expr::Int(i) => *i,
+            Expr::Not(e) => (e.eval(n) == 0) as u64,
+            Expr::Ternary(cond, then, otherwise) => {
+                if cond.eval(n) != 0 {
+                    then.eval(n)
+                } else {
+                    otherwise.eval(n)
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (l, r) = (lhs.eval(n), rhs.eval(n));
+                match op {
+                    Op::Or => (l != 0 || r != 0) as u64,
+                    Op::And => (l != 0 && r != 0) as u64,
+                    Op::Eq => (l == r) as u64,
+                    Op::Ne => (l != r) as u64,
+                    Op::Lt => (l < r) as u64,
+                    Op::Le => (l <= r) as u64,
+                    Op::Gt => (l > r) as u64,
+                    Op::Ge => (l >= r) as u64,
+                    Op::Add => l.wrapping_add(r),
+                    Op::Sub => l.wrapping_sub(r),
+                    Op::Mul => l.wrapping_mul(r),
+                    Op::Div => l.checked_div(r).unwrap_or(0),
+                    Op::Rem => l.checked_rem(r).unwrap_or(0),
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Option<Parser<'a>> {
+        let mut tokens = Vec::new();
+        let mut rest = src.trim_start();
+        while !rest.is_empty() {
+            let len = if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len())
+            } else if ["||", "&&", "==", "!=", "<=", ">="]
+                .iter()
+                .any(|op| rest.starts_with(op))
+            {
+                2
+            } else if rest.starts_with(|c: char| "n!<>+-*/%?:()".contains(c)) {
+                1
+            } else {
+                return None;
+            };
+            tokens.push(&rest[..len]);
+            rest = rest[len..].trim_start();
+        }
+        Some(Parser { tokens, pos: 0 })
+    }
+
+    fn parse(mut self) -> Option<Expr> {
+        let expr = self.ternary()?;
+        if self.pos == self.tokens.len() {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ternary(&mut self) -> Option<Expr> {
+        let cond = self.binary(0)?;
+        if self.eat("?") {
+            let then = self.ternary()?;
+            if !self.eat(":") {
+                return None;
+            }
+            let otherwise = self.ternary()?;
+            Some(Expr::Ternary(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ))
+        } else {
+            Some(cond)
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Option<Expr> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(op) = self.operator(level) {
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Some(lhs)
+    }
+
+    fn operator(&self, level: usize) -> Option<Op> {
+        let token = self.peek()?;
+        LEVELS[level]
+            .iter()
+            .find(|(symbol, _)| *symbol == token)
+            .map(|&(_, op)| op)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.eat("!") {
+            return Some(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.ternary()?;
+            return if self.eat(")") { Some(expr) } else { None };
+        }
+
+        let token = self.peek()?;
+        self.pos += 1;
+        if token == "n" {
+            Some(Expr::N)
+        } else {
+            token.parse().ok().map(Expr::Int)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogBuilder, I18n};
+
+    const POLISH: &str = "nplurals=3; \
+        plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+
+    /// The `I18n` of a request in `lang`, with a catalog declaring `plural_forms`.
+    fn i18n(lang: &'static str, plural_forms: &str, rule: fn(u64) -> usize) -> I18n {
+        let header = format!("Plural-Forms: {}\n", plural_forms);
+        let catalog = CatalogBuilder::new()
+            .msg("", &header)
+            .plural("One file", "{0} files", &["{0} plik", "{0} pliki", "{0} plików"])
+            .plural_rule(rule)
+            .build();
+        I18n::new_for_tests(lang, catalog)
+    }
+
+    fn polish(n: u64) -> usize {
+        PluralForms::parse(POLISH).unwrap().index(n)
+    }
+
+    #[test]
+    fn plural_rules_are_evaluated() {
+        let forms = PluralForms::parse(POLISH).unwrap();
+        assert_eq!(forms.nplurals, 3);
+        let indices = [1, 2, 5, 12, 22, 25, 101, 104].map(|n| forms.index(n));
+        assert_eq!(indices, [0, 1, 2, 2, 1, 2, 2, 1]);
+        assert_eq!([0, 1, 2].map(|i| forms.example(i)), [Some(1), Some(2), Some(0)]);
+
+        let french = PluralForms::parse("nplurals=2; plural=(n > 1);").unwrap();
+        assert_eq!([0, 1, 2].map(|n| french.index(n)), [0, 0, 1]);
+    }
+
+    #[test]
+    fn every_plural_form_is_rendered() {
+        let forms = i18n("pl", POLISH, polish).all_plural_forms("One file", "{0} files");
+        assert_eq!(forms, ["{0} plik", "{0} pliki", "{0} plików"]);
+
+        let english = I18n::new_for_tests("en", CatalogBuilder::new().build());
+        assert_eq!(english.all_plural_forms("One file", "{0} files"), ["One file", "{0} files"]);
+    }
+
+    #[test]
+    fn nplurals_is_read_from_the_catalog() {
+        let english = I18n::new_for_tests("en", CatalogBuilder::new().build());
+        assert_eq!(english.nplurals(), 2);
+        assert_eq!(i18n("ja", "nplurals=1; plural=0;", |_| 0).nplurals(), 1);
+        assert_eq!(i18n("pl", POLISH, polish).nplurals(), 3);
+    }
+
+    #[test]
+    fn malformed_plural_rules_are_rejected() {
+        assert!(PluralForms::parse("nplurals=2;").is_none());
+        assert!(PluralForms::parse("nplurals=0; plural=0;").is_none());
+        assert!(PluralForms::parse("nplurals=2; plural=(n != 1;").is_none());
+        assert!(PluralForms::parse("nplurals=2; plural=n ! 1;").is_none());
+        assert!(PluralForms::parse("nplurals=2; plural=x;").is_none());
+        // Out of range forms use the last one
+        assert_eq!(PluralForms::parse("nplurals=2; plural=n;").unwrap().index(7), 1);
+    }
+}
@@ -0,0 +1,225 @@
+//! A minimal reader for `.po` files, to use them without compiling them with `msgfmt` first.
+//!
+//! `gettext` can only parse `.mo` files, so `.po` files are compiled to `.mo` in memory, like
+//! `msgfmt` would: fuzzy and untranslated messages are left out, as well as obsolete ones.
+
+use crate::mo;
+
+/// A syntax error in a `.po` file, on a given line (starting from `1`).
+#[derive(Debug)]
+pub(crate) struct SyntaxError(pub usize);
+
+/// A message of a `.po` file.
+#[derive(Default)]
+struct Message {
+    context: Option<String>,
+    id: String,
+    plural: Option<String>,
+    translations: Vec<String>,
+    fuzzy: bool,
+}
+
+/// The field of a message that the current line is part of, for strings spanning several lines.
+#[derive(PartialEq)]
+enum Field {
+    Context,
+    Id,
+    Plural,
+    Translation(usize),
+}
+
+/// Compiles the content of a `.po` file to the content of the corresponding `.mo` file.
+pub(crate) fn to_mo(source: &str) -> Result<Vec<u8>, SyntaxError> {
+    let mut messages = Vec::new();
+    let mut current: Option<Message> = None;
+    let mut field = None;
+    let mut fuzzy = false;
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let error = || SyntaxError(number + 1);
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            // Flags come before the message they apply to
+            if line.starts_with("#,") && line[2..].split(',').any(|flag| flag.trim() == "fuzzy") {
+                fuzzy = true;
+            }
+            continue;
+        }
+
+        if line.starts_with('"') {
+            let value = unquote(line).ok_or_else(error)?;
+            let message = current.as_mut().ok_or_else(error)?;
+            match field.as_ref().ok_or_else(error)? {
+                Field::Context => message
+                    .context
+                    .get_or_insert_with(String::new)
+                    .push_str(&value),
+                Field::Id => message.id.push_str(&value),
+                Field::Plural => message
+                    .plural
+                    .get_or_insert_with(String::new)
+                    .push_str(&value),
+                Field::Translation(i) => message.translations[*i].push_str(&value),
+            }
+            continue;
+        }
+
+        let mut parts = line.splitn(2, char::is_whitespace);
+        let keyword = parts.next().unwrap_or_default();
+        let value = unquote(parts.next().ok_or_else(error)?.trim()).ok_or_else(error)?;
+        if keyword == "msgctxt" || (keyword == "msgid" && field != Some(Field::Context)) {
+            messages.extend(current.take());
+            current = Some(Message {
+                fuzzy,
+                ..Message::default()
+            });
+            fuzzy = false;
+        }
+        let message = current.as_mut().ok_or_else(error)?;
+        field = Some(match keyword {
+            "msgctxt" => {
+                message.context = Some(value);
+                Field::Context
+            }
+            "msgid" => {
+                message.id = value;
+                Field::Id
+            }
+            "msgid_plural" => {
+                message.plural = Some(value);
+                Field::Plural
+            }
+            "msgstr" => {
+                message.translations = vec![value];
+                Field::Translation(0)
+            }
+            _ if keyword.starts_with("msgstr[") && keyword.ends_with(']') => {
+                let index = keyword["msgstr[".len()..keyword.len() - 1]
+                    .parse::<usize>()
+                    .map_err(|_| error())?;
+                if message.translations.len() <= index {
+                    message.translations.resize(index + 1, String::new());
+                }
+                message.translations[index] = value;
+                Field::Translation(index)
+            }
+            _ => return Err(error()),
+        });
+    }
+    messages.extend(current);
+
+    // Like msgfmt, only keep translated messages, and the header even if it is fuzzy
+    let mut entries = messages
+        .into_iter()
+        .filter(|m| !m.translations.iter().all(String::is_empty))
+        .filter(|m| !m.fuzzy || (m.id.is_empty() && m.context.is_none()))
+        .map(|m| {
+            let mut original = match m.context {
+                Some(ctx) => format!("{}\u{4}{}", ctx, m.id),
+                None => m.id,
+            };
+            if let Some(plural) = m.plural {
+                original.push('\0');
+                original.push_str(&plural);
+            }
+            (original, m.translations.join("\0"))
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(mo::write(&entries))
+}
+
+/// The value of a quoted string of a `.po` file, with its escape sequences replaced.
+fn unquote(quoted: &str) -> Option<String> {
+    if quoted.len() < 2 || !quoted.starts_with('"') || !quoted.ends_with('"') {
+        return None;
+    }
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted[1..quoted.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        value.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            c @ '\\' | c @ '"' | c @ '\'' | c @ '?' => c,
+            _ => return None,
+        });
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gettext::Catalog;
+
+    const PO: &str = r#"
+# A French translation
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+"Plural-Forms: nplurals=2; plural=(n > 1);\n"
+
+msgid "Hello"
+msgstr "Bonjour"
+
+#: src/main.rs:12
+msgid ""
+"A message on "
+"two lines"
+msgstr "Un message sur\n"
+"deux lignes"
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Ouvrir"
+
+msgid "One file"
+msgid_plural "{0} files"
+msgstr[0] "Un fichier"
+msgstr[1] "{0} fichiers"
+
+#, fuzzy, c-format
+msgid "Goodbye"
+msgstr "Au revoir"
+
+msgid "Untranslated"
+msgstr ""
+
+msgid "\"Quoted\""
+msgstr "« Cité »"
+"#;
+
+    #[test]
+    fn po_files_are_compiled_like_msgfmt_does() {
+        let catalog = Catalog::parse(&to_mo(PO).unwrap()[..]).unwrap();
+        assert_eq!(catalog.gettext("Hello"), "Bonjour");
+        assert_eq!(catalog.gettext("A message on two lines"), "Un message sur\ndeux lignes");
+        assert_eq!(catalog.pgettext("menu", "Open"), "Ouvrir");
+        assert_eq!(catalog.gettext("Open"), "Open");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 0), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "{0} files", 2), "{0} fichiers");
+        assert_eq!(catalog.gettext("Goodbye"), "Goodbye");
+        assert_eq!(catalog.gettext("Untranslated"), "Untranslated");
+        assert_eq!(catalog.gettext("\"Quoted\""), "« Cité »");
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        assert_eq!(to_mo("msgid \"Hello\"\nmsgstr Bonjour").unwrap_err().0, 2);
+        assert_eq!(to_mo("\"Hello\"").unwrap_err().0, 1);
+        assert_eq!(to_mo("msgid \"Hello\"\nmsgstr \"\\x\"").unwrap_err().0, 2);
+        assert_eq!(to_mo("msgid \"Hi\"\nmsgstr \"Salut\"\nmsgfoo \"\"").unwrap_err().0, 3);
+    }
+}
@@ -0,0 +1,159 @@
+//! Pseudo-translations, to check that every message goes through the catalog, and that layouts
+//! survive longer texts.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The pseudo-translations of the messages that were looked up.
+///
+/// Lookups return borrowed strings, so pseudo-translations are leaked and reused for every
+/// request: this is only meant for development, with a bounded number of messages.
+#[derive(Clone, Default)]
+pub(crate) struct Pseudo(Arc<RwLock<HashMap<String, &'static str>>>);
+
+impl Pseudo {
+    /// The pseudo-translation of a message.
+    pub fn translate(&self, msg_id: &str) -> &'static str {
+        if let Some(translation) = self.0.read().ok().and_then(|m| m.get(msg_id).copied()) {
+            return translation;
+        }
+        let translation: &'static str = Box::leak(pseudo_translate(msg_id).into_boxed_str());
+        if let Ok(mut translations) = self.0.write() {
+            translations.insert(msg_id.to_owned(), translation);
+        }
+        translation
+    }
+}
+
+/// Accents the letters of a message (`Hello world` becomes `[!!! Ĥéļļö ŵöŕļð !!!]`). The markers
+/// make it longer, like many translations are, and show where it is truncated.
+///
+/// Placeholders (`{0}`, `{name}`, `%s`) and HTML tags are left as they are, so that they still
+/// work.
+fn pseudo_translate(msg_id: &str) -> String {
+    let mut translated = String::with_capacity(msg_id.len() * 2 + 10);
+    translated.push_str("[!!! ");
+    let mut chars = msg_id.chars().peekable();
+    while let Some(c) = chars.next() {
+        let end = match c {
+            '{' => '}',
+            '<' => '>',
+            '%' => {
+                translated.push(c);
+                if chars.peek().is_some_and(char::is_ascii_alphabetic) {
+                    translated.extend(chars.next());
+                }
+                continue;
+            }
+            c => {
+                translated.push(accented(c));
+                continue;
+            }
+        };
+        translated.push(c);
+        for c in &mut chars {
+            translated.push(c);
+            if c == end {
+                break;
+            }
+        }
+    }
+    translated.push_str(" !!!]");
+    translated
+}
+
+fn accented(c: char) -> char {
+    match c {
+        'A' => 'Å',
+        'B' => 'Ɓ',
+        'C' => 'Ç',
+        'D' => 'Đ',
+        'E' => 'É',
+        'F' => 'Ƒ',
+        'G' => 'Ĝ',
+        'H' => 'Ĥ',
+        'I' => 'Î',
+        'J' => 'Ĵ',
+        'K' => 'Ķ',
+        'L' => 'Ļ',
+        'M' => 'Ṁ',
+        'N' => 'Ñ',
+        'O' => 'Ö',
+        'P' => 'Þ',
+        'Q' => 'Ǫ',
+        'R' => 'Ŕ',
+        'S' => 'Š',
+        'T' => 'Ŧ',
+        'U' => 'Û',
+        'V' => 'Ṽ',
+        'W' => 'Ŵ',
+        'X' => 'Ẋ',
+        'Y' => 'Ý',
+        'Z' => 'Ž',
+        'a' => 'å',
+        'b' => 'ƀ',
+        'c' => 'ç',
+        'd' => 'ð',
+        'e' => 'é',
+        'f' => 'ƒ',
+        'g' => 'ĝ',
+        'h' => 'ĥ',
+        'i' => 'î',
+        'j' => 'ĵ',
+        'k' => 'ķ',
+        'l' => 'ļ',
+        'm' => 'ɱ',
+        'n' => 'ñ',
+        'o' => 'ö',
+        'p' => 'þ',
+        'q' => 'ǫ',
+        'r' => 'ŕ',
+        's' => 'š',
+        't' => 'ţ',
+        'u' => 'û',
+        'v' => 'ṽ',
+        'w' => 'ŵ',
+        'x' => 'ẋ',
+        'y' => 'ý',
+        'z' => 'ž',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        negotiation::{negotiate, RequestInfo},
+        testing, I18nConfig,
+    };
+
+    #[test]
+    fn placeholders_and_tags_are_kept() {
+        assert_eq!(pseudo_translate("Hello {0}"), "[!!! Ĥéļļö {0} !!!]");
+        assert_eq!(pseudo_translate("<b>Hi</b> %s"), "[!!! <b>Ĥî</b> %s !!!]");
+    }
+
+    #[test]
+    fn the_pseudo_locale_is_only_chosen_explicitly() {
+        let langs = testing::translations(&["en", "fr"]).with_pseudo_locale("en-XA");
+        let config = I18nConfig::builder().query("lang").build();
+        let request = |accept_language, query| RequestInfo {
+            accept_language: Some(accept_language),
+            query,
+            ..Default::default()
+        };
+
+        let lang = negotiate(&langs, Some(&config), &request("fr", Some("en-XA")));
+        assert_eq!(lang, "en-XA");
+        let i18n = langs.for_request(lang).unwrap();
+        assert_eq!(i18n.catalog.gettext("Hello"), "[!!! Ĥéļļö !!!]");
+        assert_eq!(negotiate(&langs, Some(&config), &request("en-XA", None)), "en");
+        assert_eq!(negotiate(&langs, Some(&config), &request("fr", Some("en-xa-x"))), "en");
+        assert_eq!(negotiate(&langs, Some(&config), &request("de, *", None)), "en");
+        assert_eq!(i18n.available_langs(), ["en", "fr"]);
+        assert_eq!(langs.len(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock, RwLockWriteGuard},
+};
+
+#[cfg(feature = "watch")]
+use log::{info, warn};
+#[cfg(feature = "watch")]
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    gettext_layout, leak, loader::default_dir, parse_catalog, Catalog, Error, Translations,
+};
+
+/// Translations that can be reloaded from the disk while the application is running.
+///
+/// Manage it in your application's state instead of `Translations`. Each request uses a
+/// snapshot of the translations taken when it starts, so reloading never affects requests that
+/// are already being processed.
+///
+/// Clones share the same translations: reloading one of them reloads all of them.
+#[derive(Clone)]
+pub struct ReloadableTranslations {
+    domain: String,
+    dir: PathBuf,
+    layout: fn(&str, &str) -> PathBuf,
+    current: Arc<RwLock<Arc<Translations>>>,
+}
+
+impl ReloadableTranslations {
+    /// Loads translations the same way as `i18n`. To read them from another directory, or with
+    /// another layout, use `Loader::reloadable`.
+    pub fn new(domain: &str, langs: Vec<&'static str>) -> ReloadableTranslations {
+        ReloadableTranslations::from_translations(domain, crate::i18n(domain, langs))
+    }
+
+    /// Starts from translations that were loaded otherwise (downloaded with `RemoteCatalogs`, for
+    /// instance). `reload` and `reload_lang` still read the catalogs of `domain` from the disk.
+    pub fn from_translations(domain: &str, translations: Translations) -> ReloadableTranslations {
+        ReloadableTranslations::with_layout(domain, default_dir(), gettext_layout, translations)
+    }
+
+    /// Translations whose catalogs are reloaded from `dir`, with the given layout (see
+    /// `Loader::layout`).
+    pub(crate) fn with_layout(
+        domain: &str,
+        dir: PathBuf,
+        layout: fn(&str, &str) -> PathBuf,
+        translations: Translations,
+    ) -> ReloadableTranslations {
+        ReloadableTranslations {
+            domain: domain.to_owned(),
+            dir,
+            layout,
+            current: Arc::new(RwLock::new(Arc::new(translations))),
+        }
+    }
+
+    /// The translations currently in use.
+    pub fn snapshot(&self) -> Arc<Translations> {
+        self.current
+            .read()
+            .expect("Translations lock was poisoned")
+            .clone()
+    }
+
+    /// Reloads the catalogs of all the languages.
+    ///
+    /// If any of them can't be loaded, the translations are left untouched.
+    pub fn reload(&self) -> Result<(), Error> {
+        let mut current = self.write();
+        let mut updated = Translations::clone(&current);
+        for &(lang, _) in current.iter().filter(|l| !current.is_pseudo(l.0)) {
+            updated.insert(lang, Arc::new(self.load(lang)?));
+        }
+        *current = Arc::new(updated);
+        Ok(())
+    }
+
+    /// Replaces the catalogs of the languages of `translations`, keeping the other ones as they
+    /// are. Languages that were not loaded yet are added after the other ones.
+    pub fn update(&self, translations: &Translations) {
+        let mut current = self.write();
+        let mut updated = Translations::clone(&current);
+        for (lang, catalog) in translations.iter() {
+            let lang = updated.find(lang).unwrap_or(*lang);
+            updated.insert(lang, catalog.clone());
+        }
+        *current = Arc::new(updated);
+    }
+
+    /// Reloads the catalog of a single language, keeping the other ones as they are.
+    ///
+    /// If this language was not loaded yet, it is added after the other ones.
+    pub fn reload_lang(&self, lang: &str) -> Result<(), Error> {
+        let mut current = self.write();
+        let catalog = Arc::new(self.load(lang)?);
+        let mut updated = Translations::clone(&current);
+        let lang = updated.find(lang).unwrap_or_else(|| leak(lang.to_owned()));
+        updated.insert(lang, catalog);
+        *current = Arc::new(updated);
+        Ok(())
+    }
+
+    /// Watches the directory of the catalogs (`translations`, unless they were loaded with
+    /// `Loader::reloadable`), and reloads the catalog of a supported language as soon as its `.mo`
+    /// file changes, so that new translations are used without restarting the application.
+    ///
+    /// ```rust,ignore
+    /// let translations = ReloadableTranslations::new("plume", vec!["en", "fr"]);
+    /// let _watcher = translations.watch()?;
+    /// rocket::ignite().manage(translations)
+    /// ```
+    ///
+    /// Catalogs are watched until the returned watcher is dropped. A catalog that can't be loaded
+    /// (while it is being written, for instance) is logged and left as it was. It is meant to be
+    /// used in development.
+    ///
+    /// Requires the `watch` feature.
+    #[cfg(feature = "watch")]
+    pub fn watch(&self) -> notify::Result<RecommendedWatcher> {
+        let translations = self.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) => translations.on_change(&event),
+                Err(e) => warn!("Error while watching translations: {}", e),
+            })?;
+        watcher.watch(&self.dir, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    }
+
+    /// Reloads the catalogs changed by a file system event.
+    #[cfg(feature = "watch")]
+    fn on_change(&self, event: &Event) {
+        if !(event.kind.is_create() || event.kind.is_modify()) {
+            return;
+        }
+        let langs = self.snapshot().iter().map(|l| l.0).collect::<Vec<_>>();
+        for path in &event.paths {
+            let lang = match langs
+                .iter()
+                .find(|lang| path.ends_with(self.path(lang)))
+            {
+                Some(lang) => lang,
+                None => continue,
+            };
+            match self.reload_lang(lang) {
+                Ok(()) => info!("Reloaded the catalog of {}", lang),
+                Err(e) => warn!("Couldn't reload the catalog of {}: {}", lang, e),
+            }
+        }
+    }
+
+    /// The path of the catalog of a language.
+    fn path(&self, lang: &str) -> PathBuf {
+        self.dir.join((self.layout)(&self.domain, lang))
+    }
+
+    /// Reads the catalog of a language from the disk.
+    fn load(&self, lang: &str) -> Result<Catalog, Error> {
+        parse_catalog(fs::File::open(self.path(lang))?, lang)
+    }
+
+    /// Locks the translations for an update: reloads don't interleave, and the catalogs they read
+    /// are not replaced by an older version read concurrently.
+    fn write(&self) -> RwLockWriteGuard<'_, Arc<Translations>> {
+        self.current.write().expect("Translations lock was poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{mo_file, temp_dir};
+    use crate::Loader;
+
+    #[test]
+    fn only_the_targeted_language_is_reloaded() {
+        let dir = temp_dir("reload");
+        let write = |lang: &str, hello: &str| {
+            fs::write(dir.join(format!("{}.mo", lang)), mo_file(Some(lang), hello)).unwrap()
+        };
+        write("fr", "Bonjour");
+        write("de", "Hallo");
+
+        let translations = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into())
+            .reloadable(vec!["fr", "de"]);
+        let hello = |lang| translations.snapshot().get(lang).unwrap().gettext("Hello").to_owned();
+        write("fr", "Salut");
+        write("de", "Servus");
+
+        translations.reload_lang("fr").unwrap();
+        assert_eq!(hello("fr"), "Salut");
+        assert_eq!(hello("de"), "Hallo");
+
+        translations.reload().unwrap();
+        assert_eq!(hello("de"), "Servus");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn broken_catalogs_are_not_reloaded() {
+        let dir = temp_dir("reload-broken");
+        fs::write(dir.join("fr.mo"), mo_file(Some("fr"), "Bonjour")).unwrap();
+        let translations = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into())
+            .reloadable(vec!["fr"]);
+        let before = translations.snapshot();
+
+        fs::write(dir.join("fr.mo"), "Not a catalog").unwrap();
+        assert!(translations.reload().is_err());
+        assert!(translations.reload_lang("fr").is_err());
+        assert!(Arc::ptr_eq(&before, &translations.snapshot()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn changed_catalogs_are_reloaded() {
+        use notify::{
+            event::{AccessKind, ModifyKind},
+            EventKind,
+        };
+
+        let dir = temp_dir("reload-watch");
+        let write = |lang: &str, hello: &str| {
+            fs::write(dir.join(format!("{}.mo", lang)), mo_file(Some(lang), hello)).unwrap()
+        };
+        write("fr", "Bonjour");
+        write("de", "Hallo");
+        let translations = Loader::new("app")
+            .dir(&dir)
+            .layout(|_, lang| format!("{}.mo", lang).into())
+            .reloadable(vec!["fr", "de"]);
+        let hello = |lang| translations.snapshot().get(lang).unwrap().gettext("Hello").to_owned();
+        write("fr", "Salut");
+        write("de", "Servus");
+
+        let event = |kind| Event::new(kind).add_path(dir.join("fr.mo"));
+        translations.on_change(&event(EventKind::Access(AccessKind::Any)));
+        assert_eq!(hello("fr"), "Bonjour");
+        translations.on_change(&event(EventKind::Modify(ModifyKind::Any)));
+        assert_eq!(hello("fr"), "Salut");
+        assert_eq!(hello("de"), "Hallo");
+
+        fs::write(dir.join("fr.mo"), "Not a catalog").unwrap();
+        translations.on_change(&event(EventKind::Modify(ModifyKind::Any)));
+        assert_eq!(hello("fr"), "Salut");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
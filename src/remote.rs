@@ -0,0 +1,214 @@
+//! Catalogs downloaded from a translation management system.
+
+use std::{error, fmt, time::Duration};
+
+use log::{info, warn};
+use tokio::task::JoinHandle;
+
+use crate::{parse_catalog, Error, ReloadableTranslations, Translations};
+
+/// Why catalogs couldn't be downloaded.
+#[derive(Debug)]
+pub enum RemoteError {
+    /// The catalog of a language couldn't be downloaded.
+    Http {
+        lang: &'static str,
+        url: String,
+        error: reqwest::Error,
+    },
+    /// The downloaded catalog of a language isn't a valid `.mo` file.
+    Parse {
+        lang: &'static str,
+        url: String,
+        error: Error,
+    },
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RemoteError::Http { lang, url, error } => write!(
+                f,
+                "Couldn't download the catalog of {} ({}): {}",
+                lang, url, error
+            ),
+            RemoteError::Parse { lang, url, error } => {
+                write!(f, "The catalog of {} ({}) is invalid: {}", lang, url, error)
+            }
+        }
+    }
+}
+
+impl error::Error for RemoteError {}
+
+/// Downloads `.mo` catalogs over HTTP(S), from a translation management system for instance, so
+/// that new translations can go live without deploying the application again.
+///
+/// ```rust,ignore
+/// let remote = RemoteCatalogs::new("https://tms.example.com/plume/{lang}.mo", vec!["en", "fr"]);
+/// let translations = ReloadableTranslations::from_translations("plume", remote.fetch().await?);
+/// remote.refresh_every(translations.clone(), Duration::from_secs(600));
+/// ```
+///
+/// `{lang}` is replaced with the name of each language in the URL.
+///
+/// Requires the `remote` feature.
+#[derive(Clone)]
+pub struct RemoteCatalogs {
+    url: String,
+    langs: Vec<&'static str>,
+    client: reqwest::Client,
+}
+
+impl RemoteCatalogs {
+    /// Downloads the catalogs of `langs` from `url`.
+    pub fn new<S: Into<String>>(url: S, langs: Vec<&'static str>) -> RemoteCatalogs {
+        RemoteCatalogs {
+            url: url.into(),
+            langs,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// The HTTP client to use, to authenticate with default headers or to set timeouts, for
+    /// instance.
+    pub fn client(mut self, client: reqwest::Client) -> RemoteCatalogs {
+        self.client = client;
+        self
+    }
+
+    /// Downloads every catalog.
+    ///
+    /// If any of them can't be downloaded or parsed, the first error is returned.
+    pub async fn fetch(&self) -> Result<Translations, RemoteError> {
+        let mut catalogs = Vec::with_capacity(self.langs.len());
+        for &lang in &self.langs {
+            let url = self.url.replace("{lang}", lang);
+            let bytes = match self.download(&url).await {
+                Ok(bytes) => bytes,
+                Err(error) => return Err(RemoteError::Http { lang, url, error }),
+            };
+            match parse_catalog(&bytes[..], lang) {
+                Ok(catalog) => catalogs.push((lang, catalog)),
+                Err(error) => return Err(RemoteError::Parse { lang, url, error }),
+            }
+        }
+        Ok(catalogs.into())
+    }
+
+    /// Downloads every catalog again, and swaps them into `translations` at once.
+    ///
+    /// If any of them can't be downloaded or parsed, the translations are left untouched.
+    pub async fn refresh(&self, translations: &ReloadableTranslations) -> Result<(), RemoteError> {
+        let fetched = self.fetch().await?;
+        translations.update(&fetched);
+        Ok(())
+    }
+
+    /// Refreshes `translations` in the background, every `period`, until the returned task is
+    /// aborted.
+    ///
+    /// Failed refreshes are logged, and the previous catalogs are kept until the next one.
+    /// It has to be called from a Tokio runtime.
+    pub fn refresh_every(
+        self,
+        translations: ReloadableTranslations,
+        period: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(period).await;
+                match self.refresh(&translations).await {
+                    Ok(()) => info!("Refreshed the catalogs from {}", self.url),
+                    Err(e) => warn!("{}", e),
+                }
+            }
+        })
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, reqwest::Error> {
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::mo_file;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// Serves `/en.mo` and `/fr.mo` (translating `Hello` with the current value of `bonjour`), an
+    /// invalid `/de.mo`, and nothing else, on a random port.
+    fn serve(bonjour: Arc<Mutex<&'static str>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/{{lang}}.mo", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream).read_line(&mut request_line).unwrap();
+                let (status, body) = match request_line.split(' ').nth(1) {
+                    Some("/en.mo") => ("200 OK", mo_file(None, "Hello")),
+                    Some("/fr.mo") => ("200 OK", mo_file(None, *bonjour.lock().unwrap())),
+                    Some("/de.mo") => ("200 OK", b"Hallo".to_vec()),
+                    _ => ("404 Not Found", Vec::new()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        url
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn catalogs_are_downloaded_and_refreshed() {
+        let bonjour = Arc::new(Mutex::new("Bonjour"));
+        let url = serve(bonjour.clone());
+        let hello_in = |t: &Translations, lang| t.get(lang).unwrap().gettext("Hello").to_owned();
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let remote = RemoteCatalogs::new(url.clone(), vec!["en", "fr"]).client(client);
+        let fetched = block_on(remote.fetch()).unwrap();
+        assert_eq!(fetched.all_langs().copied().collect::<Vec<_>>(), vec!["en", "fr"]);
+        assert_eq!(hello_in(&fetched, "fr"), "Bonjour");
+
+        let translations = ReloadableTranslations::from_translations("app", fetched);
+        *bonjour.lock().unwrap() = "Salut";
+        block_on(remote.refresh(&translations)).unwrap();
+        assert_eq!(hello_in(&translations.snapshot(), "fr"), "Salut");
+
+        let missing = RemoteCatalogs::new(url.clone(), vec!["en", "it"]);
+        *bonjour.lock().unwrap() = "Coucou";
+        match block_on(missing.refresh(&translations)).unwrap_err() {
+            RemoteError::Http { lang, url, error } => {
+                assert_eq!(lang, "it");
+                assert!(url.ends_with("/it.mo"));
+                assert_eq!(error.status().map(|s| s.as_u16()), Some(404));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let invalid = RemoteCatalogs::new(url, vec!["fr", "de"]);
+        let error = block_on(invalid.refresh(&translations)).unwrap_err();
+        assert!(matches!(error, RemoteError::Parse { lang: "de", .. }));
+        assert_eq!(hello_in(&translations.snapshot(), "fr"), "Salut");
+    }
+}
@@ -0,0 +1,131 @@
+use std::net::IpAddr;
+
+use crate::negotiation::RequestInfo;
+
+/// A custom way to find the language requested by a client (from the profile of the current
+/// user, for instance), to insert in `I18nConfig::chain`.
+pub trait LocaleResolver: Send + Sync {
+    /// The language requested by the client, if this resolver knows it.
+    ///
+    /// It doesn't have to be supported: unsupported languages fall back to a supported one with
+    /// the same primary language, or are skipped, like any other requested language. `auto`
+    /// means that the user has no preference.
+    fn resolve(&self, request: &LocaleRequest) -> Option<String>;
+}
+
+/// What a `LocaleResolver` can read from the current request.
+pub struct LocaleRequest<'a, 'r>(pub(crate) &'r RequestInfo<'a>);
+
+impl<'a, 'r> LocaleRequest<'a, 'r> {
+    /// The value of a header of the request.
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        self.0.header.and_then(|header| header(name))
+    }
+
+    /// The value of a cookie of the request.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.0.cookies.and_then(|cookies| cookies(name))
+    }
+
+    /// The IP address of the client, when the framework gives it (with Rocket and Actix Web).
+    ///
+    /// Behind a reverse proxy, it is the address of the proxy: read the header it sets
+    /// (`X-Forwarded-For`, for instance) instead.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.0.client_ip
+    }
+}
+
+/// A step of the negotiation of the language of a request.
+///
+/// Steps are tried in the order of `I18nConfig::chain`, until one of them finds a supported
+/// language. When none of them does, the default language is used.
+pub enum Resolver {
+    /// The query parameter named in `I18nConfig::query`.
+    Query,
+    /// The first segment of the path, if `I18nConfig::path_prefix` is enabled.
+    Path,
+    /// The subdomain, if `I18nConfig::subdomain_suffix` is set.
+    Subdomain,
+    /// The cookie named in `I18nConfig::cookie`.
+    Cookie,
+    /// The language saved by the user, found by the resolver of
+    /// `Translations::with_user_resolver`.
+    User,
+    /// The `Accept-Language` header.
+    AcceptLanguage,
+    /// The country of the client, if `I18nConfig::geo` is set.
+    Geo,
+    /// A custom resolver.
+    Custom(Box<dyn LocaleResolver>),
+}
+
+impl Resolver {
+    /// The chain used by default: the languages explicitly chosen by the user first (for this
+    /// request, then in the cookie, then in their profile), then the ones requested by their
+    /// browser, and finally the guess based on their location.
+    pub fn default_chain() -> Vec<Resolver> {
+        vec![
+            Resolver::Query,
+            Resolver::Path,
+            Resolver::Subdomain,
+            Resolver::Cookie,
+            Resolver::User,
+            Resolver::AcceptLanguage,
+            Resolver::Geo,
+        ]
+    }
+}
+
+/// The chain for requests without an `I18nConfig`, where only the language saved by the user and
+/// `Accept-Language` can be used.
+pub(crate) const DEFAULT_CHAIN: &[Resolver] = &[Resolver::User, Resolver::AcceptLanguage];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{negotiation::negotiate, testing, I18nConfig};
+
+    /// Reads the language from an `X-Lang` header.
+    struct XLang;
+
+    impl LocaleResolver for XLang {
+        fn resolve(&self, request: &LocaleRequest) -> Option<String> {
+            request.header("X-Lang").map(str::to_owned)
+        }
+    }
+
+    #[test]
+    fn resolvers_are_tried_in_the_order_of_the_chain() {
+        let langs = testing::translations(&["en", "fr", "de"]);
+        let negotiate_with = |config: &I18nConfig, x_lang: Option<&'static str>| {
+            let header = |name: &str| match name {
+                "Accept-Language" => Some("de"),
+                "X-Lang" => x_lang,
+                _ => None,
+            };
+            let cookies = |name: &str| Some("fr".to_owned()).filter(|_| name == "lang");
+            let request = RequestInfo::new(Some(config), &header, &cookies, None, "/");
+            negotiate(&langs, Some(config), &request)
+        };
+
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .chain(vec![Resolver::Custom(Box::new(XLang)), Resolver::AcceptLanguage])
+            .build();
+        assert_eq!(negotiate_with(&config, Some("en")), "en");
+        assert_eq!(negotiate_with(&config, Some("ja")), "de");
+        assert_eq!(negotiate_with(&config, Some("auto")), "de");
+        assert_eq!(negotiate_with(&config, None), "de");
+
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .chain(vec![Resolver::AcceptLanguage, Resolver::Cookie])
+            .resolver(Resolver::Custom(Box::new(XLang)))
+            .build();
+        assert_eq!(negotiate_with(&config, Some("en")), "de");
+
+        let config = I18nConfig::builder().cookie("lang").build();
+        assert_eq!(negotiate_with(&config, None), "fr");
+    }
+}
@@ -0,0 +1,74 @@
+//! Signatures of the language cookie, see `I18nConfig::cookie_key`.
+
+use std::fmt::Write;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The value of the cookie named `name`, followed by its signature (`fr.3f2a…`).
+pub(crate) fn sign(key: &[u8], name: &str, value: &str) -> String {
+    let signature = mac(key, name, value).finalize().into_bytes();
+    let mut signed = String::with_capacity(value.len() + 1 + signature.len() * 2);
+    signed.push_str(value);
+    signed.push('.');
+    for byte in signature {
+        let _ = write!(signed, "{:02x}", byte);
+    }
+    signed
+}
+
+/// The value of a signed cookie, if its signature is valid.
+///
+/// The name of the cookie is signed too, so that a value can't be copied from another cookie
+/// signed with the same key.
+pub(crate) fn verify<'a>(key: &[u8], name: &str, signed: &'a str) -> Option<&'a str> {
+    let (value, signature) = signed.rsplit_once('.')?;
+    let digits = signature
+        .chars()
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<_>>>()?;
+    let pairs = digits.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    let signature = pairs.map(|pair| (pair[0] * 16 + pair[1]) as u8).collect::<Vec<_>>();
+    // verify_slice compares in constant time
+    mac(key, name, value).verify_slice(&signature).ok()?;
+    Some(value)
+}
+
+fn mac(key: &[u8], name: &str, value: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(name.as_bytes());
+    mac.update(b"=");
+    mac.update(value.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn signed_values_are_verified() {
+        let signed = sign(KEY, "lang", "fr");
+        assert!(signed.starts_with("fr."));
+        assert_eq!(verify(KEY, "lang", &signed), Some("fr"));
+    }
+
+    #[test]
+    fn forged_values_are_rejected() {
+        let signed = sign(KEY, "lang", "fr");
+        assert_eq!(verify(KEY, "lang", "fr"), None);
+        assert_eq!(verify(KEY, "lang", &signed.replacen("fr", "de", 1)), None);
+        assert_eq!(verify(KEY, "other", &signed), None);
+        assert_eq!(verify(b"another key", "lang", &signed), None);
+        assert_eq!(verify(KEY, "lang", "fr.zz"), None);
+        assert_eq!(verify(KEY, "lang", "fr.é"), None);
+        assert_eq!(verify(KEY, "lang", &format!("{}0", signed)), None);
+    }
+}
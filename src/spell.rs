@@ -0,0 +1,156 @@
+//! Spelling out numbers, for the few languages where it is supported: English, French and
+//! German, for numbers below 1000.
+
+use crate::langs;
+
+/// Spells out a number, if it is supported in this language.
+pub(crate) fn spell_number(lang: &str, n: u64) -> Option<String> {
+    if n >= 1000 {
+        return None;
+    }
+
+    let n = n as usize;
+    match langs::base(lang).to_lowercase().as_str() {
+        "en" => Some(english(n)),
+        "fr" => Some(french(n)),
+        "de" => Some(german(n)),
+        _ => None,
+    }
+}
+
+const EN_UNITS: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+fn english(n: usize) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => english_below_100(rest),
+        (hundreds, 0) => format!("{} hundred", EN_UNITS[hundreds]),
+        (hundreds, rest) => format!("{} hundred {}", EN_UNITS[hundreds], english_below_100(rest)),
+    }
+}
+
+fn english_below_100(n: usize) -> String {
+    match (n / 10, n % 10) {
+        _ if n < 20 => EN_UNITS[n].to_owned(),
+        (tens, 0) => EN_TENS[tens].to_owned(),
+        (tens, units) => format!("{}-{}", EN_TENS[tens], EN_UNITS[units]),
+    }
+}
+
+const FR_UNITS: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix", "onze",
+    "douze", "treize", "quatorze", "quinze", "seize",
+];
+const FR_TENS: [&str; 7] = ["", "dix", "vingt", "trente", "quarante", "cinquante", "soixante"];
+
+fn french(n: usize) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => french_below_100(rest),
+        (1, 0) => "cent".to_owned(),
+        (1, rest) => format!("cent {}", french_below_100(rest)),
+        (hundreds, 0) => format!("{} cents", FR_UNITS[hundreds]),
+        (hundreds, rest) => format!("{} cent {}", FR_UNITS[hundreds], french_below_100(rest)),
+    }
+}
+
+fn french_below_100(n: usize) -> String {
+    match n {
+        0..=16 => FR_UNITS[n].to_owned(),
+        17..=19 => format!("dix-{}", FR_UNITS[n - 10]),
+        20..=69 => match (n / 10, n % 10) {
+            (tens, 0) => FR_TENS[tens].to_owned(),
+            (tens, 1) => format!("{} et un", FR_TENS[tens]),
+            (tens, units) => format!("{}-{}", FR_TENS[tens], FR_UNITS[units]),
+        },
+        71 => "soixante et onze".to_owned(),
+        70..=79 => format!("soixante-{}", french_below_100(n - 60)),
+        80 => "quatre-vingts".to_owned(),
+        _ => format!("quatre-vingt-{}", french_below_100(n - 80)),
+    }
+}
+
+const DE_UNITS: [&str; 20] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun", "zehn",
+    "elf", "zwölf", "dreizehn", "vierzehn", "fünfzehn", "sechzehn", "siebzehn", "achtzehn",
+    "neunzehn",
+];
+const DE_TENS: [&str; 10] = [
+    "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig",
+    "neunzig",
+];
+
+fn german(n: usize) -> String {
+    let hundreds = match n / 100 {
+        0 => return german_below_100(n),
+        1 => "ein",
+        hundreds => DE_UNITS[hundreds],
+    };
+    match n % 100 {
+        0 => format!("{}hundert", hundreds),
+        rest => format!("{}hundert{}", hundreds, german_below_100(rest)),
+    }
+}
+
+fn german_below_100(n: usize) -> String {
+    match (n / 10, n % 10) {
+        _ if n < 20 => DE_UNITS[n].to_owned(),
+        (tens, 0) => DE_TENS[tens].to_owned(),
+        (tens, 1) => format!("einund{}", DE_TENS[tens]),
+        (tens, units) => format!("{}und{}", DE_UNITS[units], DE_TENS[tens]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogBuilder, I18n};
+
+    #[test]
+    fn numbers_are_spelled_out() {
+        let spell = |lang, numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|n| spell_number(lang, *n).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            spell("en-GB", &[0, 13, 42, 100, 999]),
+            ["zero", "thirteen", "forty-two", "one hundred", "nine hundred ninety-nine"]
+        );
+        assert_eq!(
+            spell("fr", &[21, 71, 77, 80, 81, 91, 100, 200, 201]),
+            [
+                "vingt et un",
+                "soixante et onze",
+                "soixante-dix-sept",
+                "quatre-vingts",
+                "quatre-vingt-un",
+                "quatre-vingt-onze",
+                "cent",
+                "deux cents",
+                "deux cent un"
+            ]
+        );
+        assert_eq!(
+            spell("de", &[1, 21, 42, 101, 300]),
+            ["eins", "einundzwanzig", "zweiundvierzig", "einhunderteins", "dreihundert"]
+        );
+    }
+
+    #[test]
+    fn unsupported_numbers_are_written_with_digits() {
+        assert_eq!(spell_number("en", 1000), None);
+        assert_eq!(spell_number("ja", 42), None);
+
+        let i18n = |lang| I18n::new_for_tests(lang, CatalogBuilder::new().build());
+        assert_eq!(i18n("en").spell_number(42), "forty-two");
+        assert_eq!(i18n("en").spell_number(1234), "1234");
+        assert_eq!(i18n("ja").spell_number(42), "42");
+    }
+}
@@ -0,0 +1,126 @@
+//! The language switch endpoint of the integrations (see `routes`), independently of any
+//! framework.
+
+use crate::{negotiation::query_value, I18nConfig, Translations};
+
+/// The cookie to use when `I18nConfig::cookie` is not set.
+const DEFAULT_COOKIE: &str = "lang";
+
+/// What to do after a request to switch languages.
+pub(crate) struct LangSwitch {
+    /// The name of the cookie storing the language.
+    pub cookie: String,
+    /// The language to store in it: a supported language, or `auto`.
+    pub lang: &'static str,
+    /// Where to redirect the client.
+    pub redirect: String,
+}
+
+/// Reads the form sent to switch languages (`lang=fr&redirect=%2Fposts%2F1`).
+///
+/// The language must be supported, or be `auto` to go back to the negotiated language. The
+/// client is redirected to `redirect`, or to the page it came from (from the `Referer` header),
+/// or to `/`. Only paths of this site are accepted, so that the endpoint can't be used to
+/// redirect elsewhere.
+///
+/// `None` means that the language is missing or not supported.
+pub(crate) fn switch_lang(
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    form: &str,
+    referer: Option<&str>,
+    host: Option<&str>,
+) -> Option<LangSwitch> {
+    let lang = percent_decode(query_value(form, "lang")?);
+    let lang = if lang.eq_ignore_ascii_case("auto") {
+        "auto"
+    } else {
+        langs.find_explicit(lang.trim())?
+    };
+    let redirect = query_value(form, "redirect")
+        .map(percent_decode)
+        .filter(|path| is_local(path))
+        .or_else(|| referer.and_then(|r| same_site_path(r, host?)))
+        .unwrap_or_else(|| "/".to_owned());
+    Some(LangSwitch {
+        cookie: config
+            .and_then(|c| c.cookie.clone())
+            .unwrap_or_else(|| DEFAULT_COOKIE.to_owned()),
+        lang,
+        redirect,
+    })
+}
+
+/// Whether a path is on this site (`/posts/1`, but not `//example.com/` or `https://…`).
+fn is_local(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\')
+}
+
+/// The path of a `Referer` URL, if it is on `host`.
+fn same_site_path(referer: &str, host: &str) -> Option<String> {
+    let rest = &referer[referer.find("://")? + 3..];
+    let end = rest.find(&['/', '?', '#'][..]).unwrap_or(rest.len());
+    if !rest[..end].eq_ignore_ascii_case(host) {
+        return None;
+    }
+    let path = match &rest[end..] {
+        "" => "/".to_owned(),
+        path if path.starts_with('/') => path.to_owned(),
+        query => format!("/{}", query),
+    };
+    Some(path).filter(|path| is_local(path))
+}
+
+/// Decodes a value of an `application/x-www-form-urlencoded` body.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn only_supported_languages_are_accepted() {
+        let langs = testing::translations(&["en", "fr"]);
+        let config = I18nConfig::builder().cookie("prefs").build();
+        let switch = switch_lang(&langs, Some(&config), "lang=FR&redirect=%2Fposts", None, None);
+        let switch = switch.unwrap();
+        assert_eq!((&switch.cookie[..], switch.lang), ("prefs", "fr"));
+        assert_eq!(switch.redirect, "/posts");
+        assert_eq!(switch_lang(&langs, None, "lang=auto", None, None).unwrap().lang, "auto");
+        assert!(switch_lang(&langs, None, "lang=de", None, None).is_none());
+    }
+
+    #[test]
+    fn only_local_redirects_are_accepted() {
+        let langs = testing::translations(&["en", "fr"]);
+        let redirect = |form, referer| switch_lang(&langs, None, form, referer, Some("a.org"));
+        assert_eq!(redirect("lang=fr&redirect=%2F%2Fevil.com", None).unwrap().redirect, "/");
+        let referer = Some("https://a.org/posts?page=2");
+        assert_eq!(redirect("lang=fr", referer).unwrap().redirect, "/posts?page=2");
+        assert_eq!(redirect("lang=fr", Some("https://evil.com/x")).unwrap().redirect, "/");
+    }
+}
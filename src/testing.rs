@@ -0,0 +1,90 @@
+//! Helpers to test handlers using `I18n`, without any `.mo` file.
+//!
+//! ```rust,ignore
+//! let catalog = CatalogBuilder::new().msg("Hello, world!", "Bonjour tout le monde !").build();
+//! let i18n = I18n::new_for_tests("fr", catalog);
+//! assert_eq!(index(i18n).title, "Bonjour tout le monde !");
+//! ```
+
+#[cfg(feature = "actix-web")]
+use actix_web::test::TestRequest;
+#[cfg(feature = "rocket")]
+use std::borrow::Cow;
+
+#[cfg(feature = "rocket")]
+use rocket::{
+    http::{Header, Method},
+    local::{Client, LocalRequest},
+};
+
+#[cfg(any(feature = "actix-web", feature = "rocket"))]
+use crate::ACCEPT_LANG;
+use crate::{empty_catalog, Translations};
+
+/// Translations supporting `langs`, in this order, without any message: every message is left
+/// untranslated.
+pub fn translations(langs: &[&'static str]) -> Translations {
+    langs.iter().map(|lang| (*lang, empty_catalog())).collect()
+}
+
+/// A request to a local Rocket instance, with the `Accept-Language` header set to
+/// `accept_language`.
+///
+/// ```rust,ignore
+/// let client = Client::new(rocket::ignite().manage(testing::translations(&["en", "fr"])))?;
+/// let res = testing::rocket_request(&client, Method::Get, "/", "fr-FR, fr;q=0.8").dispatch();
+/// ```
+#[cfg(feature = "rocket")]
+pub fn rocket_request<'c, U: Into<Cow<'c, str>>>(
+    client: &'c Client,
+    method: Method,
+    uri: U,
+    accept_language: &str,
+) -> LocalRequest<'c> {
+    client
+        .req(method, uri)
+        .header(Header::new(ACCEPT_LANG, accept_language.to_owned()))
+}
+
+/// A request to an Actix Web handler, with the `Accept-Language` header set to
+/// `accept_language`, and `translations` in its application data.
+///
+/// ```rust,ignore
+/// let req = testing::actix_request(testing::translations(&["en", "fr"]), "fr").to_http_request();
+/// let i18n = I18n::extract(&req).wait()?;
+/// ```
+#[cfg(feature = "actix-web")]
+pub fn actix_request(translations: Translations, accept_language: &str) -> TestRequest {
+    TestRequest::with_header(ACCEPT_LANG, accept_language.to_owned()).data(translations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogBuilder, I18n};
+
+    #[test]
+    fn test_translations_leave_messages_untranslated() {
+        let translations = translations(&["fr", "en"]);
+        let langs = translations.iter().map(|l| l.0).collect::<Vec<_>>();
+        assert_eq!(langs, vec!["fr", "en"]);
+        assert_eq!(translations.get("fr").unwrap().gettext("Hello"), "Hello");
+
+        let catalog = CatalogBuilder::new().msg("Hello", "Bonjour").build();
+        let i18n = I18n::new_for_tests("fr", catalog);
+        assert_eq!(i18n.lang, "fr");
+        assert_eq!(i18n.catalog.gettext("Hello"), "Bonjour");
+        assert_eq!(i18n.available_langs(), &["fr"]);
+    }
+
+    #[cfg(feature = "actix-web")]
+    #[test]
+    fn actix_requests_are_negotiated() {
+        use actix_web::FromRequest;
+        use futures_01::Future;
+
+        let req = actix_request(translations(&["en", "fr"]), "fr-FR, en;q=0.5");
+        let i18n = I18n::extract(&req.to_http_request()).wait().ok().unwrap();
+        assert_eq!(i18n.lang, "fr");
+    }
+}
@@ -0,0 +1,63 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+#[cfg(feature = "rocket")]
+use std::{
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use crate::negotiation::cookie_value;
+
+/// Finds the language preferred by the user of a request, see
+/// `Translations::with_user_resolver`.
+pub(crate) type UserResolver = Arc<dyn Fn(UserRequest) -> UserFuture + Send + Sync>;
+
+/// The language preferred by the user of a request, once it is found.
+pub(crate) type UserFuture = Pin<Box<dyn Future<Output = Option<String>> + Send>>;
+
+/// What the resolver of `Translations::with_user_resolver` can read from the current request.
+///
+/// The resolver is asynchronous, so it gets a copy of the headers of the request.
+pub struct UserRequest {
+    pub(crate) headers: Vec<(String, String)>,
+}
+
+impl UserRequest {
+    /// The value of a header of the request.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of a cookie of the request.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case("Cookie"))
+            .find_map(|(_, cookies)| cookie_value(cookies, name))
+    }
+}
+
+/// Waits for the language found by a resolver on the current thread, for Rocket, whose guards are
+/// synchronous.
+#[cfg(feature = "rocket")]
+pub(crate) fn block_on(mut future: UserFuture) -> Option<String> {
+    /// Wakes the thread waiting for the future.
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(lang) => return lang,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
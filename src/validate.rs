@@ -0,0 +1,120 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+};
+
+use crate::{catalog_path, mo, Error};
+
+/// A translation that doesn't use the same formatting placeholders (`{0}`, `{1}`, …) as the
+/// string it translates.
+///
+/// Such a translation will either lose some information, or fail to be formatted at runtime.
+#[derive(Debug)]
+pub struct PlaceholderMismatch {
+    /// The language of the translation.
+    pub lang: String,
+    /// The context of the message, if any.
+    pub context: Option<String>,
+    /// The message that was translated.
+    pub msg_id: String,
+    /// The placeholders of the reference string.
+    pub expected: BTreeSet<usize>,
+    /// The placeholders of the translation.
+    pub found: BTreeSet<usize>,
+}
+
+/// Checks that the translations of every language use the same placeholders as the reference
+/// language, reading catalogs from the same place as `i18n`.
+///
+/// The strings of the reference language are its translations when they exist, and the
+/// original messages otherwise. For plural messages, all the forms are considered together, as
+/// it's common for a form not to use the count.
+///
+/// It is meant to be used in your tests, to catch broken translations before they reach
+/// production:
+///
+/// ```rust,ignore
+/// #[test]
+/// fn translations_are_valid() {
+///     let mismatches = rocket_i18n::validate_placeholders("my_app", "en", &["fr", "de"]).unwrap();
+///     assert!(mismatches.is_empty(), "{:#?}", mismatches);
+/// }
+/// ```
+pub fn validate_placeholders(
+    domain: &str,
+    reference_lang: &str,
+    langs: &[&str],
+) -> Result<Vec<PlaceholderMismatch>, Error> {
+    let reference = read_entries(domain, reference_lang)?
+        .into_iter()
+        .map(|entry| {
+            let expected = if entry.translations.iter().any(|t| !t.is_empty()) {
+                all_placeholders(&entry.translations)
+            } else {
+                all_placeholders(entry.plural.iter().chain(Some(&entry.id)))
+            };
+            ((entry.context, entry.id), expected)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut mismatches = Vec::new();
+    for lang in langs.iter().filter(|l| **l != reference_lang) {
+        for entry in read_entries(domain, lang)? {
+            if entry.translations.iter().all(String::is_empty) {
+                continue;
+            }
+
+            let key = (entry.context, entry.id);
+            let expected = match reference.get(&key) {
+                Some(expected) => expected.clone(),
+                None => all_placeholders(entry.plural.iter().chain(Some(&key.1))),
+            };
+            let found = all_placeholders(&entry.translations);
+            if found != expected {
+                mismatches.push(PlaceholderMismatch {
+                    lang: lang.to_string(),
+                    context: key.0,
+                    msg_id: key.1,
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+/// The entries of a catalog, without its header.
+fn read_entries(domain: &str, lang: &str) -> Result<Vec<mo::Entry>, Error> {
+    let bytes = fs::read(catalog_path(domain, lang))?;
+    Ok(mo::entries(&bytes)?
+        .into_iter()
+        .filter(|entry| !entry.id.is_empty())
+        .collect())
+}
+
+fn all_placeholders<'a, I: IntoIterator<Item = &'a String>>(strings: I) -> BTreeSet<usize> {
+    strings
+        .into_iter()
+        .flat_map(|s| placeholders(s))
+        .collect()
+}
+
+/// The positional placeholders (`{0}`, `{1:>4}`, …) of a format string.
+fn placeholders(s: &str) -> BTreeSet<usize> {
+    let mut found = BTreeSet::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        if rest.starts_with('{') {
+            // Escaped brace
+            rest = &rest[1..];
+            continue;
+        }
+        let end = rest.find(['}', ':']).unwrap_or(rest.len());
+        if let Ok(i) = rest[..end].parse() {
+            found.insert(i);
+        }
+    }
+    found
+}
@@ -0,0 +1,154 @@
+//! Filters to use rocket_i18n with warp.
+//!
+//! ```rust,ignore
+//! let translations = rocket_i18n::i18n("your-domain", vec!["en", "fr"]);
+//! let hello = warp::path("hello")
+//!     .and(rocket_i18n::warp::i18n(translations))
+//!     .map(|i18n: I18n| i18n!(i18n.catalog, "Hello, world!"));
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    negotiation::{cookie_value, negotiate, RequestInfo},
+    I18n, I18nConfig, Translations,
+};
+
+use ::warp::{
+    http::{header, HeaderMap},
+    path::FullPath,
+    reject::{self, Reject},
+    Filter, Rejection,
+};
+
+/// The rejection of the `i18n` filters, when the negotiated language has no catalog.
+#[derive(Debug)]
+pub struct MissingTranslations {
+    /// The negotiated language.
+    pub lang: &'static str,
+    /// The languages that have a catalog.
+    pub available: Vec<&'static str>,
+}
+
+impl Reject for MissingTranslations {}
+
+/// A filter extracting the `I18n` of the request, negotiated with the default options.
+pub fn i18n(
+    translations: Translations,
+) -> impl Filter<Extract = (I18n,), Error = Rejection> + Clone {
+    filter(Arc::new(translations), None)
+}
+
+/// A filter extracting the `I18n` of the request, negotiated with the given options.
+pub fn i18n_with_config(
+    translations: Translations,
+    config: I18nConfig,
+) -> impl Filter<Extract = (I18n,), Error = Rejection> + Clone {
+    filter(Arc::new(translations), Some(Arc::new(config)))
+}
+
+fn filter(
+    langs: Arc<Translations>,
+    config: Option<Arc<I18nConfig>>,
+) -> impl Filter<Extract = (I18n,), Error = Rejection> + Clone {
+    ::warp::header::headers_cloned()
+        .and(::warp::path::full())
+        .and(::warp::query::raw().or(::warp::any().map(String::new)).unify())
+        .and_then(move |headers: HeaderMap, path: FullPath, query: String| {
+            let langs = langs.clone();
+            let config = config.clone();
+            async move {
+                let user = langs.resolve_user(|| {
+                    headers
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                        })
+                        .collect()
+                });
+                let user = match user {
+                    Some(user) => user.await,
+                    None => None,
+                };
+                from_request(&langs, config.as_deref(), &headers, path.as_str(), &query, user)
+            }
+        })
+}
+
+fn from_request(
+    langs: &Translations,
+    config: Option<&I18nConfig>,
+    headers: &HeaderMap,
+    path: &str,
+    query: &str,
+    user: Option<String>,
+) -> Result<I18n, Rejection> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let cookies = |name: &str| {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(|cookies| cookie_value(cookies, name))
+            .map(str::to_owned)
+    };
+    let request =
+        RequestInfo::new(config, &header, &cookies, Some(query), path).with_user(user);
+    let lang = negotiate(langs, config, &request);
+
+    langs.for_request(lang).ok_or_else(|| {
+        reject::custom(MissingTranslations {
+            lang,
+            available: langs.iter().map(|l| l.0).collect(),
+        })
+    })
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn the_user_resolver_comes_before_accept_language() {
+        let translations = testing::translations(&["en", "fr", "de"]).with_user_resolver(|req| {
+            let lang = req.cookie("session").map(|s| if s == "42" { "de" } else { "fr" });
+            async move { lang.map(str::to_owned) }
+        });
+        let filter = i18n(translations);
+
+        let lang = |cookie: Option<&str>| {
+            let mut req = ::warp::test::request().header("Accept-Language", "en");
+            if let Some(cookie) = cookie {
+                req = req.header("Cookie", cookie);
+            }
+            block_on(req.filter(&filter)).unwrap().lang
+        };
+        assert_eq!(lang(Some("session=42")), "de");
+        assert_eq!(lang(Some("session=7")), "fr");
+        assert_eq!(lang(None), "en");
+    }
+
+    #[test]
+    fn the_configuration_is_used() {
+        let config = I18nConfig::builder().cookie("lang").query("lang").build();
+        let filter = i18n_with_config(testing::translations(&["en", "fr", "de"]), config);
+        let lang = |path: &str, cookie: &str| {
+            let req = ::warp::test::request()
+                .path(path)
+                .header("Accept-Language", "en")
+                .header("Cookie", cookie);
+            block_on(req.filter(&filter)).unwrap().lang
+        };
+        assert_eq!(lang("/", "lang=fr"), "fr");
+        assert_eq!(lang("/?page=2&lang=de", "lang=fr"), "de");
+        assert_eq!(lang("/", "other=fr"), "en");
+    }
+}
@@ -1,15 +1,37 @@
 use std::{error::Error, fmt};
 
-use crate::{I18n, Translations, ACCEPT_LANG};
+#[cfg(feature = "gettext")]
+use crate::ReloadableTranslations;
+use crate::{
+    negotiation::{negotiate, varies_on_accept_language, RequestInfo},
+    switch::switch_lang,
+    DefaultI18n, FailureResponse, I18n, I18nConfig, Translations,
+};
 
-use actix_web::{dev::Payload, FromRequest, HttpRequest, ResponseError};
+use actix_web::{
+    cookie::SameSite,
+    dev::{Payload, ServiceResponse},
+    http::{
+        header::{self, HeaderValue},
+        Cookie, StatusCode,
+    },
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse, Resource, ResponseError,
+};
+use futures_01::{future, Future};
+use futures_util::{compat::Compat, FutureExt};
+
+/// Marks the requests that used the `I18n` extractor, for `vary_accept_language`.
+struct Negotiated;
 
 #[derive(Debug)]
-pub struct MissingTranslationsError(String);
+pub struct MissingTranslationsError {
+    lang: String,
+    response: FailureResponse,
+}
 
 impl fmt::Display for MissingTranslationsError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Could not find translations for {}", self.0)
+        write!(f, "Could not find translations for {}", self.lang)
     }
 }
 
@@ -20,11 +42,18 @@ impl Error for MissingTranslationsError {
 }
 
 impl ResponseError for MissingTranslationsError {
-    // this defaults to an empty InternalServerError response
+    /// The response configured in `I18nConfig::on_failure`: by default, an InternalServerError
+    /// listing the available languages, as JSON if the request asked for it, and as HTML
+    /// otherwise.
+    fn error_response(&self) -> HttpResponse {
+        failure_response(&self.response)
+    }
 }
 
 #[derive(Debug)]
-pub struct MissingStateError;
+pub struct MissingStateError {
+    response: FailureResponse,
+}
 
 impl fmt::Display for MissingStateError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -39,39 +68,330 @@ impl Error for MissingStateError {
 }
 
 impl ResponseError for MissingStateError {
-    // this defaults to an empty InternalServerError response
+    /// The response configured in `I18nConfig::on_failure`: by default, an empty
+    /// InternalServerError.
+    fn error_response(&self) -> HttpResponse {
+        failure_response(&self.response)
+    }
+}
+
+fn failure_response(response: &FailureResponse) -> HttpResponse {
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut builder = HttpResponse::build(status);
+    if !response.content_type.is_empty() {
+        builder.content_type(response.content_type.as_str());
+    }
+    builder.body(response.body.clone())
 }
 
 impl FromRequest for I18n {
     type Config = ();
     type Error = actix_web::Error;
-    type Future = Result<Self, Self::Error>;
+    type Future = Box<dyn Future<Item = Self, Error = Self::Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        let langs = req.app_data::<Translations>().ok_or(MissingStateError)?;
-
-        let lang = req
-            .headers()
-            .get(ACCEPT_LANG)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("en")
-            .split(",")
-            .filter_map(|lang| {
-                lang
-                    // Get the locale, not the country code
-                    .split(|c| c == '-' || c == ';')
-                    .nth(0)
+        let user = with_translations(req, |langs, _| {
+            langs.resolve_user(|| {
+                req.headers()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                    })
+                    .collect()
+            })
+        });
+        let req = req.clone();
+        match user.flatten() {
+            Some(user) => Box::new(
+                Compat::new(user.unit_error())
+                    .then(move |user| from_request(&req, user.ok().flatten())),
+            ),
+            None => Box::new(future::result(from_request(&req, None))),
+        }
+    }
+}
+
+fn from_request(req: &HttpRequest, user: Option<String>) -> Result<I18n, actix_web::Error> {
+    with_translations(req, |langs, config| {
+        let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+        let cookies = |name: &str| req.cookie(name).map(|c| c.value().to_owned());
+        let request = RequestInfo::new(
+            config,
+            &header,
+            &cookies,
+            Some(req.query_string()),
+            req.path(),
+        )
+        .with_client_ip(req.peer_addr().map(|addr| addr.ip()))
+        .with_user(user);
+        let lang = negotiate(langs, config, &request);
+
+        match langs.for_request(lang) {
+            Some(i18n) => {
+                req.extensions_mut().insert(Negotiated);
+                Ok(i18n)
+            }
+            None => failure(req, config, Some(langs), Some(lang)),
+        }
+    })
+    .unwrap_or_else(|| {
+        failure(req, req.app_data::<I18nConfig>(), None, None)
+    })
+}
+
+/// Handles a request that can't be translated, as configured in `I18nConfig::on_failure`:
+/// `lang` is the negotiated language, or `None` if there are no translations.
+fn failure(
+    req: &HttpRequest,
+    config: Option<&I18nConfig>,
+    langs: Option<&Translations>,
+    lang: Option<&str>,
+) -> Result<I18n, actix_web::Error> {
+    let available = langs.map_or_else(Vec::new, |langs| langs.iter().map(|l| l.0).collect());
+    let accept = req.headers().get("Accept").and_then(|v| v.to_str().ok());
+    match FailureResponse::for_request(config, accept, lang, &available) {
+        Some(response) => Err(failure_error(lang, response)),
+        None => {
+            req.extensions_mut().insert(Negotiated);
+            Ok(DefaultI18n::fallback(langs, config).0)
+        }
+    }
+}
+
+fn failure_error(lang: Option<&str>, response: FailureResponse) -> actix_web::Error {
+    match lang {
+        Some(lang) => MissingTranslationsError {
+            lang: lang.to_owned(),
+            response,
+        }
+        .into(),
+        None => MissingStateError { response }.into(),
+    }
+}
+
+impl FromRequest for DefaultI18n {
+    type Config = ();
+    type Error = actix_web::Error;
+    type Future = Box<dyn Future<Item = Self, Error = Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::new(I18n::from_request(&req, payload).then(move |i18n| {
+            Ok(match i18n {
+                Ok(i18n) => DefaultI18n(i18n),
+                Err(_) => with_translations(&req, |langs, config| {
+                    DefaultI18n::fallback(Some(langs), config)
+                })
+                .unwrap_or_else(|| {
+                    DefaultI18n::fallback(None, req.app_data::<I18nConfig>())
+                }),
             })
-            // Get the first requested locale we support
-            .find(|lang| langs.iter().any(|l| l.0 == &lang.to_string()))
-            .unwrap_or("en");
-
-        match langs.iter().find(|l| l.0 == lang) {
-            Some(translation) => Ok(I18n {
-                catalog: translation.1.clone(),
-                lang: translation.0,
-            }),
-            None => Err(MissingTranslationsError(lang.to_owned()).into()),
+        }))
+    }
+}
+
+/// Calls `f` with the translations of the application (`Translations`, or a snapshot of
+/// `ReloadableTranslations`) and its `I18nConfig`, if there are translations.
+fn with_translations<T>(
+    req: &HttpRequest,
+    f: impl FnOnce(&Translations, Option<&I18nConfig>) -> T,
+) -> Option<T> {
+    let config = req.app_data::<I18nConfig>();
+    if let Some(langs) = req.app_data::<Translations>() {
+        return Some(f(langs, config));
+    }
+    #[cfg(feature = "gettext")]
+    {
+        if let Some(reloadable) = req.app_data::<ReloadableTranslations>() {
+            return Some(f(&reloadable.snapshot(), config));
         }
     }
+    None
+}
+
+/// A resource switching the language of the user: `POST /lang`, with a form containing the new
+/// language (`lang`), and optionally the page to go back to (`redirect`).
+///
+/// ```rust,ignore
+/// App::new()
+///     .data(translations)
+///     .data(I18nConfig {
+///         cookie: Some("lang".into()),
+///         ..Default::default()
+///     })
+///     .service(rocket_i18n::routes::set_lang_service())
+/// ```
+///
+/// The language must be supported (or be `auto`, to go back to the negotiated language), or the
+/// request fails with a BadRequest status. It is stored in the cookie of `I18nConfig::cookie`
+/// (`lang` if it is not set, but it has to be set for the cookie to be read), and the user is
+/// redirected to `redirect`, or to the page they came from, or to `/`.
+///
+/// Set `I18nConfig::cookie_key` to sign this cookie, so that it can't be forged: the `I18n`
+/// extractor then ignores a cookie whose signature is wrong.
+pub fn set_lang_service() -> Resource {
+    web::resource("/lang").route(web::post().to(set_lang))
+}
+
+fn set_lang(req: HttpRequest, form: String) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let switch = with_translations(&req, |langs, config| {
+        switch_lang(langs, config, &form, header("Referer"), header("Host"))
+    });
+    match switch {
+        Some(Some(switch)) => HttpResponse::SeeOther()
+            .cookie(
+                Cookie::build(switch.cookie, cookie_value(&req, switch.lang))
+                    .path("/")
+                    .permanent()
+                    .same_site(SameSite::Lax)
+                    .finish(),
+            )
+            .header(header::LOCATION, switch.redirect)
+            .finish(),
+        Some(None) => HttpResponse::BadRequest().finish(),
+        None => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+/// The value of the language cookie, signed if `I18nConfig::cookie_key` is set.
+fn cookie_value(req: &HttpRequest, lang: &str) -> String {
+    req.app_data::<I18nConfig>()
+        .map_or_else(|| lang.to_owned(), |config| config.cookie_value(lang))
+}
+
+/// Adds a `Vary: Accept-Language` header to a response if its request used the `I18n`
+/// extractor, so that shared caches don't serve it in other languages.
+///
+/// It is meant to be used as a middleware, with `wrap_fn`:
+///
+/// ```rust,ignore
+/// App::new()
+///     .data(translations)
+///     .wrap_fn(|req, srv| srv.call(req).map(rocket_i18n::vary_accept_language))
+/// ```
+pub fn vary_accept_language<B>(mut res: ServiceResponse<B>) -> ServiceResponse<B> {
+    let negotiated = res.request().extensions().get::<Negotiated>().is_some();
+    let vary = res
+        .headers()
+        .get_all(header::VARY)
+        .filter_map(|v| v.to_str().ok());
+    if negotiated && !varies_on_accept_language(vary) {
+        res.headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept-language"));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing, CatalogBuilder, OnFailure};
+    use actix_web::{test, App};
+    use std::sync::Arc;
+
+    #[test]
+    fn the_language_cookie_is_signed() {
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .cookie_key(&b"0123456789abcdef0123456789abcdef"[..])
+            .build();
+        let mut app = test::init_service(
+            App::new()
+                .data(testing::translations(&["en", "fr"]))
+                .data(config)
+                .service(set_lang_service())
+                .route("/", web::get().to(|i18n: I18n| i18n.lang)),
+        );
+        let req = test::TestRequest::post()
+            .uri("/lang")
+            .set_payload("lang=fr")
+            .to_request();
+        let res = test::call_service(&mut app, req);
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        let cookie = res.response().cookies().find(|c| c.name() == "lang").unwrap();
+        assert_ne!(cookie.value(), "fr");
+
+        let mut lang = |cookie: Cookie| {
+            let req = test::TestRequest::get().uri("/").cookie(cookie).to_request();
+            test::read_response(&mut app, req)
+        };
+        assert_eq!(lang(cookie.into_owned()), "fr");
+        assert_eq!(lang(Cookie::new("lang", "fr")), "en");
+    }
+
+    #[test]
+    fn the_user_resolver_comes_before_accept_language() {
+        let translations = testing::translations(&["en", "fr", "de"]).with_user_resolver(|req| {
+            let lang = req.cookie("session").map(|s| if s == "42" { "de" } else { "fr" });
+            async move { lang.map(str::to_owned) }
+        });
+
+        let lang = |cookie: Option<&str>| {
+            let mut req = testing::actix_request(translations.clone(), "en");
+            if let Some(cookie) = cookie {
+                req = req.header("Cookie", cookie);
+            }
+            I18n::extract(&req.to_http_request()).wait().unwrap().lang
+        };
+        assert_eq!(lang(Some("session=42")), "de");
+        assert_eq!(lang(Some("session=7")), "fr");
+        assert_eq!(lang(None), "en");
+    }
+
+    #[test]
+    fn requests_keep_the_fallback_chain_of_their_language() {
+        let translations = Translations::from(vec![
+            ("en", CatalogBuilder::new().msg("Thanks", "Thanks!").build()),
+            ("es", CatalogBuilder::new().msg("Hello", "Hola").build()),
+            ("pt", CatalogBuilder::new().msg("Bye", "Tchau").build()),
+        ])
+        .with_fallback_chain("gl", &["es", "pt", "en"]);
+        let req = testing::actix_request(translations.clone(), "gl").to_http_request();
+        let gl = I18n::extract(&req).wait().unwrap();
+        assert_eq!(gl.lang, "es");
+        assert_eq!(gl.catalog.gettext("Hello"), "Hola");
+        assert_eq!(gl.catalog.gettext("Bye"), "Tchau");
+        assert_eq!(gl.catalog.gettext("Thanks"), "Thanks!");
+
+        let req = testing::actix_request(translations, "es").to_http_request();
+        assert_eq!(I18n::extract(&req).wait().unwrap().catalog.gettext("Bye"), "Bye");
+    }
+
+    #[test]
+    fn the_default_extractor_never_fails() {
+        let req = test::TestRequest::with_header("Accept-Language", "fr").to_http_request();
+        assert!(I18n::extract(&req).wait().is_err());
+        assert_eq!(DefaultI18n::extract(&req).wait().ok().unwrap().lang, "en");
+
+        let req = testing::actix_request(testing::translations(&["de", "fr"]), "fr");
+        let default = DefaultI18n::extract(&req.to_http_request()).wait();
+        assert_eq!(default.ok().unwrap().lang, "fr");
+    }
+
+    #[test]
+    fn failures_are_answered_as_configured() {
+        let response = |config: Option<I18nConfig>| {
+            let req = test::TestRequest::with_header("Accept", "application/json");
+            let req = match config {
+                Some(config) => req.data(config),
+                None => req,
+            };
+            let error = I18n::extract(&req.to_http_request()).wait().err().unwrap();
+            error.as_response_error().error_response()
+        };
+        assert_eq!(response(None).status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let config = I18nConfig::builder().on_failure(OnFailure::Status(406)).build();
+        assert_eq!(response(Some(config)).status(), StatusCode::NOT_ACCEPTABLE);
+
+        let custom = OnFailure::Custom(Arc::new(|lang, _| FailureResponse {
+            status: 503,
+            content_type: "text/plain".into(),
+            body: format!("{:?}", lang),
+        }));
+        let res = response(Some(I18nConfig::builder().on_failure(custom).build()));
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get("Content-Type").unwrap(), "text/plain");
+    }
 }
@@ -0,0 +1,378 @@
+use std::{
+    error::Error,
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    sync::Arc,
+};
+
+#[cfg(feature = "gettext")]
+use crate::ReloadableTranslations;
+use crate::{
+    negotiation::{cookie_value, negotiate, varies_on_accept_language, RequestInfo},
+    DefaultI18n, FailureResponse, I18n, I18nConfig, Translations,
+};
+
+use actix_web_4::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderValue},
+        StatusCode,
+    },
+    web::Data,
+    FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+
+/// Marks the requests that used the `I18n` extractor, for `VaryAcceptLanguage`.
+struct Negotiated;
+
+#[derive(Debug)]
+pub struct MissingTranslationsError {
+    lang: String,
+    response: FailureResponse,
+}
+
+impl fmt::Display for MissingTranslationsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not find translations for {}", self.lang)
+    }
+}
+
+impl Error for MissingTranslationsError {}
+
+impl ResponseError for MissingTranslationsError {
+    fn status_code(&self) -> StatusCode {
+        status_code(&self.response)
+    }
+
+    /// The response configured in `I18nConfig::on_failure`: by default, an InternalServerError
+    /// listing the available languages, as JSON if the request asked for it, and as HTML
+    /// otherwise.
+    fn error_response(&self) -> HttpResponse {
+        failure_response(&self.response)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingStateError {
+    response: FailureResponse,
+}
+
+impl fmt::Display for MissingStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not retrieve state")
+    }
+}
+
+impl Error for MissingStateError {}
+
+impl ResponseError for MissingStateError {
+    fn status_code(&self) -> StatusCode {
+        status_code(&self.response)
+    }
+
+    /// The response configured in `I18nConfig::on_failure`: by default, an empty
+    /// InternalServerError.
+    fn error_response(&self) -> HttpResponse {
+        failure_response(&self.response)
+    }
+}
+
+fn status_code(response: &FailureResponse) -> StatusCode {
+    StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn failure_response(response: &FailureResponse) -> HttpResponse {
+    let mut builder = HttpResponse::build(status_code(response));
+    if !response.content_type.is_empty() {
+        builder.content_type(response.content_type.as_str());
+    }
+    builder.body(response.body.clone())
+}
+
+/// Translations are read from the `web::Data<Translations>` of the application (or its
+/// `web::Data<ReloadableTranslations>`), and options from its `web::Data<I18nConfig>`, if any.
+///
+/// ```rust,ignore
+/// App::new()
+///     .app_data(web::Data::new(rocket_i18n::i18n("your-domain", vec!["en", "fr"])))
+///     .route("/", web::get().to(|i18n: I18n| async move { i18n!(i18n.catalog, "Hello") }))
+/// ```
+impl FromRequest for I18n {
+    type Error = actix_web_4::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        let user = translations(&req).and_then(|langs| {
+            langs.resolve_user(|| {
+                req.headers()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                    })
+                    .collect()
+            })
+        });
+        Box::pin(async move {
+            let user = match user {
+                Some(user) => user.await,
+                None => None,
+            };
+            from_request(&req, user)
+        })
+    }
+}
+
+impl FromRequest for DefaultI18n {
+    type Error = actix_web_4::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        let i18n = I18n::from_request(&req, payload);
+        Box::pin(async move {
+            Ok(match i18n.await {
+                Ok(i18n) => DefaultI18n(i18n),
+                Err(_) => {
+                    let config = req.app_data::<Data<I18nConfig>>().map(Data::get_ref);
+                    DefaultI18n::fallback(translations(&req).as_deref(), config)
+                }
+            })
+        })
+    }
+}
+
+/// The translations of the application (`Translations`, or a snapshot of
+/// `ReloadableTranslations`), if any.
+fn translations(req: &HttpRequest) -> Option<Arc<Translations>> {
+    let managed = req
+        .app_data::<Data<Translations>>()
+        .map(|l| l.clone().into_inner());
+    #[cfg(feature = "gettext")]
+    let snapshot = || {
+        req.app_data::<Data<ReloadableTranslations>>()
+            .map(|r| r.snapshot())
+    };
+    #[cfg(not(feature = "gettext"))]
+    let snapshot = || None;
+    managed.or_else(snapshot)
+}
+
+fn from_request(req: &HttpRequest, user: Option<String>) -> Result<I18n, actix_web_4::Error> {
+    let config = req.app_data::<Data<I18nConfig>>().map(Data::get_ref);
+    let langs = match translations(req) {
+        Some(langs) => langs,
+        None => return failure(req, config, None, None),
+    };
+
+    let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let cookies = |name: &str| {
+        req.headers()
+            .get_all(header::COOKIE)
+            .filter_map(|v| v.to_str().ok())
+            .find_map(|cookies| cookie_value(cookies, name))
+            .map(str::to_owned)
+    };
+    let request = RequestInfo::new(
+        config,
+        &header,
+        &cookies,
+        Some(req.query_string()),
+        req.path(),
+    )
+    .with_client_ip(req.peer_addr().map(|addr| addr.ip()))
+    .with_user(user);
+    let lang = negotiate(&langs, config, &request);
+
+    match langs.for_request(lang) {
+        Some(i18n) => {
+            req.extensions_mut().insert(Negotiated);
+            Ok(i18n)
+        }
+        None => failure(req, config, Some(&langs), Some(lang)),
+    }
+}
+
+/// Handles a request that can't be translated, as configured in `I18nConfig::on_failure`:
+/// `lang` is the negotiated language, or `None` if there are no translations.
+fn failure(
+    req: &HttpRequest,
+    config: Option<&I18nConfig>,
+    langs: Option<&Translations>,
+    lang: Option<&str>,
+) -> Result<I18n, actix_web_4::Error> {
+    let available = langs.map_or_else(Vec::new, |langs| langs.iter().map(|l| l.0).collect());
+    let accept = req.headers().get("Accept").and_then(|v| v.to_str().ok());
+    match FailureResponse::for_request(config, accept, lang, &available) {
+        Some(response) => Err(failure_error(lang, response)),
+        None => {
+            req.extensions_mut().insert(Negotiated);
+            Ok(DefaultI18n::fallback(langs, config).0)
+        }
+    }
+}
+
+fn failure_error(lang: Option<&str>, response: FailureResponse) -> actix_web_4::Error {
+    match lang {
+        Some(lang) => MissingTranslationsError {
+            lang: lang.to_owned(),
+            response,
+        }
+        .into(),
+        None => MissingStateError { response }.into(),
+    }
+}
+
+/// A middleware adding a `Vary: Accept-Language` header to the responses of the requests that
+/// used the `I18n` extractor, so that shared caches don't serve them in other languages.
+///
+/// ```rust,ignore
+/// App::new()
+///     .app_data(web::Data::new(translations))
+///     .wrap(rocket_i18n::VaryAcceptLanguage)
+/// ```
+pub struct VaryAcceptLanguage;
+
+impl<S, B> Transform<S, ServiceRequest> for VaryAcceptLanguage
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web_4::Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web_4::Error;
+    type Transform = VaryAcceptLanguageMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(VaryAcceptLanguageMiddleware { service }))
+    }
+}
+
+/// The service created by `VaryAcceptLanguage`.
+pub struct VaryAcceptLanguageMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for VaryAcceptLanguageMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web_4::Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web_4::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let response = self.service.call(req);
+        Box::pin(async move {
+            let mut res = response.await?;
+            let negotiated = res.request().extensions().get::<Negotiated>().is_some();
+            let vary = res
+                .headers()
+                .get_all(header::VARY)
+                .filter_map(|v| v.to_str().ok());
+            if negotiated && !varies_on_accept_language(vary) {
+                res.headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("accept-language"));
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{testing, OnFailure};
+    use actix_web_4::test::TestRequest;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn the_language_is_negotiated() {
+        let langs = Data::new(testing::translations(&["en", "fr"]));
+        let lang = |req: TestRequest| {
+            let req = req.app_data(langs.clone()).to_http_request();
+            block_on(I18n::extract(&req)).unwrap().lang
+        };
+        assert_eq!(lang(TestRequest::default()), "en");
+        assert_eq!(
+            lang(TestRequest::default().insert_header(("Accept-Language", "fr-FR, en;q=0.5"))),
+            "fr"
+        );
+
+        let config = Data::new(I18nConfig::builder().cookie("lang").build());
+        let req = TestRequest::default()
+            .insert_header(("Accept-Language", "en"))
+            .insert_header(("Cookie", "theme=dark; lang=fr"))
+            .app_data(config);
+        assert_eq!(lang(req), "fr");
+    }
+
+    #[test]
+    fn missing_translations_are_answered_as_configured() {
+        let req = TestRequest::default().to_http_request();
+        let error = block_on(I18n::extract(&req)).err().unwrap();
+        assert_eq!(
+            error.as_response_error().status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        let config = I18nConfig::builder()
+            .on_failure(OnFailure::Status(406))
+            .build();
+        let req = TestRequest::default()
+            .app_data(Data::new(config))
+            .to_http_request();
+        let error = block_on(I18n::extract(&req)).err().unwrap();
+        assert_eq!(error.as_response_error().status_code(), StatusCode::NOT_ACCEPTABLE);
+
+        let i18n = block_on(DefaultI18n::extract(&req)).unwrap();
+        assert_eq!(i18n.0.lang, "en");
+    }
+
+    #[test]
+    fn translated_responses_vary_on_accept_language() {
+        use actix_web_4::{rt::System, test, web, App};
+
+        System::new().block_on(async {
+            let app = test::init_service(
+                App::new()
+                    .app_data(Data::new(testing::translations(&["en", "fr"])))
+                    .wrap(VaryAcceptLanguage)
+                    .route("/", web::get().to(|i18n: I18n| async move { i18n.lang }))
+                    .route("/hello", web::get().to(|| async { "Hello" }))
+                    .route(
+                        "/cached",
+                        web::get().to(|_: I18n| async {
+                            HttpResponse::Ok()
+                                .insert_header((header::VARY, "Accept-Language"))
+                                .finish()
+                        }),
+                    ),
+            )
+            .await;
+            let vary = |uri: &'static str| {
+                let req = test::TestRequest::get().uri(uri).to_request();
+                let res = test::call_service(&app, req);
+                async move {
+                    let res = res.await;
+                    let vary = res.headers().get_all(header::VARY);
+                    vary.map(|v| v.to_str().unwrap().to_owned()).collect::<Vec<_>>()
+                }
+            };
+            assert_eq!(vary("/").await, vec!["accept-language"]);
+            assert!(vary("/hello").await.is_empty());
+            assert_eq!(vary("/cached").await, vec!["Accept-Language"]);
+        });
+    }
+}
@@ -0,0 +1,200 @@
+use std::{convert::Infallible, sync::Arc};
+
+#[cfg(feature = "gettext")]
+use crate::ReloadableTranslations;
+use crate::{
+    error_body::ErrorBody,
+    negotiation::{cookie_value, negotiate, RequestInfo},
+    DefaultI18n, I18n, I18nConfig, Translations,
+};
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+/// The rejection of the `I18n` extractor: an InternalServerError response.
+///
+/// When the negotiated language has no catalog, it lists the available languages, as JSON if the
+/// request asked for it, and as HTML otherwise.
+#[derive(Debug)]
+pub struct I18nRejection {
+    /// `None` if there are no translations in the extensions of the request.
+    body: Option<ErrorBody>,
+}
+
+impl IntoResponse for I18nRejection {
+    fn into_response(self) -> Response {
+        match self.body {
+            None => (StatusCode::INTERNAL_SERVER_ERROR, "Could not retrieve state").into_response(),
+            Some(body) => {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(header::CONTENT_TYPE, body.content_type())],
+                    body.content,
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// Translations are read from the extensions of the request, so they should be added with a
+/// layer:
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(Extension(Arc::new(rocket_i18n::i18n("your-domain", vec!["en", "fr"]))));
+/// ```
+///
+/// `Arc<ReloadableTranslations>` and `Arc<I18nConfig>` extensions are used too, if present.
+impl<S: Send + Sync> FromRequestParts<S> for I18n {
+    type Rejection = I18nRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<I18n, I18nRejection> {
+        let langs = translations(parts).ok_or(I18nRejection { body: None })?;
+
+        let user = langs.resolve_user(|| {
+            parts
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                })
+                .collect()
+        });
+        let user = match user {
+            Some(user) => user.await,
+            None => None,
+        };
+
+        let config = parts.extensions.get::<Arc<I18nConfig>>().cloned();
+        let config = config.as_deref();
+        let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+        let cookies = |name: &str| {
+            parts
+                .headers
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .find_map(|cookies| cookie_value(cookies, name))
+                .map(str::to_owned)
+        };
+        let request = RequestInfo::new(
+            config,
+            &header,
+            &cookies,
+            parts.uri.query(),
+            parts.uri.path(),
+        )
+        .with_user(user);
+        let lang = negotiate(&langs, config, &request);
+
+        match langs.for_request(lang) {
+            Some(i18n) => Ok(i18n),
+            None => {
+                let available = langs.iter().map(|l| l.0).collect::<Vec<_>>();
+                Err(I18nRejection {
+                    body: Some(ErrorBody::new(header("Accept"), &available)),
+                })
+            }
+        }
+    }
+}
+
+/// `None` when the `I18n` extractor would be rejected, instead of an error response.
+impl<S: Send + Sync> OptionalFromRequestParts<S> for I18n {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Option<I18n>, Infallible> {
+        Ok(<I18n as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .ok())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for DefaultI18n {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<DefaultI18n, Infallible> {
+        if let Ok(i18n) = <I18n as FromRequestParts<S>>::from_request_parts(parts, state).await {
+            return Ok(DefaultI18n(i18n));
+        }
+        let config = parts.extensions.get::<Arc<I18nConfig>>().cloned();
+        Ok(DefaultI18n::fallback(
+            translations(parts).as_deref(),
+            config.as_deref(),
+        ))
+    }
+}
+
+/// The translations in the extensions of a request (`Arc<Translations>`, or a snapshot of
+/// `Arc<ReloadableTranslations>`), if any.
+fn translations(parts: &Parts) -> Option<Arc<Translations>> {
+    let managed = parts.extensions.get::<Arc<Translations>>().cloned();
+    #[cfg(feature = "gettext")]
+    let snapshot = || {
+        parts
+            .extensions
+            .get::<Arc<ReloadableTranslations>>()
+            .map(|r| r.snapshot())
+    };
+    #[cfg(not(feature = "gettext"))]
+    let snapshot = || None;
+    managed.or_else(snapshot)
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use axum::http::Request;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// The parts of a request with the given headers and extensions.
+    fn parts(headers: &[(&str, &str)], config: Option<I18nConfig>, translations: bool) -> Parts {
+        let mut req = Request::builder();
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        if translations {
+            req = req.extension(Arc::new(testing::translations(&["en", "fr", "de"])));
+        }
+        if let Some(config) = config {
+            req = req.extension(Arc::new(config));
+        }
+        req.body(()).unwrap().into_parts().0
+    }
+
+    fn extract(mut parts: Parts) -> Result<I18n, I18nRejection> {
+        block_on(<I18n as FromRequestParts<()>>::from_request_parts(&mut parts, &()))
+    }
+
+    #[test]
+    fn the_language_is_negotiated_from_the_request() {
+        let accept = [("Accept-Language", "fr")];
+        assert_eq!(extract(parts(&accept, None, true)).unwrap().lang, "fr");
+
+        let config = I18nConfig::builder().cookie("lang").build();
+        let headers = [("Accept-Language", "fr"), ("Cookie", "session=1; lang=de")];
+        assert_eq!(extract(parts(&headers, Some(config), true)).unwrap().lang, "de");
+    }
+
+    #[test]
+    fn requests_without_translations_are_rejected() {
+        let response = extract(parts(&[], None, false)).err().unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let mut parts = parts(&[("Accept-Language", "fr")], None, false);
+        let fallback = <DefaultI18n as FromRequestParts<()>>::from_request_parts(&mut parts, &());
+        assert_eq!(block_on(fallback).unwrap().0.lang, "en");
+    }
+}
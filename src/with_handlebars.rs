@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson,
+};
+
+#[cfg(feature = "format")]
+use crate::format;
+use crate::{I18n, Translations};
+
+/// The key of the render data containing the language of the request.
+const LANG_KEY: &str = "lang";
+
+/// Registers the `t` helper in a Handlebars registry, to translate messages in its templates.
+///
+/// ```rust,ignore
+/// let mut handlebars = Handlebars::new();
+/// rocket_i18n::register_handlebars(&mut handlebars, rocket_i18n::i18n("plume", vec!["en", "fr"]));
+///
+/// // In a handler
+/// handlebars.render("index", &i18n.handlebars_data(json!({ "count": 42 })))
+/// ```
+///
+/// ```text
+/// {{t "Hello, world!"}}
+/// {{t "One new message" "{0} new messages" count}}
+/// ```
+///
+/// Like `I18n::plural`, the plural form replaces `{0}` with the number. The language is read from
+/// the `lang` field of the render data, that `I18n::handlebars_data` adds. Messages are left
+/// untranslated if it is missing, or not supported.
+///
+/// With the `format` feature, a `currency` helper formats amounts of money in this language, like
+/// `I18n::format_currency`: `{{currency price "EUR"}}`.
+///
+/// Requires the `handlebars` feature.
+pub fn register_handlebars(handlebars: &mut Handlebars<'_>, translations: Translations) {
+    handlebars.register_helper(
+        "t",
+        Box::new(TranslateHelper {
+            translations: Arc::new(translations),
+        }),
+    );
+    #[cfg(feature = "format")]
+    handlebars.register_helper("currency", Box::new(CurrencyHelper));
+}
+
+impl I18n {
+    /// Adds the language of the request to the data used to render a Handlebars template, so
+    /// that the `t` helper translates messages in this language (see `register_handlebars`).
+    ///
+    /// `data` should be an object: anything else is replaced.
+    ///
+    /// Requires the `handlebars` feature.
+    pub fn handlebars_data(&self, data: JsonValue) -> JsonValue {
+        let mut data = match data {
+            JsonValue::Object(data) => data,
+            _ => Default::default(),
+        };
+        data.insert(LANG_KEY.to_owned(), JsonValue::from(self.lang));
+        JsonValue::Object(data)
+    }
+}
+
+struct TranslateHelper {
+    translations: Arc<Translations>,
+}
+
+impl HelperDef for TranslateHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let param = |index| {
+            h.param(index)
+                .and_then(|p| p.value().as_str())
+                .ok_or(RenderErrorReason::ParamNotFoundForIndex("t", index))
+        };
+        let msg = param(0)?;
+        let i18n = request_lang(ctx).and_then(|lang| self.translations.for_request(lang));
+
+        let translated = if h.params().len() < 3 {
+            match i18n {
+                Some(i18n) => i18n.catalog.gettext(msg).to_owned(),
+                None => msg.to_owned(),
+            }
+        } else {
+            let plural = param(1)?;
+            let n = h
+                .param(2)
+                .and_then(|p| p.value().as_u64())
+                .ok_or(RenderErrorReason::ParamTypeMismatchForName(
+                    "t",
+                    "2".to_owned(),
+                    "u64".to_owned(),
+                ))?;
+            match i18n {
+                Some(i18n) => i18n.plural(msg, plural, n),
+                None if n == 1 => msg.replace("{0}", "1"),
+                None => plural.replace("{0}", &n.to_string()),
+            }
+        };
+        Ok(ScopedJson::Derived(JsonValue::String(translated)))
+    }
+}
+
+#[cfg(feature = "format")]
+struct CurrencyHelper;
+
+#[cfg(feature = "format")]
+impl HelperDef for CurrencyHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let amount = h.param(0).and_then(|p| p.value().as_f64()).ok_or(
+            RenderErrorReason::ParamTypeMismatchForName(
+                "currency",
+                "0".to_owned(),
+                "f64".to_owned(),
+            ),
+        )?;
+        let currency = h
+            .param(1)
+            .and_then(|p| p.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("currency", 1))?;
+        let formatted = format::format_currency(request_lang(ctx).unwrap_or(""), amount, currency);
+        Ok(ScopedJson::Derived(JsonValue::String(formatted)))
+    }
+}
+
+/// The language of the request, added to the render data by `I18n::handlebars_data`.
+fn request_lang(ctx: &Context) -> Option<&str> {
+    ctx.data().get(LANG_KEY).and_then(JsonValue::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CatalogBuilder;
+
+    /// A registry with the `t` helper, translating in French.
+    fn handlebars() -> Handlebars<'static> {
+        let fr = CatalogBuilder::new()
+            .msg("Hello", "Bonjour")
+            .plural("One message", "{0} messages", &["Un message", "{0} messages"])
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        let mut handlebars = Handlebars::new();
+        register_handlebars(&mut handlebars, Translations::from(vec![("fr", fr)]));
+        handlebars
+    }
+
+    #[test]
+    fn messages_are_translated_in_the_language_of_the_request() {
+        let handlebars = handlebars();
+        let template = r#"{{t "Hello"}}, {{t "One message" "{0} messages" count}}"#;
+        let render = |lang| {
+            let i18n = I18n::new_for_tests(lang, CatalogBuilder::new().build());
+            let mut data = i18n.handlebars_data(JsonValue::Null);
+            data["count"] = JsonValue::from(0);
+            handlebars.render_template(template, &data)
+        };
+        assert_eq!(render("fr").unwrap(), "Bonjour, Un message");
+        assert_eq!(render("de").unwrap(), "Hello, 0 messages");
+
+        assert!(handlebars.render_template(template, &JsonValue::Null).is_err());
+        let mut without_lang = JsonValue::Object(Default::default());
+        without_lang["count"] = JsonValue::from(2);
+        let untranslated = handlebars.render_template(template, &without_lang);
+        assert_eq!(untranslated.unwrap(), "Hello, 2 messages");
+    }
+
+    #[cfg(feature = "format")]
+    #[test]
+    fn amounts_are_formatted_in_the_language_of_the_request() {
+        let handlebars = handlebars();
+        let render = |lang| {
+            let i18n = I18n::new_for_tests(lang, CatalogBuilder::new().build());
+            let mut data = i18n.handlebars_data(JsonValue::Null);
+            data["price"] = JsonValue::from(1234.5);
+            handlebars.render_template(r#"{{currency price "EUR"}}"#, &data)
+        };
+        assert_eq!(render("fr").unwrap(), "1\u{202f}234,50\u{a0}€");
+        assert_eq!(render("en").unwrap(), "€1,234.50");
+        assert!(handlebars.render_template(r#"{{currency "1" "EUR"}}"#, &JsonValue::Null).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use maud::PreEscaped;
+
+use crate::I18n;
+
+/// Translations for Maud templates.
+///
+/// Text inserted in `html!` blocks is escaped by Maud, so plain translations can be used as is:
+/// `(i18n.catalog.gettext("Hello"))`. These methods are for messages containing HTML markup.
+impl I18n {
+    /// Translates a message containing HTML markup, to insert it in a Maud template without
+    /// escaping it.
+    ///
+    /// ```rust,ignore
+    /// html! {
+    ///     p { (i18n.markup("Read the <a href=\"/docs\">documentation</a>")) }
+    /// }
+    /// ```
+    ///
+    /// Requires the `maud` feature.
+    pub fn markup<'a>(&'a self, msg_id: &'a str) -> PreEscaped<&'a str> {
+        PreEscaped(self.catalog.gettext(msg_id))
+    }
+
+    /// Same as `markup`, but for a message with a plural form: `{0}` is replaced by the number,
+    /// like with `plural`.
+    ///
+    /// Requires the `maud` feature.
+    pub fn plural_markup(&self, msg_id: &str, msg_id_plural: &str, n: u64) -> PreEscaped<String> {
+        PreEscaped(self.plural(msg_id, msg_id_plural, n))
+    }
+}
+
+/// Works the same way as `i18n_escaped`, but returns markup that Maud inserts as is: the
+/// translated string may contain HTML, and the formatting arguments are escaped.
+///
+/// ```rust,ignore
+/// html! {
+///     p { (t_html!(i18n.catalog, "Hello <b>{0}</b>!"; user.name)) }
+/// }
+/// ```
+///
+/// Requires the `maud` feature.
+#[macro_export]
+macro_rules! t_html {
+    ($( $args:tt )+) => {
+        $crate::__maud::PreEscaped(i18n_escaped!($( $args )+).to_string())
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CatalogBuilder;
+    use maud::html;
+
+    #[test]
+    fn markup_is_inserted_without_being_escaped() {
+        let files = ["<b>Un</b> fichier", "<b>{0}</b> fichiers"];
+        let catalog = CatalogBuilder::new()
+            .msg("Read the <a>docs</a>", "Lisez la <a>doc</a>")
+            .plural("<b>One</b> file", "<b>{0}</b> files", &files)
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        let i18n = I18n::new_for_tests("fr", catalog);
+        let page = html! {
+            p { (i18n.markup("Read the <a>docs</a>")) }
+            p { (i18n.catalog.gettext("Read the <a>docs</a>")) }
+            p { (i18n.plural_markup("<b>One</b> file", "<b>{0}</b> files", 3)) }
+        };
+        assert_eq!(
+            page.into_string(),
+            "<p>Lisez la <a>doc</a></p>\
+             <p>Lisez la &lt;a&gt;doc&lt;/a&gt;</p>\
+             <p><b>3</b> fichiers</p>"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+#[cfg(feature = "gettext")]
+use crate::ReloadableTranslations;
+use crate::{
+    error_body::ErrorBody,
+    negotiation::{cookie_value, negotiate, RequestInfo},
+    DefaultI18n, I18n, I18nConfig, Translations,
+};
+
+use poem::{
+    http::{header, StatusCode},
+    Error, FromRequest, Request, RequestBody, Response,
+};
+
+/// Translations are read from the data of the request, so they should be added with a
+/// middleware:
+///
+/// ```rust,ignore
+/// let app = Route::new()
+///     .at("/", get(handler))
+///     .data(Arc::new(rocket_i18n::i18n("your-domain", vec!["en", "fr"])));
+/// ```
+///
+/// `Arc<ReloadableTranslations>` and `Arc<I18nConfig>` data are used too, if present.
+///
+/// If the negotiated language has no catalog, the request fails with an InternalServerError,
+/// listing the available languages as JSON if the request asked for it, and as HTML otherwise.
+impl<'a> FromRequest<'a> for I18n {
+    async fn from_request(req: &'a Request, _: &mut RequestBody) -> poem::Result<I18n> {
+        let langs = translations(req).ok_or_else(|| {
+            Error::from_string(
+                "Could not retrieve state",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+
+        let user = langs.resolve_user(|| {
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                })
+                .collect()
+        });
+        let user = match user {
+            Some(user) => user.await,
+            None => None,
+        };
+
+        let config = req.data::<Arc<I18nConfig>>().map(|c| &**c);
+        let header = |name: &str| req.header(name);
+        let cookies = |name: &str| {
+            req.headers()
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .find_map(|cookies| cookie_value(cookies, name))
+                .map(str::to_owned)
+        };
+        let request = RequestInfo::new(
+            config,
+            &header,
+            &cookies,
+            req.uri().query(),
+            req.uri().path(),
+        )
+        .with_user(user);
+        let lang = negotiate(&langs, config, &request);
+
+        langs.for_request(lang).ok_or_else(|| {
+            let available = langs.iter().map(|l| l.0).collect::<Vec<_>>();
+            let body = ErrorBody::new(header("Accept"), &available);
+            Error::from_response(
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .content_type(body.content_type())
+                    .body(body.content),
+            )
+        })
+    }
+}
+
+impl<'a> FromRequest<'a> for DefaultI18n {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> poem::Result<DefaultI18n> {
+        if let Ok(i18n) = I18n::from_request(req, body).await {
+            return Ok(DefaultI18n(i18n));
+        }
+        let config = req.data::<Arc<I18nConfig>>().map(|c| &**c);
+        Ok(DefaultI18n::fallback(translations(req).as_deref(), config))
+    }
+}
+
+/// The translations in the data of a request (`Arc<Translations>`, or a snapshot of
+/// `Arc<ReloadableTranslations>`), if any.
+fn translations(req: &Request) -> Option<Arc<Translations>> {
+    let managed = req.data::<Arc<Translations>>().cloned();
+    #[cfg(feature = "gettext")]
+    let snapshot = || req.data::<Arc<ReloadableTranslations>>().map(|r| r.snapshot());
+    #[cfg(not(feature = "gettext"))]
+    let snapshot = || None;
+    managed.or_else(snapshot)
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn request(cookie: &str, config: Option<I18nConfig>, translations: bool) -> Request {
+        let mut req = Request::builder()
+            .header("Accept-Language", "fr")
+            .header("Cookie", cookie)
+            .finish();
+        if translations {
+            req.set_data(Arc::new(testing::translations(&["en", "fr", "de"])));
+        }
+        if let Some(config) = config {
+            req.set_data(Arc::new(config));
+        }
+        req
+    }
+
+    #[test]
+    fn the_language_is_negotiated_from_the_request() {
+        let lang = |req: Request| {
+            let i18n = block_on(I18n::from_request(&req, &mut RequestBody::default()));
+            i18n.map(|i18n| i18n.lang)
+        };
+        assert_eq!(lang(request("lang=de", None, true)).unwrap(), "fr");
+        let config = I18nConfig::builder().cookie("lang").build();
+        assert_eq!(lang(request("lang=de", Some(config), true)).unwrap(), "de");
+
+        let error = lang(request("", None, false)).err().unwrap();
+        assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let req = request("", None, false);
+        let fallback = block_on(DefaultI18n::from_request(&req, &mut RequestBody::default()));
+        assert_eq!(fallback.unwrap().0.lang, "en");
+    }
+}
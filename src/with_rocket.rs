@@ -1,38 +1,606 @@
-use crate::{ACCEPT_LANG, I18n, Translations};
+use std::{
+    error::Error,
+    fmt,
+    io::{Cursor, Read},
+};
+
+#[cfg(feature = "gettext")]
+use crate::{leak, Loader, ReloadableTranslations};
+use crate::{
+    error_body::ErrorBody,
+    negotiation::{negotiate, varies_on_accept_language, RequestInfo},
+    switch::switch_lang,
+    user, DefaultI18n, I18n, I18nConfig, Translations,
+};
 
+#[cfg(feature = "gettext")]
+use log::error;
+#[cfg(feature = "gettext")]
 use rocket::{
-    http::Status,
+    config::{Config, Value},
+    Rocket,
+};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    handler,
+    http::{Cookie, Method, SameSite, Status},
     request::{self, FromRequest},
-    Outcome, Request, State,
+    response::{self, Redirect, Response},
+    Catcher, Data, Outcome, Request, Route, State,
 };
 
+/// The language negotiated for a request, cached by the `I18n` guard for `I18nFairing`.
+struct Negotiated(Option<&'static str>);
+
+/// The maximum size of the form sent to `set_lang`.
+const FORM_LIMIT: u64 = 1024;
+
+/// Why the `I18n` guard failed.
+///
+/// In both cases, the request fails with an InternalServerError, that `catcher` can turn into a
+/// meaningful error page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardError {
+    /// Neither `Translations` nor `ReloadableTranslations` are managed by Rocket.
+    MissingState,
+    /// The negotiated language has no catalog.
+    MissingTranslations(&'static str),
+}
+
+impl fmt::Display for GuardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuardError::MissingState => write!(
+                f,
+                "Couldn't retrieve translations because they are not managed by Rocket"
+            ),
+            GuardError::MissingTranslations(lang) => {
+                write!(f, "Could not find translations for {}", lang)
+            }
+        }
+    }
+}
+
+impl Error for GuardError {}
+
 impl<'a, 'r> FromRequest<'a, 'r> for I18n {
+    type Error = GuardError;
+
+    fn from_request(req: &'a Request) -> request::Outcome<I18n, GuardError> {
+        with_translations(req, |langs| {
+            let config = req.guard::<State<I18nConfig>>().succeeded().map(|c| c.inner());
+            let header = |name: &str| req.headers().get_one(name);
+            let cookies = |name: &str| req.cookies().get(name).map(|c| c.value().to_owned());
+            let mut request = RequestInfo::new(
+                config,
+                &header,
+                &cookies,
+                req.uri().query(),
+                req.uri().path(),
+            )
+            .with_client_ip(req.client_ip())
+            .with_user(langs.resolve_user(|| headers(req)).and_then(user::block_on));
+            // With a key, the language switch endpoint stores the language in a private cookie
+            if let Some(name) = private_cookie(config) {
+                let cookie = req.cookies().get_private(name).map(|c| c.value().to_owned());
+                request = request.with_cookie(cookie);
+            }
+            let lang = negotiate(langs, config, &request);
+
+            match langs.for_request(lang) {
+                Some(i18n) => {
+                    req.local_cache(|| Negotiated(Some(i18n.lang)));
+                    Outcome::Success(i18n)
+                }
+                None => Outcome::Failure((
+                    Status::InternalServerError,
+                    GuardError::MissingTranslations(lang),
+                )),
+            }
+        })
+        .unwrap_or(Outcome::Failure((
+            Status::InternalServerError,
+            GuardError::MissingState,
+        )))
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for DefaultI18n {
     type Error = ();
 
-    fn from_request(req: &'a Request) -> request::Outcome<I18n, ()> {
-        let langs = &*req
-            .guard::<State<Translations>>()
-            .expect("Couldn't retrieve translations because they are not managed by Rocket.");
-
-        let lang = req
-            .headers()
-            .get_one(ACCEPT_LANG)
-            .unwrap_or("en")
-            .split(",")
-            .filter_map(|lang| lang
-                // Get the locale, not the country code
-                .split(|c| c == '-' || c == ';')
-                .nth(0))
-            // Get the first requested locale we support
-            .find(|lang| langs.iter().any(|l| l.0 == &lang.to_string()))
-            .unwrap_or("en");
-
-        match langs.iter().find(|l| l.0 == lang) {
-            Some(translation) => Outcome::Success(I18n {
-                catalog: translation.1.clone(),
-                lang: translation.0,
-            }),
-            None => Outcome::Failure((Status::InternalServerError, ())),
+    fn from_request(req: &'a Request) -> request::Outcome<DefaultI18n, ()> {
+        let config = req.guard::<State<I18nConfig>>().succeeded().map(|c| c.inner());
+        let i18n = with_translations(req, |langs| match req.guard::<I18n>() {
+            Outcome::Success(i18n) => DefaultI18n(i18n),
+            _ => DefaultI18n::fallback(Some(langs), config),
+        })
+        .unwrap_or_else(|| DefaultI18n::fallback(None, config));
+        Outcome::Success(i18n)
+    }
+}
+
+/// The headers of a request, for the resolver of `Translations::with_user_resolver`.
+///
+/// Its cookies are read from the jar of the request (where local requests only have them), and
+/// given to the resolver as a single `Cookie` header.
+fn headers(req: &Request) -> Vec<(String, String)> {
+    let mut headers: Vec<_> = req
+        .headers()
+        .iter()
+        .filter(|header| !header.name().eq_ignore_ascii_case("Cookie"))
+        .map(|header| (header.name().to_owned(), header.value().to_owned()))
+        .collect();
+    let cookies: Vec<_> = req
+        .cookies()
+        .iter()
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect();
+    if !cookies.is_empty() {
+        headers.push(("Cookie".to_owned(), cookies.join("; ")));
+    }
+    headers
+}
+
+/// Calls `f` with the translations managed by Rocket (`Translations`, or a snapshot of
+/// `ReloadableTranslations`), if any.
+fn with_translations<T>(req: &Request, f: impl FnOnce(&Translations) -> T) -> Option<T> {
+    if let Some(langs) = req.guard::<State<Translations>>().succeeded() {
+        return Some(f(langs.inner()));
+    }
+    #[cfg(feature = "gettext")]
+    {
+        if let Some(reloadable) = req.guard::<State<ReloadableTranslations>>().succeeded() {
+            return Some(f(&reloadable.snapshot()));
+        }
+    }
+    None
+}
+
+/// A route switching the language of the user: `POST /lang`, with a form containing the new
+/// language (`lang`), and optionally the page to go back to (`redirect`).
+///
+/// ```rust,ignore
+/// rocket::ignite()
+///     .manage(translations)
+///     .manage(I18nConfig {
+///         cookie: Some("lang".into()),
+///         ..Default::default()
+///     })
+///     .mount("/", vec![rocket_i18n::routes::set_lang()])
+/// ```
+///
+/// ```html
+/// <form method="post" action="/lang">
+///     <input type="hidden" name="redirect" value="/posts/1">
+///     <select name="lang">…</select>
+/// </form>
+/// ```
+///
+/// The language must be supported (or be `auto`, to go back to the negotiated language), or the
+/// request fails with a BadRequest status. It is stored in the cookie of `I18nConfig::cookie`
+/// (`lang` if it is not set, but it has to be set for the cookie to be read), and the user is
+/// redirected to `redirect`, or to the page they came from, or to `/`.
+///
+/// Set `I18nConfig::cookie_key` to make this cookie a private cookie, encrypted with the
+/// `secret_key` of Rocket, so that it can't be forged: the `I18n` guard then ignores a cookie that
+/// is not.
+pub fn set_lang() -> Route {
+    Route::new(Method::Post, "/lang", set_lang_handler)
+}
+
+fn set_lang_handler<'r>(req: &'r Request, data: Data) -> handler::Outcome<'r> {
+    let mut form = String::new();
+    if data.open().take(FORM_LIMIT).read_to_string(&mut form).is_err() {
+        return handler::Outcome::failure(Status::BadRequest);
+    }
+    let config = req.guard::<State<I18nConfig>>().succeeded().map(|c| c.inner());
+    let switch = with_translations(req, |langs| {
+        switch_lang(
+            langs,
+            config,
+            &form,
+            req.headers().get_one("Referer"),
+            req.headers().get_one("Host"),
+        )
+    });
+    match switch {
+        Some(Some(switch)) => {
+            let cookie = Cookie::build(switch.cookie, switch.lang)
+                .path("/")
+                .permanent()
+                .same_site(SameSite::Lax)
+                .finish();
+            if private_cookie(config).is_some() {
+                req.cookies().add_private(cookie);
+            } else {
+                req.cookies().add(cookie);
+            }
+            handler::Outcome::from(req, Redirect::to(switch.redirect))
+        }
+        Some(None) => handler::Outcome::failure(Status::BadRequest),
+        None => handler::Outcome::failure(Status::InternalServerError),
+    }
+}
+
+/// The name of the language cookie, if it is a private cookie (when `I18nConfig::cookie_key` is
+/// set).
+fn private_cookie(config: Option<&I18nConfig>) -> Option<&str> {
+    config
+        .filter(|c| c.cookie_key.is_some())
+        .and_then(|c| c.cookie.as_deref())
+}
+
+/// A fairing adding a `Content-Language` header to the responses of the requests that used the
+/// `I18n` guard, so that clients and caches know in which language they are, and a
+/// `Vary: Accept-Language` header, so that shared caches don't serve them in other languages.
+///
+/// ```rust,ignore
+/// rocket::ignite()
+///     .manage(translations)
+///     .attach(rocket_i18n::I18nFairing)
+/// ```
+///
+/// Responses that already have a `Content-Language` header are left as they are.
+pub struct I18nFairing;
+
+impl Fairing for I18nFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Content-Language and Vary",
+            kind: Kind::Response,
+        }
+    }
+
+    fn on_response(&self, req: &Request, res: &mut Response) {
+        if let Negotiated(Some(lang)) = req.local_cache(|| Negotiated(None)) {
+            if !res.headers().contains("Content-Language") {
+                res.set_raw_header("Content-Language", *lang);
+            }
+            if !varies_on_accept_language(res.headers().get("Vary")) {
+                res.adjoin_raw_header("Vary", "Accept-Language");
+            }
+        }
+    }
+}
+
+/// A fairing loading translations as configured in the `i18n` table of `Rocket.toml`, and managing
+/// them when the application is launched, so that the supported languages can change without
+/// recompiling it.
+///
+/// ```toml
+/// [global.i18n]
+/// langs = ["de", "en"]
+/// dir = "locales"
+/// default_lang = "de"
+/// ```
+///
+/// ```rust,ignore
+/// rocket::ignite()
+///     .attach(rocket_i18n::TranslationsFairing::new("plume"))
+/// ```
+///
+/// `langs` is required, and `dir` is `translations` by default. `default_lang` is managed as an
+/// `I18nConfig`, unless one is already managed: to use other options too, manage your own
+/// `I18nConfig` before attaching the fairing. The launch is aborted if the table is missing, or if
+/// a catalog can't be loaded.
+///
+/// Requires the `gettext` feature.
+#[cfg(feature = "gettext")]
+pub struct TranslationsFairing {
+    domain: String,
+}
+
+#[cfg(feature = "gettext")]
+impl TranslationsFairing {
+    /// Loads the catalogs of a gettext domain.
+    pub fn new<S: Into<String>>(domain: S) -> TranslationsFairing {
+        TranslationsFairing {
+            domain: domain.into(),
+        }
+    }
+}
+
+#[cfg(feature = "gettext")]
+impl Fairing for TranslationsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Translations from Rocket.toml",
+            kind: Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let settings = match FileSettings::read(rocket.config()) {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!("Invalid i18n configuration in Rocket.toml: {}", e);
+                return Err(rocket);
+            }
+        };
+        let translations = match Loader::new(&self.domain)
+            .dir(&settings.dir)
+            .try_load(settings.langs)
+        {
+            Ok(translations) => translations,
+            Err(e) => {
+                error!("{}", e);
+                return Err(rocket);
+            }
+        };
+
+        let rocket = rocket.manage(translations);
+        if settings.default_lang.is_none() || rocket.state::<I18nConfig>().is_some() {
+            return Ok(rocket);
+        }
+        Ok(rocket.manage(I18nConfig {
+            default_lang: settings.default_lang,
+            ..Default::default()
+        }))
+    }
+}
+
+/// The `i18n` table of `Rocket.toml`, see `TranslationsFairing`.
+#[cfg(feature = "gettext")]
+struct FileSettings {
+    langs: Vec<&'static str>,
+    dir: String,
+    default_lang: Option<String>,
+}
+
+#[cfg(feature = "gettext")]
+impl FileSettings {
+    fn read(config: &Config) -> Result<FileSettings, String> {
+        let table = config
+            .get_table("i18n")
+            .map_err(|_| "there is no i18n table".to_owned())?;
+        let string = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_str()
+                .map(|s| Some(s.to_owned()))
+                .ok_or_else(|| format!("{} should be a string", key)),
+        };
+        let langs = table
+            .get("langs")
+            .ok_or_else(|| "langs is missing".to_owned())?
+            .as_array()
+            .and_then(|langs| langs.iter().map(Value::as_str).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| "langs should be an array of strings".to_owned())?;
+        Ok(FileSettings {
+            langs: langs.into_iter().map(|l| leak(l.to_owned())).collect(),
+            dir: string("dir")?.unwrap_or_else(|| "translations".to_owned()),
+            default_lang: string("default_lang")?,
+        })
+    }
+}
+
+/// A catcher for InternalServerError responses, listing the available languages as JSON if the
+/// request asked for it, and as HTML otherwise.
+///
+/// Requests that can't be translated fail with this status, so you can register it to get
+/// meaningful error pages instead of Rocket's default ones:
+///
+/// ```rust,ignore
+/// rocket::ignite()
+///     .manage(translations)
+///     .register(vec![rocket_i18n::catcher()])
+/// ```
+///
+/// Note that it will be used for every InternalServerError of your application.
+pub fn catcher() -> Catcher {
+    Catcher::new(500, missing_translations)
+}
+
+fn missing_translations<'r>(req: &'r Request) -> response::Result<'r> {
+    let available: Vec<_> = match req.guard::<State<Translations>>().succeeded() {
+        Some(langs) => langs.iter().map(|l| l.0).collect(),
+        #[cfg(feature = "gettext")]
+        None => req
+            .guard::<State<ReloadableTranslations>>()
+            .succeeded()
+            .map(|r| r.snapshot().iter().map(|l| l.0).collect())
+            .unwrap_or_default(),
+        #[cfg(not(feature = "gettext"))]
+        None => Vec::new(),
+    };
+    let body = ErrorBody::new(req.headers().get_one("Accept"), &available);
+
+    Response::build()
+        .status(Status::InternalServerError)
+        .raw_header("Content-Type", body.content_type())
+        .sized_body(Cursor::new(body.content))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use rocket::{http::Header, local::Client};
+
+    fn lang<'r>(req: &'r Request, _: Data) -> handler::Outcome<'r> {
+        match req.guard::<I18n>() {
+            Outcome::Success(i18n) => handler::Outcome::from(req, i18n.lang),
+            _ => handler::Outcome::failure(Status::InternalServerError),
+        }
+    }
+
+    fn client(config: I18nConfig) -> Client {
+        let rocket = rocket::ignite()
+            .manage(testing::translations(&["en", "fr"]))
+            .manage(config)
+            .mount("/", vec![set_lang(), Route::new(Method::Get, "/", lang)]);
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn the_language_cookie_is_plain_without_a_key() {
+        let client = client(I18nConfig::builder().cookie("lang").build());
+
+        let res = client.post("/lang").body("lang=fr").dispatch();
+        assert_eq!(res.status(), Status::SeeOther);
+        let cookie = res.headers().get_one("Set-Cookie").unwrap();
+        assert!(cookie.starts_with("lang=fr;"));
+        assert_eq!(client.get("/").dispatch().body_string().unwrap(), "fr");
+
+        let set = client.get("/").cookie(Cookie::new("lang", "fr"));
+        assert_eq!(set.dispatch().body_string().unwrap(), "fr");
+    }
+
+    #[test]
+    fn the_language_cookie_is_private_with_a_key() {
+        let config = I18nConfig::builder()
+            .cookie("lang")
+            .cookie_key(&b"0123456789abcdef0123456789abcdef"[..])
+            .build();
+        let client = client(config);
+
+        let res = client.post("/lang").body("lang=fr").dispatch();
+        assert_eq!(res.status(), Status::SeeOther);
+        let cookie = res.headers().get_one("Set-Cookie").unwrap();
+        assert!(cookie.starts_with("lang=") && !cookie.starts_with("lang=fr;"));
+        assert_eq!(client.get("/").dispatch().body_string().unwrap(), "fr");
+
+        let forged = client.get("/").cookie(Cookie::new("lang", "fr"));
+        assert_eq!(forged.dispatch().body_string().unwrap(), "en");
+    }
+
+    #[test]
+    fn the_user_resolver_comes_before_accept_language() {
+        let translations = testing::translations(&["en", "fr", "de"]).with_user_resolver(|req| {
+            let lang = req.cookie("session").map(|s| if s == "42" { "de" } else { "fr" });
+            async move { lang.map(str::to_owned) }
+        });
+        let rocket = rocket::ignite()
+            .manage(translations)
+            .mount("/", vec![Route::new(Method::Get, "/", lang)]);
+        let client = Client::new(rocket).unwrap();
+
+        let lang = |session: Option<&str>| {
+            let mut req = client.get("/").header(Header::new("Accept-Language", "en"));
+            if let Some(session) = session {
+                req = req.cookie(Cookie::new("session", session.to_owned()));
+            }
+            req.dispatch().body_string().unwrap()
+        };
+        assert_eq!(lang(Some("42")), "de");
+        assert_eq!(lang(Some("7")), "fr");
+        assert_eq!(lang(None), "en");
+    }
+
+    #[test]
+    fn the_fairing_tells_the_language_of_translated_responses() {
+        fn untranslated<'r>(req: &'r Request, _: Data) -> handler::Outcome<'r> {
+            handler::Outcome::from(req, "Hello")
+        }
+        let rocket = rocket::ignite()
+            .manage(testing::translations(&["en", "fr"]))
+            .attach(I18nFairing)
+            .mount(
+                "/",
+                vec![
+                    Route::new(Method::Get, "/", lang),
+                    Route::new(Method::Get, "/hello", untranslated),
+                ],
+            );
+        let client = Client::new(rocket).unwrap();
+
+        let res = testing::rocket_request(&client, Method::Get, "/", "fr-FR, en;q=0.5").dispatch();
+        assert_eq!(res.headers().get_one("Content-Language"), Some("fr"));
+        assert_eq!(res.headers().get_one("Vary"), Some("Accept-Language"));
+
+        let res = testing::rocket_request(&client, Method::Get, "/hello", "fr").dispatch();
+        assert_eq!(res.headers().get_one("Content-Language"), None);
+        assert_eq!(res.headers().get_one("Vary"), None);
+    }
+
+    #[test]
+    fn the_default_guard_never_fails() {
+        fn default_lang<'r>(req: &'r Request, _: Data) -> handler::Outcome<'r> {
+            let i18n = req.guard::<DefaultI18n>().unwrap();
+            let negotiated = req.guard::<Option<I18n>>().unwrap().is_some();
+            handler::Outcome::from(req, format!("{} {}", i18n.lang, negotiated))
         }
+        let get = |rocket: rocket::Rocket| {
+            let rocket = rocket.mount("/", vec![Route::new(Method::Get, "/", default_lang)]);
+            let client = Client::new(rocket).unwrap();
+            let mut res = testing::rocket_request(&client, Method::Get, "/", "fr").dispatch();
+            res.body_string().unwrap()
+        };
+        assert_eq!(get(rocket::ignite()), "en false");
+        let langs = testing::translations(&["de", "fr"]);
+        assert_eq!(get(rocket::ignite().manage(langs)), "fr true");
+    }
+
+    #[test]
+    fn the_guard_fails_without_translations() {
+        fn error<'r>(req: &'r Request, _: Data) -> handler::Outcome<'r> {
+            match req.guard::<I18n>() {
+                Outcome::Failure((status, e)) => {
+                    handler::Outcome::from(req, format!("{} {}", status.code, e))
+                }
+                _ => handler::Outcome::from(req, "translated".to_owned()),
+            }
+        }
+        let rocket = rocket::ignite()
+            .register(vec![catcher()])
+            .mount(
+                "/",
+                vec![
+                    Route::new(Method::Get, "/", lang),
+                    Route::new(Method::Get, "/error", error),
+                ],
+            );
+        let client = Client::new(rocket).unwrap();
+
+        let mut res = client.get("/error").dispatch();
+        assert_eq!(res.body_string().unwrap(), format!("500 {}", GuardError::MissingState));
+
+        let json = Header::new("Accept", "application/json");
+        let mut res = client.get("/").header(json).dispatch();
+        assert_eq!(res.status(), Status::InternalServerError);
+        assert_eq!(res.headers().get_one("Content-Type"), Some("application/json"));
+        assert!(res.body_string().unwrap().contains(r#""available":[]"#));
+    }
+
+    #[test]
+    #[cfg(feature = "gettext")]
+    fn translations_are_loaded_from_the_configuration() {
+        use crate::tests::{mo_file, temp_dir};
+        use rocket::{config::Environment, error::LaunchErrorKind};
+        use std::{collections::BTreeMap, fs};
+
+        let dir = temp_dir("rocket-toml");
+        for (lang, hello) in &[("en", "Hello"), ("de", "Hallo")] {
+            let path = dir.join(lang).join("LC_MESSAGES");
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("app.mo"), mo_file(None, hello)).unwrap();
+        }
+        let rocket = |langs: &[&str]| {
+            let mut table = BTreeMap::new();
+            table.insert("dir".to_owned(), Value::from(dir.to_str().unwrap()));
+            table.insert("default_lang".to_owned(), Value::from("de"));
+            let langs = langs.iter().map(|&l| Value::from(l)).collect::<Vec<_>>();
+            table.insert("langs".to_owned(), Value::Array(langs));
+            let config = Config::build(Environment::Development)
+                .extra("i18n", table)
+                .unwrap();
+            rocket::custom(config)
+                .attach(TranslationsFairing::new("app"))
+                .mount("/", vec![Route::new(Method::Get, "/", lang)])
+        };
+
+        let client = Client::new(rocket(&["en", "de"])).unwrap();
+        assert_eq!(client.get("/").dispatch().body_string().unwrap(), "de");
+        let res = testing::rocket_request(&client, Method::Get, "/", "en-US");
+        assert_eq!(res.dispatch().body_string().unwrap(), "en");
+
+        let fails = |rocket| match Client::new(rocket) {
+            Err(e) => matches!(e.kind(), LaunchErrorKind::FailedFairings(_)),
+            Ok(_) => false,
+        };
+        assert!(fails(rocket(&["en", "fr"])));
+        let without_table = rocket::custom(Config::development());
+        assert!(fails(without_table.attach(TranslationsFairing::new("app"))));
     }
 }
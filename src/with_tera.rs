@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tera::{Error, Result, Tera, Value};
+
+#[cfg(feature = "format")]
+use crate::format;
+use crate::Translations;
+
+/// Registers the `tr` and `tr_n` functions in a Tera instance, to translate messages in dynamic
+/// templates.
+///
+/// Tera functions don't have access to the request, so its language has to be given to them:
+/// add `i18n.lang` to the context of your templates.
+///
+/// ```rust,ignore
+/// // With rocket_dyn_templates
+/// let translations = rocket_i18n::i18n("plume", vec!["en", "fr"]);
+/// Template::custom(move |engines| {
+///     rocket_i18n::register_tera(&mut engines.tera, translations.clone())
+/// })
+/// ```
+///
+/// ```text
+/// {{ tr(lang=lang, msg="Hello, world!") }}
+/// {{ tr(lang=lang, msg="Open", context="file menu") }}
+/// {{ tr_n(lang=lang, msg="One new message", plural="{0} new messages", n=count) }}
+/// ```
+///
+/// Like `I18n::plural`, `tr_n` replaces `{0}` with the number. Messages are left untranslated
+/// if the language is not supported.
+///
+/// With the `format` feature, a `currency` function formats amounts of money like
+/// `I18n::format_currency`: `{{ currency(lang=lang, amount=price, currency="EUR") }}`.
+///
+/// Requires the `tera` feature.
+pub fn register_tera(tera: &mut Tera, translations: Translations) {
+    let translations = Arc::new(translations);
+
+    let langs = translations.clone();
+    tera.register_function("tr", move |args: &HashMap<String, Value>| {
+        let msg = arg(args, "tr", "msg")?;
+        let i18n = langs.for_request(arg(args, "tr", "lang")?);
+        let translated = match (i18n, args.get("context").and_then(Value::as_str)) {
+            (Some(i18n), Some(ctx)) => i18n.catalog.pgettext(ctx, msg).to_owned(),
+            (Some(i18n), None) => i18n.catalog.gettext(msg).to_owned(),
+            (None, _) => msg.to_owned(),
+        };
+        Ok(Value::String(translated))
+    });
+
+    tera.register_function("tr_n", move |args: &HashMap<String, Value>| {
+        let msg = arg(args, "tr_n", "msg")?;
+        let plural = arg(args, "tr_n", "plural")?;
+        let n = args
+            .get("n")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Error::msg("tr_n: `n` must be a positive integer"))?;
+        let translated = match translations.for_request(arg(args, "tr_n", "lang")?) {
+            Some(i18n) => i18n.plural(msg, plural, n),
+            None if n == 1 => msg.replace("{0}", "1"),
+            None => plural.replace("{0}", &n.to_string()),
+        };
+        Ok(Value::String(translated))
+    });
+
+    #[cfg(feature = "format")]
+    tera.register_function("currency", |args: &HashMap<String, Value>| {
+        let amount = args
+            .get("amount")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| Error::msg("currency: `amount` must be a number"))?;
+        Ok(Value::String(format::format_currency(
+            arg(args, "currency", "lang")?,
+            amount,
+            arg(args, "currency", "currency")?,
+        )))
+    });
+}
+
+/// A string argument of a function, that is required.
+fn arg<'a>(args: &'a HashMap<String, Value>, function: &str, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::msg(format!("{}: missing `{}` argument", function, name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CatalogBuilder;
+    use tera::Context;
+
+    /// Renders a template with the translation functions, in `lang`.
+    fn render(template: &str, lang: &str) -> Result<String> {
+        let fr = CatalogBuilder::new()
+            .msg("Hello", "Bonjour")
+            .msg_in_context("file menu", "Open", "Ouvrir")
+            .plural("One message", "{0} messages", &["Un message", "{0} messages"])
+            .plural_rule(|n| if n > 1 { 1 } else { 0 })
+            .build();
+        let mut tera = Tera::default();
+        register_tera(&mut tera, Translations::from(vec![("fr", fr)]));
+        tera.add_raw_template("test", template)?;
+        let mut context = Context::new();
+        context.insert("lang", lang);
+        context.insert("count", &3);
+        tera.render("test", &context)
+    }
+
+    #[test]
+    fn messages_are_translated_in_templates() {
+        let template = concat!(
+            r#"{{ tr(lang=lang, msg="Hello") }}, "#,
+            r#"{{ tr(lang=lang, msg="Open", context="file menu") }}, "#,
+            r#"{{ tr_n(lang=lang, msg="One message", plural="{0} messages", n=count) }}"#,
+        );
+        assert_eq!(render(template, "fr").unwrap(), "Bonjour, Ouvrir, 3 messages");
+        assert_eq!(render(template, "de").unwrap(), "Hello, Open, 3 messages");
+        let one = r#"{{ tr_n(lang=lang, msg="One message", plural="{0} messages", n=0) }}"#;
+        assert_eq!(render(one, "fr").unwrap(), "Un message");
+        assert!(render(r#"{{ tr(msg="Hello") }}"#, "fr").is_err());
+        assert!(render(r#"{{ tr_n(lang=lang, msg="One", plural="Many", n=-1) }}"#, "fr").is_err());
+    }
+
+    #[cfg(feature = "format")]
+    #[test]
+    fn amounts_are_formatted_in_templates() {
+        let template = r#"{{ currency(lang=lang, amount=1234.5, currency="EUR") }}"#;
+        assert_eq!(render(template, "fr").unwrap(), "1\u{202f}234,50\u{a0}€");
+        assert_eq!(render(template, "en").unwrap(), "€1,234.50");
+        assert!(render(r#"{{ currency(lang=lang, amount="1", currency="EUR") }}"#, "en").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use crate::{
+    negotiation::{negotiate, RequestInfo},
+    I18n, I18nConfig, Translations,
+};
+
+use tide::{utils::async_trait, Middleware, Next, Request};
+
+/// A tide middleware negotiating the language of each request, and storing the corresponding
+/// `I18n` in its extensions, where `RequestExt::i18n` can find it.
+///
+/// ```rust,ignore
+/// let mut app = tide::new();
+/// app.with(I18nMiddleware::new(rocket_i18n::i18n("your-domain", vec!["en", "fr"])));
+/// app.at("/").get(|req: Request<()>| async move {
+///     let i18n = req.i18n().ok_or_else(|| tide::Error::from_str(500, "No translations"))?;
+///     Ok(i18n!(i18n.catalog, "Hello, world!"))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct I18nMiddleware {
+    langs: Arc<Translations>,
+    config: Option<Arc<I18nConfig>>,
+}
+
+impl I18nMiddleware {
+    /// Negotiates the language of requests with the default options.
+    pub fn new(translations: Translations) -> I18nMiddleware {
+        I18nMiddleware {
+            langs: Arc::new(translations),
+            config: None,
+        }
+    }
+
+    /// Negotiates the language of requests with the given options.
+    pub fn with_config(translations: Translations, config: I18nConfig) -> I18nMiddleware {
+        I18nMiddleware {
+            langs: Arc::new(translations),
+            config: Some(Arc::new(config)),
+        }
+    }
+}
+
+#[async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for I18nMiddleware {
+    async fn handle(&self, mut req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let langs = &*self.langs;
+        let config = self.config.as_deref();
+        let user = langs.resolve_user(|| {
+            req.iter()
+                .map(|(name, values)| (name.as_str().to_owned(), values.last().as_str().to_owned()))
+                .collect()
+        });
+        let user = match user {
+            Some(user) => user.await,
+            None => None,
+        };
+        let i18n = {
+            let header = |name: &str| req.header(name).map(|v| v.last().as_str());
+            let cookies = |name: &str| req.cookie(name).map(|c| c.value().to_owned());
+            let url = req.url();
+            let request = RequestInfo::new(config, &header, &cookies, url.query(), url.path())
+                .with_user(user);
+            langs.for_request(negotiate(langs, config, &request))
+        };
+
+        if let Some(i18n) = i18n {
+            req.set_ext(i18n);
+        }
+        Ok(next.run(req).await)
+    }
+}
+
+/// Gives access to the `I18n` negotiated by `I18nMiddleware`.
+pub trait RequestExt {
+    /// The `I18n` of this request.
+    ///
+    /// `None` if `I18nMiddleware` is not used, or if the negotiated language has no catalog.
+    fn i18n(&self) -> Option<&I18n>;
+}
+
+impl<State> RequestExt for Request<State> {
+    fn i18n(&self) -> Option<&I18n> {
+        self.ext::<I18n>()
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use tide::http::{Method, Url};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn the_negotiated_language_is_stored_in_the_request() {
+        let mut app = tide::new();
+        let config = I18nConfig::builder().cookie("lang").build();
+        let translations = testing::translations(&["en", "fr", "de"]);
+        app.with(I18nMiddleware::with_config(translations, config));
+        app.at("/").get(|req: Request<()>| async move {
+            Ok(req.i18n().map_or("none", |i18n| i18n.lang))
+        });
+
+        let lang = |cookie: Option<&str>| {
+            let url = Url::parse("http://example.com/").unwrap();
+            let mut req = tide::http::Request::new(Method::Get, url);
+            req.insert_header("Accept-Language", "fr");
+            if let Some(cookie) = cookie {
+                req.insert_header("Cookie", cookie);
+            }
+            block_on(async {
+                let mut res: tide::http::Response = app.respond(req).await.unwrap();
+                res.body_string().await.unwrap()
+            })
+        };
+        assert_eq!(lang(None), "fr");
+        assert_eq!(lang(Some("lang=de")), "de");
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use crate::{
+    negotiation::{cookie_value, negotiate, RequestInfo},
+    I18nConfig, Translations,
+};
+
+use http::{header, Request};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A tower layer negotiating the language of each request, and storing the corresponding `I18n`
+/// in its extensions.
+///
+/// It works with any tower-based stack (axum, tonic, hyper…):
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/", get(|Extension(i18n): Extension<I18n>| async move {
+///         i18n!(i18n.catalog, "Hello, world!")
+///     }))
+///     .layer(I18nLayer::new(rocket_i18n::i18n("your-domain", vec!["en", "fr"])));
+/// ```
+#[derive(Clone)]
+pub struct I18nLayer {
+    langs: Arc<Translations>,
+    config: Option<Arc<I18nConfig>>,
+}
+
+impl I18nLayer {
+    /// Negotiates the language of requests with the default options.
+    pub fn new(translations: Translations) -> I18nLayer {
+        I18nLayer {
+            langs: Arc::new(translations),
+            config: None,
+        }
+    }
+
+    /// Negotiates the language of requests with the given options.
+    pub fn with_config(translations: Translations, config: I18nConfig) -> I18nLayer {
+        I18nLayer {
+            langs: Arc::new(translations),
+            config: Some(Arc::new(config)),
+        }
+    }
+}
+
+impl<S> Layer<S> for I18nLayer {
+    type Service = I18nService<S>;
+
+    fn layer(&self, inner: S) -> I18nService<S> {
+        I18nService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service created by `I18nLayer`.
+#[derive(Clone)]
+pub struct I18nService<S> {
+    inner: S,
+    layer: I18nLayer,
+}
+
+impl<S, B> Service<Request<B>> for I18nService<S>
+where
+    S: Service<Request<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        // The service that was polled ready is the one that should be called.
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        let user = layer.langs.resolve_user(|| {
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                })
+                .collect()
+        });
+
+        Box::pin(async move {
+            let user = match user {
+                Some(user) => user.await,
+                None => None,
+            };
+            let langs = &*layer.langs;
+            let config = layer.config.as_deref();
+            let i18n = {
+                let headers = req.headers();
+                let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+                let cookies = |name: &str| {
+                    headers
+                        .get_all(header::COOKIE)
+                        .iter()
+                        .filter_map(|v| v.to_str().ok())
+                        .find_map(|cookies| cookie_value(cookies, name))
+                        .map(str::to_owned)
+                };
+                let uri = req.uri();
+                let request = RequestInfo::new(config, &header, &cookies, uri.query(), uri.path())
+                    .with_user(user);
+                langs.for_request(negotiate(langs, config, &request))
+            };
+
+            if let Some(i18n) = i18n {
+                req.extensions_mut().insert(i18n);
+            }
+            inner.call(req).await
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{testing, I18n};
+    use std::convert::Infallible;
+
+    /// Answers with the language negotiated by `I18nLayer`.
+    #[derive(Clone)]
+    struct Lang;
+
+    impl Service<Request<()>> for Lang {
+        type Response = Option<&'static str>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Option<&'static str>, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            std::future::ready(Ok(req.extensions().get::<I18n>().map(|i18n| i18n.lang)))
+        }
+    }
+
+    #[test]
+    fn the_user_resolver_is_awaited() {
+        let translations = testing::translations(&["en", "fr"])
+            .with_user_resolver(|req| {
+                let lang = req.header("X-User").map(str::to_owned);
+                async move { lang }
+            });
+        let mut service = I18nLayer::new(translations).layer(Lang);
+
+        let lang = |service: &mut I18nService<Lang>, user: Option<&str>| {
+            let mut req = Request::builder().header("Accept-Language", "en");
+            if let Some(user) = user {
+                req = req.header("X-User", user);
+            }
+            let res = service.call(req.body(()).unwrap());
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(res)
+                .unwrap()
+        };
+        assert_eq!(lang(&mut service, Some("fr")), Some("fr"));
+        assert_eq!(lang(&mut service, Some("ja")), Some("en"));
+        assert_eq!(lang(&mut service, None), Some("en"));
+    }
+}